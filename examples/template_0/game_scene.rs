@@ -15,7 +15,7 @@ use ranger::{
         node_properties::NodeData,
         scenes::scene_manager::{GlobalSceneData, SceneManager},
     },
-    rendering::color::Palette,
+    rendering::{color::Palette, stroke::LineStyle},
     world::World,
 };
 
@@ -91,6 +91,7 @@ impl GameScene {
         match bword.as_any().downcast_ref::<VectorTextNode>() {
             Some(vtn) => {
                 vtn.set_text(&String::from("RANGER IS A GO!"));
+                vtn.set_line_style(LineStyle::AntiAliased);
             }
             None => panic!("Downcast failed for VectorTextNode"),
         };
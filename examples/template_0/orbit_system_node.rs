@@ -1,5 +1,5 @@
 use std::any::Any;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use ranger::{
@@ -38,7 +38,7 @@ pub struct OrbitSystemNode {
     // Transformed vertices
     bucket: RefCell<Vec<Point>>,
 
-    color: Palette,
+    color: Cell<Palette>,
 }
 
 impl Drop for OrbitSystemNode {
@@ -62,7 +62,7 @@ impl OrbitSystemNode {
             angle_motion: RefCell::new(AngularMotion::new()),
             vertices: Vec::new(),
             bucket: RefCell::new(Vec::new()),
-            color: Palette::DEFAULT(),
+            color: Cell::new(Palette::DEFAULT()),
         };
 
         OrbitSystemNode::build(&mut tn, world);
@@ -122,10 +122,6 @@ impl OrbitSystemNode {
 
         orbit.angle_motion.borrow_mut().set_step_value(2.0);
     }
-
-    pub fn set_color(&mut self, color: Palette) {
-        self.color = color;
-    }
 }
 
 impl NodeTrait for OrbitSystemNode {
@@ -144,6 +140,10 @@ impl NodeTrait for OrbitSystemNode {
         &self.data
     }
 
+    fn set_color(&self, color: Palette) {
+        self.color.set(color);
+    }
+
     // --------------------------------------------------------
     // Rendering
     // --------------------------------------------------------
@@ -155,7 +155,7 @@ impl NodeTrait for OrbitSystemNode {
             // self.data().borrow_mut().node.set_dirty(false);
         }
 
-        context.set_draw_color(&self.color);
+        context.set_draw_color(&self.color.get());
         context.render_rectangle(&self.bucket);
 
         // Draw AABB box for debugging
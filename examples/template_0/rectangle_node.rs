@@ -1,5 +1,5 @@
 use std::any::Any;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use ranger::{
@@ -10,7 +10,11 @@ use ranger::{
         node_properties::NodeData,
         scenes::scene_manager::{GlobalSceneData, IOEvent, IOEventData, SceneManager},
     },
-    rendering::{color::Palette, render_context::Context},
+    rendering::{
+        color::Palette,
+        render_context::Context,
+        stroke::{LineJoin, StrokeStyle},
+    },
     world::World,
 };
 
@@ -31,7 +35,7 @@ pub struct RectangleNode {
     // Transformed vertices
     bucket: RefCell<Vec<Point>>,
 
-    color: Palette,
+    color: Cell<Palette>,
 }
 
 impl Drop for RectangleNode {
@@ -53,7 +57,7 @@ impl RectangleNode {
             parent: Rc::new(RefCell::new(parent)),
             vertices: Vec::new(),
             bucket: RefCell::new(Vec::new()),
-            color: Palette::DEFAULT(),
+            color: Cell::new(Palette::DEFAULT()),
         };
 
         RectangleNode::build(&mut tn, world);
@@ -85,10 +89,6 @@ impl RectangleNode {
             b.push(Point::new());
         }
     }
-
-    pub fn set_color(&mut self, color: Palette) {
-        self.color = color;
-    }
 }
 
 impl NodeTrait for RectangleNode {
@@ -107,6 +107,10 @@ impl NodeTrait for RectangleNode {
         &self.data
     }
 
+    fn set_color(&self, color: Palette) {
+        self.color.set(color);
+    }
+
     // --------------------------------------------------------
     // Rendering
     // --------------------------------------------------------
@@ -117,9 +121,17 @@ impl NodeTrait for RectangleNode {
             self.set_node_dirty(false);
         }
 
-        context.set_draw_color(&self.color);
+        context.set_draw_color(&self.color.get());
         context.render_rectangle(&self.bucket);
 
+        // Outline
+        context.set_draw_color(&Palette::WHITE(200));
+        context.render_polyline(
+            &self.bucket,
+            true,
+            &StrokeStyle::new(3.0).with_join(LineJoin::Round),
+        );
+
         // Draw AABB box for debugging
         Nodes::draw_aabb(&self.bucket.borrow(), context);
     }
@@ -0,0 +1,322 @@
+use animation::keyframe::Track;
+use geometry::point::Point;
+use rendering::color::Palette;
+
+// Whole cycles in one advance are skipped rather than stepped through, so
+// a huge dt can't stall. Their events still fire once per cycle, for up to
+// this many cycles.
+const MAX_EVENT_CYCLES: f64 = 16.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayMode {
+    /// Play once and hold the last frame.
+    Once,
+    /// Restart from the beginning once the end is reached.
+    Loop,
+    /// Alternate between playing forwards and backwards.
+    PingPong,
+}
+
+/// A named marker that fires when playback crosses `time`.
+#[derive(Debug, Clone)]
+pub struct ClipEvent {
+    pub time: f64,
+    pub name: String,
+}
+
+/// The tracks that animate a single node. The node is located by `path`
+/// relative to the node the clip is bound to, for example, "layer/ship".
+/// Tracks that are `None` leave that property untouched.
+pub struct NodeTracks {
+    pub path: String,
+    pub position: Option<Track<Point>>,
+    /// Degrees
+    pub rotation: Option<Track<f64>>,
+    /// (sx, sy) stored as a Point
+    pub scale: Option<Track<Point>>,
+    pub visible: Option<Track<bool>>,
+    pub color: Option<Track<Palette>>,
+}
+
+impl NodeTracks {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            position: None,
+            rotation: None,
+            scale: None,
+            visible: None,
+            color: None,
+        }
+    }
+
+    pub fn duration(&self) -> f64 {
+        let mut duration = 0.0;
+        if let Some(ref t) = self.position {
+            duration = f64::max(duration, t.duration());
+        }
+        if let Some(ref t) = self.rotation {
+            duration = f64::max(duration, t.duration());
+        }
+        if let Some(ref t) = self.scale {
+            duration = f64::max(duration, t.duration());
+        }
+        if let Some(ref t) = self.visible {
+            duration = f64::max(duration, t.duration());
+        }
+        if let Some(ref t) = self.color {
+            duration = f64::max(duration, t.duration());
+        }
+        duration
+    }
+}
+
+/// A sampled snapshot of a NodeTracks at the clip's current time.
+#[derive(Debug, Clone, Copy)]
+pub struct NodePose {
+    pub position: Option<Point>,
+    pub rotation: Option<f64>,
+    pub scale: Option<Point>,
+    pub visible: Option<bool>,
+    pub color: Option<Palette>,
+}
+
+/// An AnimationClip is a set of keyframed node tracks plus playback state.
+/// Times are in the same units as the Scheduler's `dt` (milliseconds).
+pub struct AnimationClip {
+    name: String,
+
+    tracks: Vec<NodeTracks>,
+    events: Vec<ClipEvent>,
+
+    // By default the duration is the time of the last keyframe of any
+    // track, but it can be overridden.
+    duration: f64,
+
+    mode: PlayMode,
+    speed: f64,
+
+    // Playback
+    time: f64,
+    forward: bool,
+    playing: bool,
+}
+
+impl AnimationClip {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            tracks: Vec::new(),
+            events: Vec::new(),
+            duration: 0.0,
+            mode: PlayMode::Once,
+            speed: 1.0,
+            time: 0.0,
+            forward: true,
+            playing: false,
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn add_tracks(&mut self, tracks: NodeTracks) {
+        self.duration = f64::max(self.duration, tracks.duration());
+        self.tracks.push(tracks);
+    }
+
+    pub fn tracks(&self) -> &Vec<NodeTracks> {
+        &self.tracks
+    }
+
+    /// Events are kept ordered by time.
+    pub fn add_event(&mut self, time: f64, name: &str) {
+        let index = self.events.partition_point(|e| e.time <= time);
+        self.events.insert(
+            index,
+            ClipEvent {
+                time,
+                name: name.to_string(),
+            },
+        );
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    pub fn set_duration(&mut self, duration: f64) {
+        self.duration = duration;
+    }
+
+    pub fn mode(&self) -> PlayMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: PlayMode) {
+        self.mode = mode;
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// 1.0 is normal speed, 0.5 is half speed etc. Negative speeds are
+    /// treated as 0.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = f64::max(speed, 0.0);
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn is_forward(&self) -> bool {
+        self.forward
+    }
+
+    /// Starts (or restarts) playback from the beginning.
+    pub fn play(&mut self) {
+        self.time = 0.0;
+        self.forward = true;
+        self.playing = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn resume(&mut self) {
+        self.playing = true;
+    }
+
+    pub fn seek(&mut self, time: f64) {
+        self.time = f64::max(0.0, f64::min(time, self.duration));
+    }
+
+    /// Advances playback by `dt` (scaled by the speed) and appends the
+    /// names of any events crossed, in playback order, to `fired`.
+    pub fn advance(&mut self, dt: f64, fired: &mut Vec<String>) {
+        if !self.playing {
+            return;
+        }
+
+        if self.duration <= 0.0 {
+            self.playing = self.mode != PlayMode::Once;
+            return;
+        }
+
+        let mut remaining = dt * self.speed;
+        if !remaining.is_finite() {
+            return;
+        }
+
+        let period = match self.mode {
+            PlayMode::Once => f64::INFINITY,
+            PlayMode::Loop => self.duration,
+            PlayMode::PingPong => 2.0 * self.duration,
+        };
+        let cycles = (remaining / period).floor();
+        if cycles >= 1.0 {
+            // A whole cycle ends where it started, so step one to collect
+            // its events and repeat them for the rest.
+            let start = fired.len();
+            self.step(period, fired);
+            let events = fired[start..].to_vec();
+            for _ in 1..(cycles.min(MAX_EVENT_CYCLES) as usize) {
+                fired.extend_from_slice(&events);
+            }
+            remaining %= period;
+        }
+
+        self.step(remaining, fired);
+    }
+
+    fn step(&mut self, mut remaining: f64, fired: &mut Vec<String>) {
+        while remaining > 0.0 {
+            if self.forward {
+                let to_end = self.duration - self.time;
+                if remaining < to_end {
+                    self.fire_events(self.time, self.time + remaining, fired);
+                    self.time += remaining;
+                    break;
+                }
+
+                self.fire_events(self.time, self.duration, fired);
+                remaining -= to_end;
+                self.time = self.duration;
+
+                match self.mode {
+                    PlayMode::Once => {
+                        self.playing = false;
+                        break;
+                    }
+                    PlayMode::Loop => {
+                        self.time = 0.0;
+                        self.fire_events_at(0.0, fired);
+                    }
+                    PlayMode::PingPong => self.forward = false,
+                }
+            } else {
+                if remaining < self.time {
+                    self.fire_events_reverse(self.time - remaining, self.time, fired);
+                    self.time -= remaining;
+                    break;
+                }
+
+                self.fire_events_reverse(0.0, self.time, fired);
+                remaining -= self.time;
+                self.time = 0.0;
+                self.forward = true;
+            }
+        }
+    }
+
+    /// Samples the tracks at `index` at the current time.
+    pub fn sample(&self, index: usize) -> NodePose {
+        self.sample_at(index, self.time)
+    }
+
+    pub fn sample_at(&self, index: usize, time: f64) -> NodePose {
+        let tracks = &self.tracks[index];
+
+        NodePose {
+            position: tracks.position.as_ref().and_then(|t| t.sample(time)),
+            rotation: tracks.rotation.as_ref().and_then(|t| t.sample(time)),
+            scale: tracks.scale.as_ref().and_then(|t| t.sample(time)),
+            visible: tracks.visible.as_ref().and_then(|t| t.sample(time)),
+            color: tracks.color.as_ref().and_then(|t| t.sample(time)),
+        }
+    }
+
+    // Forward: events within (from, to]
+    fn fire_events(&self, from: f64, to: f64, fired: &mut Vec<String>) {
+        for event in self.events.iter() {
+            if event.time > from && event.time <= to {
+                fired.push(event.name.clone());
+            }
+        }
+    }
+
+    // Backward: events within [from, to) in descending order
+    fn fire_events_reverse(&self, from: f64, to: f64, fired: &mut Vec<String>) {
+        for event in self.events.iter().rev() {
+            if event.time >= from && event.time < to {
+                fired.push(event.name.clone());
+            }
+        }
+    }
+
+    fn fire_events_at(&self, time: f64, fired: &mut Vec<String>) {
+        for event in self.events.iter() {
+            if event.time == time {
+                fired.push(event.name.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use math::interpolation::{Easing, Lerp};
+
+/// How a keyframe's value transitions to the next keyframe's value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyInterpolation {
+    /// Hold the value until the next keyframe is reached.
+    Step,
+    Linear,
+    /// Linear interpolation shaped by a `cubic-bezier(x1, y1, x2, y2)` curve.
+    Bezier(f64, f64, f64, f64),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe<T: Lerp> {
+    pub time: f64,
+    pub value: T,
+    /// Interpolation used between this keyframe and the next.
+    pub interpolation: KeyInterpolation,
+}
+
+/// A Track is a time ordered collection of keyframes for a single value.
+/// Times are in the same units as the Scheduler's `dt` (milliseconds).
+pub struct Track<T: Lerp> {
+    keys: Vec<Keyframe<T>>,
+}
+
+impl<T: Lerp> Track<T> {
+    pub fn new() -> Self {
+        Self { keys: Vec::new() }
+    }
+
+    /// Inserts a keyframe keeping the keys ordered by time. A key at an
+    /// existing time replaces that key.
+    pub fn add_key(&mut self, time: f64, value: T, interpolation: KeyInterpolation) {
+        let key = Keyframe {
+            time,
+            value,
+            interpolation,
+        };
+
+        let index = self.keys.partition_point(|k| k.time < time);
+        if index < self.keys.len() && self.keys[index].time == time {
+            self.keys[index] = key;
+        } else {
+            self.keys.insert(index, key);
+        }
+    }
+
+    /// Builder style version of `add_key`
+    pub fn with_key(mut self, time: f64, value: T, interpolation: KeyInterpolation) -> Self {
+        self.add_key(time, value, interpolation);
+        self
+    }
+
+    pub fn keys(&self) -> &Vec<Keyframe<T>> {
+        &self.keys
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Time of the last keyframe.
+    pub fn duration(&self) -> f64 {
+        match self.keys.last() {
+            Some(key) => key.time,
+            None => 0.0,
+        }
+    }
+
+    /// Samples the track at `time`. Times before the first key or after
+    /// the last key hold the first or last value respectively.
+    pub fn sample(&self, time: f64) -> Option<T> {
+        let first = self.keys.first()?;
+        if time <= first.time {
+            return Some(first.value);
+        }
+
+        // Index of the first key whose time is > `time`
+        let next = self.keys.partition_point(|k| k.time <= time);
+        if next >= self.keys.len() {
+            return self.keys.last().map(|k| k.value);
+        }
+
+        let from = &self.keys[next - 1];
+        let to = &self.keys[next];
+        let t = (time - from.time) / (to.time - from.time);
+
+        let value = match from.interpolation {
+            KeyInterpolation::Step => from.value,
+            KeyInterpolation::Linear => T::lerp(&from.value, &to.value, t),
+            KeyInterpolation::Bezier(x1, y1, x2, y2) => T::lerp(
+                &from.value,
+                &to.value,
+                Easing::CubicBezier(x1, y1, x2, y2).ease(t),
+            ),
+        };
+
+        Some(value)
+    }
+}
+
+// Visibility is a discrete value so it always steps regardless of the
+// keyframe's interpolation.
+impl Lerp for bool {
+    fn lerp(from: &bool, to: &bool, t: f64) -> bool {
+        if t >= 1.0 {
+            *to
+        } else {
+            *from
+        }
+    }
+}
@@ -1 +1,4 @@
-pub mod motion;
\ No newline at end of file
+pub mod clip;
+pub mod keyframe;
+pub mod motion;
+pub mod sprite;
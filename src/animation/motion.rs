@@ -1,18 +1,60 @@
-use math::interpolation::Interpolation;
+use std::f64::consts::PI;
+use std::ops::Add;
 
+use math::interpolation::{Easing, Interpolation, Lerp};
+use rendering::color::Palette;
+
+/// A Motion is advanced by a node's `update(dt)` (i.e. the fixed
+/// Scheduler step) and sampled by the node's `interpolate(t)` during
+/// the visit pass. `t` is the interpolation between the previous and
+/// current update (0.0 -> 1.0).
+pub trait Motion {
+    type Value;
+
+    fn update(&mut self, dt: f64);
+    fn interpolate(&mut self, t: f64) -> Self::Value;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AngularUnits {
+    Degrees,
+    Radians,
+}
+
+impl AngularUnits {
+    /// A full revolution in these units.
+    pub fn period(&self) -> f64 {
+        match *self {
+            AngularUnits::Degrees => 360.0,
+            AngularUnits::Radians => 2.0 * PI,
+        }
+    }
+}
+
+// -----------------------------------------------------------------
+// Angular
+// -----------------------------------------------------------------
 pub struct AngularMotion {
     // From and To values
     from: f64,
     to: f64,
     step_value: f64,
 
-    // Caution:
-    // Interpolation can sometimes be a tiny bit off at the wrap boundary
-    // which renders as a small jitter at the boundary. This can
-    // most noticable for orbit type nodes where the error is
-    // magnified.
-    // It is enabled by default.
+    // Shapes `t` prior to interpolating. Defaults to Linear.
+    easing: Easing,
+
+    units: AngularUnits,
+
+    // When enabled `from`, `to` and interpolated values are kept within
+    // a single revolution [0, period). The range is shifted as a whole so
+    // the interpolation never jumps at the wrap boundary.
+    // It is disabled by default.
     auto_wrap: bool,
+
+    // When enabled `interpolate` travels the shortest arc between
+    // `from` and `to`, for example, 350 -> 10 passes through 0 rather
+    // than sweeping back through 180.
+    shortest_path: bool,
 }
 
 impl AngularMotion {
@@ -21,7 +63,10 @@ impl AngularMotion {
             from: 0.0,
             to: 0.0,
             step_value: 0.0,
+            easing: Easing::Linear,
+            units: AngularUnits::Degrees,
             auto_wrap: false,
+            shortest_path: false,
         }
     }
 
@@ -29,10 +74,23 @@ impl AngularMotion {
         self.auto_wrap = wrap;
     }
 
+    pub fn enable_shortest_path(&mut self, shortest: bool) {
+        self.shortest_path = shortest;
+    }
+
+    /// Degrees by default.
+    pub fn set_units(&mut self, units: AngularUnits) {
+        self.units = units;
+    }
+
     pub fn set_step_value(&mut self, value: f64) {
         self.step_value = value;
     }
 
+    pub fn set_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
     pub fn set(&mut self, from: f64, to: f64) {
         self.from = from;
         self.to = to;
@@ -40,36 +98,215 @@ impl AngularMotion {
 
     // step returns a the value between `from` and `to` given t = (0.0 -> 1.0)
     pub fn interpolate(&mut self, t: f64) -> f64 {
-        let mut value = Interpolation::lerp(self.from, self.to, t);
+        let t = self.easing.ease(t);
+        let period = self.units.period();
 
-        // Adjust value if wrapped
-        if self.auto_wrap {
-            if self.step_value > 0.0 {
-                if value >= 360.0 {
-                    // Wrap range back around
-                    self.from = 0.0;
-                    self.to = self.step_value;
-                    // Calc new value from the adjusted range
-                    value = Interpolation::lerp(self.from, self.to, t); //value - 360.0;
-                }
-            } else {
-                if value <= 0.0 {
-                    self.from = 359.0;
-                    self.to = 359.0 + self.step_value;
-                    value = Interpolation::lerp(self.from, self.to, t); //360.0 + value;
-                }
-            }
+        if self.shortest_path {
+            return Interpolation::lerp_angle(self.from, self.to, t, period);
         }
 
-        value
+        let value = Interpolation::lerp(self.from, self.to, t);
+
+        if self.auto_wrap {
+            Interpolation::wrap_angle(value, period)
+        } else {
+            value
+        }
     }
 
     pub fn update(&mut self, _dt: f64) {
         self.from = self.to;
-        self.to = self.to + self.step_value;
+
+        if self.auto_wrap {
+            // Shift the whole range so that `from` is within a revolution.
+            // `to` may extend past the boundary which is fine because the
+            // interpolated value is wrapped.
+            self.from = Interpolation::wrap_angle(self.from, self.units.period());
+        }
+
+        self.to = self.from + self.step_value;
     }
 
     pub fn to_string(&self) -> String {
         format!("{} -> {}", self.from, self.to)
     }
 }
+
+impl Motion for AngularMotion {
+    type Value = f64;
+
+    fn update(&mut self, dt: f64) {
+        AngularMotion::update(self, dt);
+    }
+
+    fn interpolate(&mut self, t: f64) -> f64 {
+        AngularMotion::interpolate(self, t)
+    }
+}
+
+// -----------------------------------------------------------------
+// Linear
+// -----------------------------------------------------------------
+/// Moves a value by `step_value` every update, for example, a position
+/// moving at a constant velocity.
+///
+/// ```ignore
+/// let mut scalar: LinearMotion<f64> = LinearMotion::new(0.0, 0.0);
+/// let mut point: LinearMotion<Point> = LinearMotion::new(Point::new(), Point::new());
+/// ```
+pub struct LinearMotion<T: Lerp + Add<Output = T>> {
+    from: T,
+    to: T,
+    step_value: T,
+
+    easing: Easing,
+}
+
+impl<T: Lerp + Add<Output = T>> LinearMotion<T> {
+    pub fn new(start: T, step_value: T) -> Self {
+        Self {
+            from: start,
+            to: start,
+            step_value,
+            easing: Easing::Linear,
+        }
+    }
+
+    pub fn set_step_value(&mut self, value: T) {
+        self.step_value = value;
+    }
+
+    pub fn set_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
+    pub fn set(&mut self, from: T, to: T) {
+        self.from = from;
+        self.to = to;
+    }
+
+    pub fn from(&self) -> T {
+        self.from
+    }
+
+    pub fn to(&self) -> T {
+        self.to
+    }
+
+    pub fn interpolate(&mut self, t: f64) -> T {
+        T::lerp(&self.from, &self.to, self.easing.ease(t))
+    }
+
+    pub fn update(&mut self, _dt: f64) {
+        self.from = self.to;
+        self.to = self.to + self.step_value;
+    }
+}
+
+impl<T: Lerp + Add<Output = T>> Motion for LinearMotion<T> {
+    type Value = T;
+
+    fn update(&mut self, dt: f64) {
+        LinearMotion::update(self, dt);
+    }
+
+    fn interpolate(&mut self, t: f64) -> T {
+        LinearMotion::interpolate(self, t)
+    }
+}
+
+// -----------------------------------------------------------------
+// Color
+// -----------------------------------------------------------------
+/// Fades from one color to another over a duration. The duration is in
+/// the same units as the `dt` given to `update` (i.e. the Scheduler's
+/// frame dt in milliseconds).
+pub struct ColorMotion {
+    from: Palette,
+    to: Palette,
+
+    duration: f64,
+    elapsed: f64,
+
+    // Progress (0.0 -> 1.0) at the previous and current update.
+    previous: f64,
+    current: f64,
+
+    easing: Easing,
+    looping: bool,
+}
+
+impl ColorMotion {
+    pub fn new(from: Palette, to: Palette, duration: f64) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            elapsed: 0.0,
+            previous: 0.0,
+            current: 0.0,
+            easing: Easing::Linear,
+            looping: false,
+        }
+    }
+
+    pub fn set(&mut self, from: Palette, to: Palette) {
+        self.from = from;
+        self.to = to;
+    }
+
+    pub fn set_duration(&mut self, duration: f64) {
+        self.duration = duration;
+    }
+
+    pub fn set_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
+    /// When looping the fade restarts from `from` once complete.
+    pub fn enable_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+        self.previous = 0.0;
+        self.current = 0.0;
+    }
+
+    pub fn is_complete(&self) -> bool {
+        !self.looping && self.current >= 1.0
+    }
+
+    pub fn interpolate(&mut self, t: f64) -> Palette {
+        let progress = Interpolation::lerp(self.previous, self.current, t);
+        Palette::lerp(&self.from, &self.to, self.easing.ease(progress))
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        if self.looping && self.current >= 1.0 {
+            self.reset();
+        }
+
+        self.previous = self.current;
+        self.elapsed += dt;
+
+        self.current = if self.duration > 0.0 {
+            f64::min(self.elapsed / self.duration, 1.0)
+        } else {
+            1.0
+        };
+    }
+}
+
+impl Motion for ColorMotion {
+    type Value = Palette;
+
+    fn update(&mut self, dt: f64) {
+        ColorMotion::update(self, dt);
+    }
+
+    fn interpolate(&mut self, t: f64) -> Palette {
+        ColorMotion::interpolate(self, t)
+    }
+}
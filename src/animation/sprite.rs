@@ -0,0 +1,243 @@
+// Frame (flip-book) animation for sprite sheets. A SpriteClip is a named
+// sequence of sheet frames each with its own duration. A SpriteAnimator
+// holds a set of clips and plays one at a time.
+// Durations are in the same units as the Scheduler's `dt` (milliseconds).
+
+use rendering::sprite_sheet::SpriteSheet;
+
+// Durations are clamped to this to guarantee progress.
+const MIN_FRAME_DURATION: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpriteFrame {
+    /// Index of the frame within the SpriteSheet
+    pub index: usize,
+    pub duration: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpriteClip {
+    name: String,
+    frames: Vec<SpriteFrame>,
+    looping: bool,
+}
+
+impl SpriteClip {
+    pub fn new(name: &str, looping: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            frames: Vec::new(),
+            looping,
+        }
+    }
+
+    /// A clip of `count` consecutive sheet frames starting at `first`,
+    /// each shown for `duration`.
+    pub fn from_range(
+        name: &str,
+        first: usize,
+        count: usize,
+        duration: f64,
+        looping: bool,
+    ) -> Self {
+        let mut clip = SpriteClip::new(name, looping);
+        for index in first..first + count {
+            clip.add_frame(index, duration);
+        }
+        clip
+    }
+
+    /// A clip of the sheet's named frames that start with `prefix`, e.g.
+    /// "walk_0", "walk_1" ... from an atlas. Frames are ordered by the
+    /// number their names end in, so "walk_10" follows "walk_9".
+    pub fn from_prefix(
+        name: &str,
+        sheet: &SpriteSheet,
+        prefix: &str,
+        duration: f64,
+        looping: bool,
+    ) -> Self {
+        let mut frames: Vec<(Option<u64>, &String, usize)> = sheet
+            .frame_names()
+            .filter(|&(frame, _)| frame.starts_with(prefix))
+            .map(|(frame, &index)| {
+                let digits = frame
+                    .bytes()
+                    .rev()
+                    .take_while(|b| b.is_ascii_digit())
+                    .count();
+                let number = frame[frame.len() - digits..].parse().ok();
+                (number, frame, index)
+            })
+            .collect();
+        frames.sort();
+
+        let mut clip = SpriteClip::new(name, looping);
+        for (_, _, index) in frames {
+            clip.add_frame(index, duration);
+        }
+        clip
+    }
+
+    pub fn add_frame(&mut self, index: usize, duration: f64) {
+        self.frames.push(SpriteFrame {
+            index,
+            duration: f64::max(duration, MIN_FRAME_DURATION),
+        });
+    }
+
+    pub fn with_frame(mut self, index: usize, duration: f64) -> Self {
+        self.add_frame(index, duration);
+        self
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn frames(&self) -> &Vec<SpriteFrame> {
+        &self.frames
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.frames.iter().map(|f| f.duration).sum()
+    }
+}
+
+pub struct SpriteAnimator {
+    clips: Vec<SpriteClip>,
+
+    // Index of the active clip
+    current: Option<usize>,
+    // Index into the active clip's frames
+    frame: usize,
+    elapsed: f64,
+
+    playing: bool,
+    finished: bool,
+}
+
+impl SpriteAnimator {
+    pub fn new() -> Self {
+        Self {
+            clips: Vec::new(),
+            current: None,
+            frame: 0,
+            elapsed: 0.0,
+            playing: false,
+            finished: false,
+        }
+    }
+
+    /// A clip with the same name as an existing clip replaces it.
+    pub fn add_clip(&mut self, clip: SpriteClip) {
+        match self.clips.iter().position(|c| c.name == clip.name) {
+            Some(index) => self.clips[index] = clip,
+            None => self.clips.push(clip),
+        }
+    }
+
+    pub fn clip(&self, name: &str) -> Option<&SpriteClip> {
+        self.clips.iter().find(|c| c.name == name)
+    }
+
+    /// Plays the named clip from its first frame. If the clip is already
+    /// playing it continues uninterrupted which means `play` can be called
+    /// every update, for example, from an input handler.
+    /// Returns false if no clip has that name.
+    pub fn play(&mut self, name: &str) -> bool {
+        let index = match self.clips.iter().position(|c| c.name == name) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        if self.current == Some(index) && self.playing {
+            return true;
+        }
+
+        self.current = Some(index);
+        self.restart();
+        true
+    }
+
+    /// Restarts the active clip from its first frame.
+    pub fn restart(&mut self) {
+        self.frame = 0;
+        self.elapsed = 0.0;
+        self.finished = false;
+        self.playing = self.current.is_some();
+    }
+
+    pub fn stop(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn resume(&mut self) {
+        self.playing = self.current.is_some() && !self.finished;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// True once a non-looping clip has shown its last frame for its full
+    /// duration.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn current_clip(&self) -> Option<&String> {
+        self.current.map(|i| &self.clips[i].name)
+    }
+
+    /// The sheet frame index to display, if a clip is active.
+    pub fn current_frame(&self) -> Option<usize> {
+        self.current
+            .and_then(|i| self.clips[i].frames.get(self.frame))
+            .map(|f| f.index)
+    }
+
+    /// Advances the active clip by `dt`. Returns true if the displayed
+    /// frame changed.
+    pub fn update(&mut self, dt: f64) -> bool {
+        if !self.playing {
+            return false;
+        }
+
+        let clip = match self.current {
+            Some(index) => &self.clips[index],
+            None => return false,
+        };
+
+        if clip.frames.is_empty() {
+            self.playing = false;
+            self.finished = true;
+            return false;
+        }
+
+        let start = self.frame;
+        self.elapsed += dt;
+
+        while self.elapsed >= clip.frames[self.frame].duration {
+            self.elapsed -= clip.frames[self.frame].duration;
+
+            if self.frame + 1 < clip.frames.len() {
+                self.frame += 1;
+            } else if clip.looping {
+                self.frame = 0;
+            } else {
+                // Hold the last frame
+                self.elapsed = 0.0;
+                self.playing = false;
+                self.finished = true;
+                break;
+            }
+        }
+
+        self.frame != start
+    }
+}
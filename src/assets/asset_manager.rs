@@ -0,0 +1,310 @@
+use std::any::{Any, TypeId};
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+use std::time::SystemTime;
+
+use rendering::atlas::AtlasError;
+use rendering::codecs::ImageError;
+
+// The AssetManager loads assets by path, relative to its root, and caches
+// them by type and path. Loading the same path again while any Handle to
+// it is alive returns the cached asset; once the last Handle is dropped
+// the asset is freed and the next load reads the file again.
+//
+// With watching enabled, on by default in debug builds, the World checks
+// once a second for files that changed on disk and reloads them in place.
+// Handles see the new value and can compare versions to rebuild anything
+// derived from it.
+
+#[derive(Debug)]
+pub enum AssetError {
+    Io(io::Error),
+    Image(ImageError),
+    Atlas(AtlasError),
+    /// A text asset (mesh, font...) couldn't be parsed.
+    Parse(String),
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AssetError::Io(ref err) => write!(f, "I/O error: {}", err),
+            AssetError::Image(ref err) => write!(f, "{}", err),
+            AssetError::Atlas(ref err) => write!(f, "{}", err),
+            AssetError::Parse(ref what) => write!(f, "Parse error: {}", what),
+        }
+    }
+}
+
+impl Error for AssetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            AssetError::Io(ref err) => Some(err),
+            AssetError::Image(ref err) => Some(err),
+            AssetError::Atlas(ref err) => Some(err),
+            AssetError::Parse(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for AssetError {
+    fn from(err: io::Error) -> Self {
+        AssetError::Io(err)
+    }
+}
+
+impl From<ImageError> for AssetError {
+    fn from(err: ImageError) -> Self {
+        AssetError::Image(err)
+    }
+}
+
+impl From<AtlasError> for AssetError {
+    fn from(err: AtlasError) -> Self {
+        AssetError::Atlas(err)
+    }
+}
+
+/// Anything that can be loaded from a file. See loaders.rs for the
+/// engine's own asset types.
+pub trait Asset: Sized + 'static {
+    fn load(path: &Path) -> Result<Self, AssetError>;
+}
+
+struct Slot<T> {
+    key: String,
+    value: RefCell<T>,
+    version: Cell<usize>,
+}
+
+/// A shared reference to a loaded asset.
+pub struct Handle<T> {
+    slot: Rc<Slot<T>>,
+}
+
+impl<T> Handle<T> {
+    /// Don't hold the borrow across a reload, i.e. past the end of a frame.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.slot.value.borrow()
+    }
+
+    /// Starts at 0 and goes up by one each time the asset is reloaded.
+    pub fn version(&self) -> usize {
+        self.slot.version.get()
+    }
+
+    /// The path, or name, the asset was loaded or inserted under.
+    pub fn key(&self) -> &str {
+        &self.slot.key
+    }
+
+    /// Whether both handles refer to the same asset.
+    pub fn ptr_eq(&self, other: &Handle<T>) -> bool {
+        Rc::ptr_eq(&self.slot, &other.slot)
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+// The manager only keeps a Weak to each asset, the Handles own it. The
+// functions are instantiated for the asset's type so entries of any type
+// can live in one map.
+struct Entry {
+    // None for inserted assets, which can't be reloaded.
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    slot: Box<dyn Any>,
+    alive: fn(&dyn Any) -> bool,
+    reload: fn(&dyn Any, &Path) -> Result<bool, AssetError>,
+}
+
+fn alive<T: Asset>(slot: &dyn Any) -> bool {
+    slot.downcast_ref::<Weak<Slot<T>>>()
+        .is_some_and(|weak| weak.strong_count() > 0)
+}
+
+// Returns false if the asset was already freed.
+fn reload<T: Asset>(slot: &dyn Any, path: &Path) -> Result<bool, AssetError> {
+    let slot = match slot.downcast_ref::<Weak<Slot<T>>>().and_then(Weak::upgrade) {
+        Some(slot) => slot,
+        None => return Ok(false),
+    };
+
+    let value = T::load(path)?;
+    *slot.value.borrow_mut() = value;
+    slot.version.set(slot.version.get() + 1);
+
+    Ok(true)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+pub struct AssetManager {
+    root: PathBuf,
+    entries: HashMap<(TypeId, String), Entry>,
+    watching: bool,
+}
+
+impl AssetManager {
+    /// Paths given to load() are relative to `root`, unless absolute.
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            entries: HashMap::new(),
+            watching: cfg!(debug_assertions),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Only affects assets loaded afterwards.
+    pub fn set_root<P: AsRef<Path>>(&mut self, root: P) {
+        self.root = root.as_ref().to_path_buf();
+    }
+
+    /// Returns the cached asset if it's still loaded, otherwise reads it.
+    pub fn load<T: Asset>(&mut self, path: &str) -> Result<Handle<T>, AssetError> {
+        if let Some(handle) = self.get(path) {
+            return Ok(handle);
+        }
+
+        let full = self.root.join(path);
+        let stamp = modified(&full);
+        let value = T::load(&full)?;
+        Ok(self.add(path, Some(full), stamp, value))
+    }
+
+    /// The asset if it's loaded, without touching the file system.
+    pub fn get<T: Asset>(&self, key: &str) -> Option<Handle<T>> {
+        let entry = self.entries.get(&(TypeId::of::<T>(), key.to_string()))?;
+        entry
+            .slot
+            .downcast_ref::<Weak<Slot<T>>>()
+            .and_then(Weak::upgrade)
+            .map(|slot| Handle { slot })
+    }
+
+    /// Adds an asset made in code, e.g. generated geometry, so it can be
+    /// shared by key like a loaded one. It is never reloaded.
+    pub fn insert<T: Asset>(&mut self, key: &str, value: T) -> Handle<T> {
+        self.add(key, None, None, value)
+    }
+
+    pub fn is_loaded<T: Asset>(&self, key: &str) -> bool {
+        self.get::<T>(key).is_some()
+    }
+
+    fn add<T: Asset>(
+        &mut self,
+        key: &str,
+        path: Option<PathBuf>,
+        modified: Option<SystemTime>,
+        value: T,
+    ) -> Handle<T> {
+        let slot = Rc::new(Slot {
+            key: key.to_string(),
+            value: RefCell::new(value),
+            version: Cell::new(0),
+        });
+
+        self.entries.insert(
+            (TypeId::of::<T>(), key.to_string()),
+            Entry {
+                path,
+                modified,
+                slot: Box::new(Rc::downgrade(&slot)),
+                alive: alive::<T>,
+                reload: reload::<T>,
+            },
+        );
+
+        Handle { slot }
+    }
+
+    /// Assets still referenced by a Handle.
+    pub fn len(&self) -> usize {
+        self.entries
+            .values()
+            .filter(|entry| (entry.alive)(entry.slot.as_ref()))
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets assets that are no longer referenced.
+    pub fn purge(&mut self) {
+        self.entries
+            .retain(|_, entry| (entry.alive)(entry.slot.as_ref()));
+    }
+
+    pub fn set_watching(&mut self, watching: bool) {
+        self.watching = watching;
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watching
+    }
+
+    /// Reads a loaded asset's file again, whether or not it changed.
+    pub fn reload<T: Asset>(&mut self, path: &str) -> Result<bool, AssetError> {
+        let entry = match self.entries.get_mut(&(TypeId::of::<T>(), path.to_string())) {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+        let full = match entry.path {
+            Some(ref full) => full.clone(),
+            None => return Ok(false),
+        };
+
+        entry.modified = modified(&full);
+        (entry.reload)(entry.slot.as_ref(), &full)
+    }
+
+    /// Reloads the assets whose files changed since they were (re)loaded,
+    /// returning each one's key and outcome. A failed reload keeps the
+    /// previous value and isn't retried until the file changes again.
+    pub fn reload_changed(&mut self) -> Vec<(String, Result<(), AssetError>)> {
+        self.purge();
+
+        let mut results = Vec::new();
+        for ((_, key), entry) in self.entries.iter_mut() {
+            let full = match entry.path {
+                Some(ref full) => full,
+                None => continue,
+            };
+
+            let stamp = modified(full);
+            if stamp.is_none() || stamp == entry.modified {
+                // Missing files are usually mid-save, wait for them.
+                continue;
+            }
+            entry.modified = stamp;
+
+            match (entry.reload)(entry.slot.as_ref(), full) {
+                Ok(true) => results.push((key.clone(), Ok(()))),
+                Ok(false) => (),
+                Err(err) => results.push((key.clone(), Err(err))),
+            }
+        }
+
+        results
+    }
+}
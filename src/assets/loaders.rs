@@ -0,0 +1,56 @@
+use std::fs;
+use std::path::Path;
+
+use assets::asset_manager::{Asset, AssetError};
+use geometry::mesh::Mesh;
+use rendering::atlas::Atlas;
+use rendering::codecs;
+use rendering::image::RGBA;
+use rendering::vector_font::VectorFont;
+
+// The asset types the engine knows how to load. Games add their own by
+// implementing Asset.
+
+/// BMP, TGA or PPM/PGM, picked by extension or contents.
+impl Asset for RGBA {
+    fn load(path: &Path) -> Result<Self, AssetError> {
+        Ok(codecs::load(path)?)
+    }
+}
+
+/// Atlas metadata plus its pages.
+impl Asset for Atlas {
+    fn load(path: &Path) -> Result<Self, AssetError> {
+        Ok(Atlas::load(path)?)
+    }
+}
+
+/// Text and data files, e.g. level descriptions.
+impl Asset for String {
+    fn load(path: &Path) -> Result<Self, AssetError> {
+        Ok(fs::read_to_string(path)?)
+    }
+}
+
+/// Raw bytes, e.g. sounds, which are decoded by whoever plays them.
+impl Asset for Vec<u8> {
+    fn load(path: &Path) -> Result<Self, AssetError> {
+        Ok(fs::read(path)?)
+    }
+}
+
+impl Asset for Mesh {
+    fn load(path: &Path) -> Result<Self, AssetError> {
+        Mesh::parse(&fs::read_to_string(path)?).map_err(|err| parse_error(path, err))
+    }
+}
+
+impl Asset for VectorFont {
+    fn load(path: &Path) -> Result<Self, AssetError> {
+        VectorFont::parse(&fs::read_to_string(path)?).map_err(|err| parse_error(path, err))
+    }
+}
+
+fn parse_error(path: &Path, message: String) -> AssetError {
+    AssetError::Parse(format!("{}: {}", path.display(), message))
+}
@@ -0,0 +1,2 @@
+pub mod asset_manager;
+pub mod loaders;
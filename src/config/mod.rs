@@ -0,0 +1,475 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use engine::timing::rates::MAX_RATE;
+use rendering::clear_mode::ClearMode;
+use rendering::codecs;
+use rendering::color::Palette;
+use world::WorldProperties;
+
+use self::json::{JsonError, Value};
+
+pub mod json;
+
+// The World's configuration file. It's JSON and every setting is
+// optional; whatever is present overrides the values the game passed to
+// World::new, anything else is an error so typos don't go unnoticed:
+//
+//     {
+//         "window": { "width": 1024, "height": 576, "title": "Game", "vsync": true },
+//         "view": { "width": 1536, "height": 864, "centered": true },
+//         "clear": { "enabled": true, "mode": "solid", "color": [0, 0, 64] },
+//         "timing": { "updates_per_second": 30, "frames_per_second": 120,
+//                     "limit_frame_rate": true, "max_catch_up": 5 },
+//         "bindings": { "quit": ["Escape"] },
+//         "debug": { "stats": true, "coordinates": false }
+//     }
+//
+// Clear modes are "solid" (color), "checkerboard" (size, dark, light),
+// "gradient" (top, bottom) and "image" (a path to a BMP, TGA or PPM,
+// relative to the config file's directory).
+// Colors are [r, g, b] or [r, g, b, a]. Bindings map an action to the
+// SDL names of the keys that trigger it. The Core handles "quit", "pause"
+// and "step" (one update while paused), the rest are up to the game.
+// Space, Left and Right can't be bound to pause or step.
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    /// The file isn't valid JSON.
+    Syntax(JsonError),
+    /// A setting is unknown or has a bad value. `key` is the dotted path
+    /// to it, e.g. "window.width".
+    Invalid {
+        key: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::Io(ref err) => write!(f, "I/O error: {}", err),
+            ConfigError::Syntax(ref err) => write!(f, "Config syntax error at {}", err),
+            ConfigError::Invalid {
+                ref key,
+                ref message,
+            } => write!(f, "Config setting '{}': {}", key, message),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ConfigError::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+impl From<JsonError> for ConfigError {
+    fn from(err: JsonError) -> Self {
+        ConfigError::Syntax(err)
+    }
+}
+
+/// Merges the file at `path` over `properties`. If there is no file the
+/// properties are left as they are and false is returned, the caller can
+/// then `save` them as a starting point, as World::new does.
+pub fn load<P: AsRef<Path>>(
+    path: P,
+    properties: &mut WorldProperties,
+) -> Result<bool, ConfigError> {
+    match fs::read_to_string(path.as_ref()) {
+        Ok(text) => {
+            let dir = path.as_ref().parent().unwrap_or_else(|| Path::new(""));
+            apply_in(&text, dir, properties)?;
+            Ok(true)
+        }
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(ConfigError::Io(err)),
+    }
+}
+
+/// Merges the settings in `text` over `properties`. Nothing is changed
+/// unless every setting is valid. Paths are relative to the current
+/// directory.
+pub fn apply(text: &str, properties: &mut WorldProperties) -> Result<(), ConfigError> {
+    apply_in(text, Path::new(""), properties)
+}
+
+/// As apply, with paths relative to `dir`.
+pub fn apply_in(
+    text: &str,
+    dir: &Path,
+    properties: &mut WorldProperties,
+) -> Result<(), ConfigError> {
+    let root = json::parse(text)?;
+    let mut merged = WorldProperties::from(properties);
+    merge(&root, dir, &mut merged)?;
+    properties.set(&merged);
+    Ok(())
+}
+
+pub fn save<P: AsRef<Path>>(path: P, properties: &WorldProperties) -> Result<(), ConfigError> {
+    fs::write(path, format!("{}\n", to_json(properties)))?;
+    Ok(())
+}
+
+/// The properties as a complete configuration. Image backgrounds can't be
+/// written so only whether clearing is enabled is kept for them.
+pub fn to_json(properties: &WorldProperties) -> Value {
+    let number = |n: f64| Value::Number(n);
+    let color = |c: Palette| {
+        let mut channels = vec![c.r, c.g, c.b];
+        if c.a != 255 {
+            channels.push(c.a);
+        }
+        Value::Array(channels.into_iter().map(|v| number(v as f64)).collect())
+    };
+    let member = |key: &str, value: Value| (key.to_string(), value);
+
+    let mut clear = vec![member("enabled", Value::Bool(properties.perform_clear))];
+    match properties.clear_mode {
+        ClearMode::Solid(c) => {
+            clear.push(member("mode", Value::String(String::from("solid"))));
+            clear.push(member("color", color(c)));
+        }
+        ClearMode::Checkerboard { size, dark, light } => {
+            clear.push(member("mode", Value::String(String::from("checkerboard"))));
+            clear.push(member("size", number(size as f64)));
+            clear.push(member("dark", color(dark)));
+            clear.push(member("light", color(light)));
+        }
+        ClearMode::VerticalGradient { top, bottom } => {
+            clear.push(member("mode", Value::String(String::from("gradient"))));
+            clear.push(member("top", color(top)));
+            clear.push(member("bottom", color(bottom)));
+        }
+        ClearMode::Image(_) => (),
+    }
+
+    let bindings = properties
+        .bindings
+        .iter()
+        .map(|(action, keys)| {
+            let keys = keys.iter().map(|k| Value::String(k.clone())).collect();
+            member(action, Value::Array(keys))
+        })
+        .collect();
+
+    Value::Object(vec![
+        member(
+            "window",
+            Value::Object(vec![
+                member("width", number(properties.window_width as f64)),
+                member("height", number(properties.window_height as f64)),
+                member("title", Value::String(properties.title.clone())),
+                member("vsync", Value::Bool(properties.vysnc_enabled)),
+            ]),
+        ),
+        member(
+            "view",
+            Value::Object(vec![
+                member("width", number(properties.view_width)),
+                member("height", number(properties.view_height)),
+                member("centered", Value::Bool(properties.view_centered)),
+            ]),
+        ),
+        member("clear", Value::Object(clear)),
+        member(
+            "timing",
+            Value::Object(vec![
+                member(
+                    "updates_per_second",
+                    number(properties.updates_per_second as f64),
+                ),
+                member(
+                    "frames_per_second",
+                    number(properties.frames_per_second as f64),
+                ),
+                member("limit_frame_rate", Value::Bool(properties.limit_frame_rate)),
+                member("max_catch_up", number(properties.max_catch_up as f64)),
+            ]),
+        ),
+        member("bindings", Value::Object(bindings)),
+        member(
+            "debug",
+            Value::Object(vec![
+                member("stats", Value::Bool(properties.show_stats)),
+                member("coordinates", Value::Bool(properties.show_coordinates)),
+            ]),
+        ),
+    ])
+}
+
+// ----------------------------------------------------------------
+// Validation
+// ----------------------------------------------------------------
+fn merge(root: &Value, dir: &Path, wp: &mut WorldProperties) -> Result<(), ConfigError> {
+    for (section, value) in members(root, "")? {
+        let path = section.as_str();
+        match path {
+            "window" => {
+                for (key, v) in members(value, path)? {
+                    let key = join(path, key);
+                    match key.as_str() {
+                        "window.width" => wp.window_width = integer(v, &key, 1, 1 << 15)?,
+                        "window.height" => wp.window_height = integer(v, &key, 1, 1 << 15)?,
+                        "window.title" => wp.title = string(v, &key)?,
+                        "window.vsync" => wp.vysnc_enabled = boolean(v, &key)?,
+                        _ => return Err(unknown(&key)),
+                    }
+                }
+            }
+            "view" => {
+                for (key, v) in members(value, path)? {
+                    let key = join(path, key);
+                    match key.as_str() {
+                        "view.width" => wp.view_width = positive(v, &key)?,
+                        "view.height" => wp.view_height = positive(v, &key)?,
+                        "view.centered" => wp.view_centered = boolean(v, &key)?,
+                        _ => return Err(unknown(&key)),
+                    }
+                }
+            }
+            "clear" => merge_clear(value, dir, wp)?,
+            "timing" => {
+                for (key, v) in members(value, path)? {
+                    let key = join(path, key);
+                    match key.as_str() {
+                        "timing.updates_per_second" => {
+                            wp.updates_per_second = integer(v, &key, 1, MAX_RATE)?
+                        }
+                        "timing.frames_per_second" => {
+                            wp.frames_per_second = integer(v, &key, 1, MAX_RATE)?
+                        }
+                        "timing.limit_frame_rate" => wp.limit_frame_rate = boolean(v, &key)?,
+                        "timing.max_catch_up" => wp.max_catch_up = integer(v, &key, 1, 100)?,
+                        _ => return Err(unknown(&key)),
+                    }
+                }
+            }
+            "bindings" => {
+                for (action, v) in members(value, path)? {
+                    let key = join(path, action);
+                    let keys = match *v {
+                        Value::String(_) => vec![string(v, &key)?],
+                        Value::Array(ref items) => items
+                            .iter()
+                            .enumerate()
+                            .map(|(i, item)| string(item, &format!("{}[{}]", key, i)))
+                            .collect::<Result<Vec<String>, ConfigError>>()?,
+                        _ => return Err(expected(&key, "a key name or an array of them", v)),
+                    };
+                    wp.bindings.insert(action.clone(), keys);
+                }
+            }
+            "debug" => {
+                for (key, v) in members(value, path)? {
+                    let key = join(path, key);
+                    match key.as_str() {
+                        "debug.stats" => wp.show_stats = boolean(v, &key)?,
+                        "debug.coordinates" => wp.show_coordinates = boolean(v, &key)?,
+                        _ => return Err(unknown(&key)),
+                    }
+                }
+            }
+            _ => return Err(unknown(path)),
+        }
+    }
+
+    Ok(())
+}
+
+fn merge_clear(value: &Value, dir: &Path, wp: &mut WorldProperties) -> Result<(), ConfigError> {
+    let settings = members(value, "clear")?;
+
+    if let Some(v) = value.get("enabled") {
+        wp.perform_clear = boolean(v, "clear.enabled")?;
+    }
+
+    let mode = match value.get("mode") {
+        Some(v) => string(v, "clear.mode")?,
+        None => {
+            if let Some((key, _)) = settings.iter().find(|m| m.0 != "enabled") {
+                return Err(invalid(&join("clear", key), "needs a 'mode'"));
+            }
+            return Ok(());
+        }
+    };
+
+    let (keys, required): (&[&str], &[&str]) = match mode.as_str() {
+        "solid" => (&["color"], &["color"]),
+        "checkerboard" => (&["size", "dark", "light"], &[]),
+        "gradient" => (&["top", "bottom"], &["top", "bottom"]),
+        "image" => (&["image"], &["image"]),
+        _ => {
+            return Err(invalid(
+                "clear.mode",
+                &format!(
+                    "unknown mode '{}', expected solid, checkerboard, gradient or image",
+                    mode
+                ),
+            ))
+        }
+    };
+
+    for (key, _) in settings {
+        if key != "enabled" && key != "mode" && !keys.contains(&key.as_str()) {
+            return Err(invalid(
+                &join("clear", key),
+                &format!("doesn't apply to the {} mode", mode),
+            ));
+        }
+    }
+    if let Some(key) = required.iter().find(|k| value.get(k).is_none()) {
+        return Err(invalid(
+            &join("clear", key),
+            &format!("is required by the {} mode", mode),
+        ));
+    }
+
+    let color_of = |key: &str| -> Result<Option<Palette>, ConfigError> {
+        match value.get(key) {
+            Some(v) => color(v, &join("clear", key)).map(Some),
+            None => Ok(None),
+        }
+    };
+
+    wp.clear_mode = match mode.as_str() {
+        "solid" => ClearMode::Solid(color_of("color")?.unwrap()),
+        "checkerboard" => {
+            // Unset colors and size keep the current checkerboard's.
+            let mut checkerboard = match wp.clear_mode {
+                ClearMode::Checkerboard { .. } => wp.clear_mode.clone(),
+                _ => ClearMode::checkerboard(),
+            };
+            if let ClearMode::Checkerboard {
+                ref mut size,
+                ref mut dark,
+                ref mut light,
+            } = checkerboard
+            {
+                if let Some(v) = value.get("size") {
+                    *size = integer(v, "clear.size", 1, 1 << 15)? as u32;
+                }
+                *dark = color_of("dark")?.unwrap_or(*dark);
+                *light = color_of("light")?.unwrap_or(*light);
+            }
+            checkerboard
+        }
+        "gradient" => ClearMode::VerticalGradient {
+            top: color_of("top")?.unwrap(),
+            bottom: color_of("bottom")?.unwrap(),
+        },
+        _ => {
+            let path = string(value.get("image").unwrap(), "clear.image")?;
+            match codecs::load(dir.join(&path)) {
+                Ok(image) => ClearMode::Image(Rc::new(image)),
+                Err(err) => return Err(invalid("clear.image", &format!("'{}': {}", path, err))),
+            }
+        }
+    };
+
+    Ok(())
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn invalid(key: &str, message: &str) -> ConfigError {
+    ConfigError::Invalid {
+        key: key.to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn unknown(key: &str) -> ConfigError {
+    invalid(key, "unknown setting")
+}
+
+fn expected(key: &str, what: &str, found: &Value) -> ConfigError {
+    invalid(key, &format!("expected {}, found {}", what, found.kind()))
+}
+
+fn members<'a>(value: &'a Value, key: &str) -> Result<&'a [(String, Value)], ConfigError> {
+    match *value {
+        Value::Object(ref members) => Ok(members),
+        _ if key.is_empty() => Err(invalid("(top level)", "expected an object")),
+        _ => Err(expected(key, "an object", value)),
+    }
+}
+
+fn boolean(value: &Value, key: &str) -> Result<bool, ConfigError> {
+    match *value {
+        Value::Bool(b) => Ok(b),
+        _ => Err(expected(key, "true or false", value)),
+    }
+}
+
+fn string(value: &Value, key: &str) -> Result<String, ConfigError> {
+    match *value {
+        Value::String(ref s) if !s.is_empty() => Ok(s.clone()),
+        Value::String(_) => Err(invalid(key, "must not be empty")),
+        _ => Err(expected(key, "a string", value)),
+    }
+}
+
+fn positive(value: &Value, key: &str) -> Result<f64, ConfigError> {
+    match *value {
+        Value::Number(n) if n > 0.0 => Ok(n),
+        Value::Number(n) => Err(invalid(key, &format!("must be positive, found {}", n))),
+        _ => Err(expected(key, "a number", value)),
+    }
+}
+
+fn integer(value: &Value, key: &str, min: usize, max: usize) -> Result<usize, ConfigError> {
+    match *value {
+        Value::Number(n) if n.fract() == 0.0 && n >= min as f64 && n <= max as f64 => {
+            Ok(n as usize)
+        }
+        Value::Number(n) => Err(invalid(
+            key,
+            &format!(
+                "expected a whole number from {} to {}, found {}",
+                min, max, n
+            ),
+        )),
+        _ => Err(expected(key, "a whole number", value)),
+    }
+}
+
+fn color(value: &Value, key: &str) -> Result<Palette, ConfigError> {
+    let channels = match *value {
+        Value::Array(ref items) if items.len() == 3 || items.len() == 4 => items
+            .iter()
+            .enumerate()
+            .map(|(i, c)| integer(c, &format!("{}[{}]", key, i), 0, 255).map(|c| c as u8))
+            .collect::<Result<Vec<u8>, ConfigError>>()?,
+        _ => return Err(expected(key, "a color [r, g, b] or [r, g, b, a]", value)),
+    };
+
+    Ok(Palette::RGBA(
+        channels[0],
+        channels[1],
+        channels[2],
+        *channels.get(3).unwrap_or(&255),
+    ))
+}
@@ -3,27 +3,17 @@ extern crate sdl2;
 use self::sdl2::{event::Event, keyboard::Keycode, Sdl};
 use std::time::{Duration, Instant};
 
+use assets::asset_manager::AssetManager;
+use engine::timing::rates::{RateSettings, Rates};
 use engine::timing::scheduler::Scheduler;
 use nodes::{
     node::RNode,
     scenes::scene_manager::{IOEventData, SceneManager},
 };
+use world::WorldProperties;
 
 const SECOND: u32 = 1000000000; // billion ns in a second
 
-// If Sleep is disabled or removed from the code, or VSync is enabled
-// then the below FPS variable has no effect.
-const FRAMES_PER_SECOND: usize = 120; // Minimum fps to achieve
-
-const UPDATES_PER_SECOND: usize = 30; // Maximum updates per second
-
-// 1 frame period is equal to a fraction. For example, if
-// FRAMES_PER_SECOND = 60.0 then frame period is 0.01666666667s of a second
-// or in milliseconds it is 1000.0/60.0 = 16.66666667ms per frame.
-// 1ms = 1000us = 1000000ns
-const FRAME_PERIOD: f64 = 1_000_000_000.0 / FRAMES_PER_SECOND as f64; // in nanoseconds
-const UPDATE_PERIOD: f64 = 1_000_000_000.0 / UPDATES_PER_SECOND as f64; // in nanoseconds
-
 pub struct Core {
     // -------------------------------------------------------
     // Debug and diagnostics
@@ -31,13 +21,19 @@ pub struct Core {
     // step_enabled: bool,
     fps: usize,
     ups: usize,
-    frame_period: Duration,
-    update_period: Duration,
     avg_blit_time: f64,
     avg_ren_time: f64,
     // avg_sleep_time: f64,
     avg_up_time: f64,
     // -------------------------------------------------------
+
+    // Target update and frame rates, see timing/rates.rs.
+    rates: Rates,
+
+    // Keys bound to the "quit", "pause" and "step" actions.
+    quit_keys: Vec<Keycode>,
+    pause_keys: Vec<Keycode>,
+    step_keys: Vec<Keycode>,
 }
 
 impl Core {
@@ -49,18 +45,61 @@ impl Core {
             // step_enabled: false,
             fps: 0,
             ups: 0,
-            frame_period: Duration::new(0, FRAME_PERIOD.round() as u32),
-            update_period: Duration::new(0, UPDATE_PERIOD.round() as u32),
             // frame_count: 0,
             // avg_process_time: 0.0,
             avg_blit_time: 0.0,
             avg_ren_time: 0.0,
             // avg_sleep_time: 0.0,
             avg_up_time: 0.0,
+            rates: Rates::new(RateSettings::new()),
+            quit_keys: vec![Keycode::Escape],
+            pause_keys: Vec::new(),
+            step_keys: Vec::new(),
+        }
+    }
+
+    /// Applies the rates and the input bindings the Core handles itself.
+    /// Fails if a key name isn't known to SDL.
+    pub fn initialize(&mut self, wp: &WorldProperties) -> Result<(), String> {
+        self.rates.set(RateSettings {
+            updates_per_second: wp.updates_per_second,
+            frames_per_second: wp.frames_per_second,
+            limit_frame_rate: wp.limit_frame_rate,
+            max_catch_up: wp.max_catch_up,
+        });
+
+        self.quit_keys = bound_keys(wp, "quit")?;
+        // Toggles the Scheduler's pause and advances one update while
+        // paused. They are matched before the keys the loop routes
+        // itself, so those can't be bound.
+        self.pause_keys = bound_keys(wp, "pause")?;
+        self.step_keys = bound_keys(wp, "step")?;
+        for key in self.pause_keys.iter().chain(self.step_keys.iter()) {
+            if ROUTED_KEYS.contains(key) {
+                return Err(format!(
+                    "Key '{}' is reserved and can't be bound to pause or step",
+                    key.name()
+                ));
+            }
         }
+
+        Ok(())
+    }
+
+    /// The target time between rendered frames. Frames captured once per
+    /// frame are this far apart, e.g. for GIF export.
+    pub fn frame_period(&self) -> Duration {
+        self.rates.get().frame_period()
     }
 
-    pub fn initialize(&mut self) {}
+    pub fn update_period(&self) -> Duration {
+        self.rates.get().update_period()
+    }
+
+    /// A handle for changing the rates while the game runs.
+    pub fn rates(&self) -> Rates {
+        self.rates.clone()
+    }
 
     // This loop is losely based on:
     // http://gameprogrammingpatterns.com/game-loop.html
@@ -69,12 +108,13 @@ impl Core {
         context: &Sdl,
         scene_manager: &mut SceneManager,
         scheduler: &mut Scheduler,
+        assets: &mut AssetManager,
     ) -> Result<String, String> {
-        let ns_per_update = self.update_period.subsec_nanos();
-        let frame_dt = ns_per_update as f64 / 1000000.0;
-        println!("ns_per_update: {}", ns_per_update);
+        let mut settings = self.rates.get();
+        let mut frame_dt = settings.update_period().as_secs_f64() * 1000.0;
+        println!("ns_per_update: {}", settings.update_period().as_nanos());
 
-        let mut lag = 0;
+        let mut lag = Duration::new(0, 0);
         let mut second_acm = 0;
         let mut fps = 0;
         let mut ups = 0;
@@ -97,16 +137,31 @@ impl Core {
         'fast: loop {
             let current_t = Instant::now();
 
+            // Rate changes take effect from the frame after they are made.
+            if self.rates.get() != settings {
+                settings = self.rates.get();
+                frame_dt = settings.update_period().as_secs_f64() * 1000.0;
+            }
+
             // ##############################################################
             // Input
             // ##############################################################
             for event in event_pump.poll_iter() {
                 match event {
-                    Event::Quit { .. }
-                    | Event::KeyDown {
-                        keycode: Some(Keycode::Escape),
+                    Event::Quit { .. } => break 'fast,
+                    Event::KeyDown {
+                        keycode: Some(key),
                         .. // don't-care about other fields
-                    } => break 'fast,
+                    } if self.quit_keys.contains(&key) => break 'fast,
+                    Event::KeyDown {
+                        keycode: Some(key),
+                        repeat: false,
+                        ..
+                    } if self.pause_keys.contains(&key) => scheduler.time().toggle_pause(),
+                    Event::KeyDown {
+                        keycode: Some(key),
+                        ..
+                    } if self.step_keys.contains(&key) => scheduler.time().step(),
                     Event::KeyDown {
                         keycode: Some(Keycode::Space),
                         repeat: false,
@@ -154,20 +209,17 @@ impl Core {
             // ##############################################################
             let elapsed_t = current_t - previous_t;
             previous_t = current_t;
-            lag += elapsed_t.subsec_nanos();
+            lag += elapsed_t;
 
             let u = Instant::now();
 
-            'up: loop {
-                if lag >= ns_per_update {
-                    scheduler.update(frame_dt);
-                    lag -= ns_per_update;
-                    ups += 1;
-                } else {
-                    ups = 0;
-                    break 'up;
-                }
+            // Updates missed by a slow frame are caught up, up to a limit.
+            let (due, remaining) = settings.catch_up(lag);
+            for _ in 0..due {
+                scheduler.update(frame_dt);
             }
+            lag = remaining;
+            ups += due;
 
             // ::std::thread::sleep(Duration::from_millis(15)); // force/test pipeline overload
 
@@ -177,7 +229,7 @@ impl Core {
             // ##############################################################
             // Render
             // ##############################################################
-            let interpolation = (lag as f64) / (ns_per_update as f64);
+            let interpolation = lag.as_secs_f64() / settings.update_period().as_secs_f64();
             let pn: Duration;
             {
                 // let mut scm = scene_manager.borrow_mut();
@@ -224,20 +276,13 @@ impl Core {
             // Sleep
             // ##############################################################
             // How much time was taken for the above steps
-            let work = un + bn + pn;
+            let work = current_t.elapsed();
 
             // Was the work done in this frame less than the alotted period
-            if work < self.frame_period {
+            let frame_period = settings.frame_period();
+            if settings.limit_frame_rate && work < frame_period {
                 // Sleep is the remainder.
-                // sleep = self.frame_period - work;
-                // std::thread::sleep(sleep);
-
-                // Simply sleep for a tiny amount to allow main thread processing.
-                // std::thread::sleep(Duration::from_micros(500));
-                // std::thread::sleep(Duration::from_millis(1));
-                // std::thread::yield_now();
-            } else {
-                //sleep = Duration::new(0, 0);
+                std::thread::sleep(frame_period - work);
             }
 
             // The total elapsed time spent for the frame is the work plus any
@@ -254,9 +299,7 @@ impl Core {
             if second_acm >= SECOND {
                 // println!("display update {}, {}", ns_per_update, SECOND);
                 self.fps = fps;
-                // (1 / (((ups+1) * ns_per_update) / 1000000)) * 1000 = fps
-                self.ups =
-                    ((1.0 / (((ups + 1) * ns_per_update) as f32 / 1000000.0)) * 1000.0) as usize;
+                self.ups = ups;
                 self.avg_blit_time = ((blit_accum as f64) / (fps as f64)) / 1000000.0;
                 self.avg_ren_time = ((proc_accum as f64) / (fps as f64)) / 1000000.0;
                 // self.avg_sleep_time = ((sleep_accum as f64) / (fps as f64)) / 1000000.0;
@@ -275,6 +318,15 @@ impl Core {
                 //     total ,
                 // );
 
+                if assets.is_watching() {
+                    for (key, result) in assets.reload_changed() {
+                        match result {
+                            Ok(()) => println!("Reloaded asset: '{}'", key),
+                            Err(err) => println!("Failed to reload asset '{}': {}", key, err),
+                        }
+                    }
+                }
+
                 second_acm = 0;
                 // sleep_accum = 0;
                 proc_accum = 0;
@@ -298,3 +350,17 @@ impl Core {
         scene_manager.push_scene(scene);
     }
 }
+
+// Keys the core loop handles itself.
+const ROUTED_KEYS: [Keycode; 3] = [Keycode::Space, Keycode::Right, Keycode::Left];
+
+fn bound_keys(wp: &WorldProperties, action: &str) -> Result<Vec<Keycode>, String> {
+    let mut keys = Vec::new();
+    for name in wp.bindings.get(action).into_iter().flatten() {
+        match Keycode::from_name(name) {
+            Some(key) => keys.push(key),
+            None => return Err(format!("Unknown key '{}' bound to '{}'", name, action)),
+        }
+    }
+    Ok(keys)
+}
@@ -1,2 +1,5 @@
+pub mod rates;
 pub mod scheduler;
+pub mod time_control;
+pub mod timers;
 pub mod timing_target;
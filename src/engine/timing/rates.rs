@@ -0,0 +1,122 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+// The Core's target rates. Updates run at a fixed rate, frames are drawn
+// as often as possible (or as vsync allows) unless the frame limiter is
+// on, in which case the Core sleeps away what's left of each frame
+// period.
+//
+// When a frame takes too long the updates it missed are run before the
+// next frame, at most `max_catch_up` of them. Anything beyond that is
+// dropped, otherwise slow updates would make every frame later than the
+// last (the "spiral of death").
+
+/// If sleep isn't enabled or vsync is, this has no effect.
+pub const FRAMES_PER_SECOND: usize = 120;
+pub const UPDATES_PER_SECOND: usize = 30;
+pub const MAX_CATCH_UP: usize = 5;
+
+/// Rates are clamped to [1, MAX_RATE].
+pub const MAX_RATE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateSettings {
+    pub updates_per_second: usize,
+    pub frames_per_second: usize,
+    pub limit_frame_rate: bool,
+    /// Most updates run back to back before a frame, at least 1.
+    pub max_catch_up: usize,
+}
+
+impl RateSettings {
+    pub fn new() -> Self {
+        Self {
+            updates_per_second: UPDATES_PER_SECOND,
+            frames_per_second: FRAMES_PER_SECOND,
+            limit_frame_rate: false,
+            max_catch_up: MAX_CATCH_UP,
+        }
+    }
+
+    fn clamped(mut self) -> Self {
+        self.updates_per_second = self.updates_per_second.clamp(1, MAX_RATE);
+        self.frames_per_second = self.frames_per_second.clamp(1, MAX_RATE);
+        self.max_catch_up = self.max_catch_up.max(1);
+        self
+    }
+
+    // 1 frame period is equal to a fraction. For example, if
+    // frames_per_second = 60 then frame period is 0.01666666667s of a second
+    // or in milliseconds it is 1000.0/60.0 = 16.66666667ms per frame.
+    pub fn frame_period(&self) -> Duration {
+        period(self.frames_per_second)
+    }
+
+    pub fn update_period(&self) -> Duration {
+        period(self.updates_per_second)
+    }
+
+    /// How many updates are due for `lag` and the lag left afterwards.
+    /// Updates past max_catch_up are dropped along with their time.
+    pub fn catch_up(&self, lag: Duration) -> (usize, Duration) {
+        let period = self.update_period().as_nanos();
+        let lag = lag.as_nanos();
+        let due = (lag / period) as usize;
+        let remaining = Duration::from_nanos((lag % period) as u64);
+
+        (due.min(self.max_catch_up.max(1)), remaining)
+    }
+}
+
+fn period(rate: usize) -> Duration {
+    Duration::from_nanos((1_000_000_000.0 / rate.max(1) as f64).round() as u64)
+}
+
+/// A shared handle to the rates so they can be changed while the game
+/// runs, e.g. from a node. The Core applies changes at the start of the
+/// next frame.
+#[derive(Clone)]
+pub struct Rates {
+    settings: Rc<Cell<RateSettings>>,
+}
+
+impl Rates {
+    pub fn new(settings: RateSettings) -> Self {
+        Self {
+            settings: Rc::new(Cell::new(settings.clamped())),
+        }
+    }
+
+    pub fn get(&self) -> RateSettings {
+        self.settings.get()
+    }
+
+    pub fn set(&self, settings: RateSettings) {
+        self.settings.set(settings.clamped());
+    }
+
+    pub fn set_updates_per_second(&self, rate: usize) {
+        let mut settings = self.get();
+        settings.updates_per_second = rate;
+        self.set(settings);
+    }
+
+    pub fn set_frames_per_second(&self, rate: usize) {
+        let mut settings = self.get();
+        settings.frames_per_second = rate;
+        self.set(settings);
+    }
+
+    pub fn set_limit_frame_rate(&self, limit: bool) {
+        let mut settings = self.get();
+        settings.limit_frame_rate = limit;
+        self.set(settings);
+    }
+
+    pub fn set_max_catch_up(&self, updates: usize) {
+        let mut settings = self.get();
+        settings.max_catch_up = updates;
+        self.set(settings);
+    }
+}
@@ -1,25 +1,29 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use engine::timing::time_control::TimeControl;
+use engine::timing::timers::{TimerHandle, Timers};
 use nodes::node::RNode;
 
 pub type RScheduler = Rc<RefCell<Scheduler>>;
 
-/// UpdateTargets are independent timing targets. That is they are
-/// timing events independent of the core timing loop events.
-// type UpdateTarget = fn(dt: f64);
-
 /// The `Scheduler` is responsible to updating timing targets.
 /// There are two types of targets:
 ///
 /// # Targets:
 ///
 /// * `TimingTarget` - are targets drivin by the `Core` frame timing.
-/// * `UpdateTarget` - are targets drivin by an independent timer.
+/// * Timers - closures run after a delay, every interval or every update,
+///   see timers.rs.
 pub struct Scheduler {
     system_targets: Vec<RNode>,
     // non_system_targets: Vec<Rc<RefCell<TimingTarget>>>,
     normal_targets: Vec<RNode>,
+
+    // Time scale and pause, see time_control.rs.
+    time: TimeControl,
+
+    timers: Timers,
 }
 
 /// The order of target updates.
@@ -47,38 +51,81 @@ impl Scheduler {
             system_targets: Vec::new(),
             // non_system_targets: Vec::new(),
             normal_targets: Vec::new(),
+            time: TimeControl::new(),
+            timers: Timers::new(),
         }
     }
 
+    /// A handle for scaling and pausing time while the game runs.
+    pub fn time(&self) -> TimeControl {
+        self.time.clone()
+    }
+
     pub fn update(&self, dt: f64) {
+        // System targets run even when the game is paused.
+        let running = self.time.advance();
+
         // Update TimingTargets first.
+        let system_dt = self.time.scaled_dt(TimingPriority::System, dt);
         for target in &self.system_targets {
             let t = target.borrow();
             if !t.paused() {
-                t.update(dt);
+                t.update(system_dt);
             }
         }
 
-        // for target in &non_system_targets {
-        //     let t = target.borrow();
-        //     if !t.paused() {
-        //         t.update(dt);
-        //     }
-        // }
-
-        for target in &self.normal_targets {
-            let t = target.borrow();
-            // println!(
-            //     "scheduler normal target: '{}', paused: {}",
-            //     t.name(),
-            //     t.paused()
-            // );
-            if !t.paused() {
-                t.update(dt);
+        // Updates from here on are game time.
+        if running {
+            // for target in &non_system_targets {
+            //     let t = target.borrow();
+            //     if !t.paused() {
+            //         t.update(dt);
+            //     }
+            // }
+
+            let normal_dt = self.time.scaled_dt(TimingPriority::Normal, dt);
+            for target in &self.normal_targets {
+                let t = target.borrow();
+                // println!(
+                //     "scheduler normal target: '{}', paused: {}",
+                //     t.name(),
+                //     t.paused()
+                // );
+                if !t.paused() {
+                    t.update(normal_dt);
+                }
             }
         }
 
-        // TODO Custom (aka Update targets)
+        self.timers.update(&self.time, running, dt);
+    }
+
+    // ----------------------------------------------------------
+    // Timers
+    // ----------------------------------------------------------
+    /// A handle for scheduling callbacks while the game runs.
+    pub fn timers(&self) -> Timers {
+        self.timers.clone()
+    }
+
+    /// Calls `callback` once, `delay` ms from now.
+    pub fn after<F: FnMut(f64) + 'static>(&mut self, delay: f64, callback: F) -> TimerHandle {
+        self.timers.after(delay, callback)
+    }
+
+    /// Calls `callback` every `interval` ms, `repeats` times or forever if
+    /// None.
+    pub fn every<F: FnMut(f64) + 'static>(
+        &mut self,
+        interval: f64,
+        repeats: Option<usize>,
+        callback: F,
+    ) -> TimerHandle {
+        self.timers.every(interval, repeats, callback)
+    }
+
+    pub fn every_frame<F: FnMut(f64) + 'static>(&mut self, callback: F) -> TimerHandle {
+        self.timers.every_frame(callback)
     }
 
     // ----------------------------------------------------------
@@ -133,6 +180,8 @@ impl Scheduler {
     }
 
     pub fn unschedule_timing_target_by_id(&mut self, target_id: usize) {
+        self.timers.cancel_bound(target_id);
+
         // Check each list
         self.normal_targets
             .retain(|ref node| node.borrow().id() != target_id);
@@ -141,6 +190,8 @@ impl Scheduler {
     }
 
     pub fn unschedule_timing_target(&mut self, target: RNode) {
+        self.timers.cancel_bound(target.borrow().id());
+
         match target.borrow().priority() {
             // We check Normals first as they are occur the most.
             TimingPriority::Normal => {
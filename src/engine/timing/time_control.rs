@@ -0,0 +1,127 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use engine::timing::scheduler::TimingPriority;
+
+// Game time versus real time. The Scheduler passes each target the fixed
+// update dt multiplied by the target's priority scale and, for everything
+// but TimingPriority::System, by the global scale. Global pause likewise
+// stops everything but System targets, so menus, transitions and debug
+// overlays keep running.
+//
+// While paused, step() lets game time advance by exactly one update,
+// which is handy for stepping through a bug frame by frame.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TimeState {
+    scale: f64,
+    system_scale: f64,
+    normal_scale: f64,
+    paused: bool,
+    // Updates to run while paused.
+    steps: usize,
+}
+
+/// A shared handle to the Scheduler's time settings, so they can be
+/// changed while the game runs, e.g. from a node.
+#[derive(Clone)]
+pub struct TimeControl {
+    state: Rc<Cell<TimeState>>,
+}
+
+impl TimeControl {
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(Cell::new(TimeState {
+                scale: 1.0,
+                system_scale: 1.0,
+                normal_scale: 1.0,
+                paused: false,
+                steps: 0,
+            })),
+        }
+    }
+
+    fn modify<F: FnOnce(&mut TimeState)>(&self, f: F) {
+        let mut state = self.state.get();
+        f(&mut state);
+        self.state.set(state);
+    }
+
+    /// Less than 1.0 is slow motion, more is fast forward. Negative
+    /// scales are treated as 0.
+    pub fn set_scale(&self, scale: f64) {
+        self.modify(|s| s.scale = scale.max(0.0));
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.state.get().scale
+    }
+
+    /// Scales one priority on top of the global scale.
+    pub fn set_priority_scale(&self, priority: TimingPriority, scale: f64) {
+        let scale = scale.max(0.0);
+        self.modify(|s| match priority {
+            TimingPriority::System => s.system_scale = scale,
+            TimingPriority::Normal => s.normal_scale = scale,
+        });
+    }
+
+    pub fn priority_scale(&self, priority: TimingPriority) -> f64 {
+        let state = self.state.get();
+        match priority {
+            TimingPriority::System => state.system_scale,
+            TimingPriority::Normal => state.normal_scale,
+        }
+    }
+
+    /// Pausing drops any steps not yet taken.
+    pub fn pause(&self, paused: bool) {
+        self.modify(|s| {
+            s.paused = paused;
+            s.steps = 0;
+        });
+    }
+
+    pub fn paused(&self) -> bool {
+        self.state.get().paused
+    }
+
+    pub fn toggle_pause(&self) {
+        let paused = self.paused();
+        self.pause(!paused);
+    }
+
+    /// Advances a paused game by one update. Does nothing if not paused.
+    pub fn step(&self) {
+        self.modify(|s| {
+            if s.paused {
+                s.steps += 1;
+            }
+        });
+    }
+
+    /// The dt a target of `priority` gets for a fixed update of `dt`.
+    pub fn scaled_dt(&self, priority: TimingPriority, dt: f64) -> f64 {
+        let state = self.state.get();
+        match priority {
+            TimingPriority::System => dt * state.system_scale,
+            TimingPriority::Normal => dt * state.scale * state.normal_scale,
+        }
+    }
+
+    /// Called by the Scheduler once per update. Whether game time advances
+    /// this update, using up a step if paused.
+    pub fn advance(&self) -> bool {
+        let mut state = self.state.get();
+        if !state.paused {
+            return true;
+        }
+        if state.steps == 0 {
+            return false;
+        }
+        state.steps -= 1;
+        self.state.set(state);
+        true
+    }
+}
@@ -0,0 +1,310 @@
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use engine::timing::scheduler::TimingPriority;
+use engine::timing::time_control::TimeControl;
+use nodes::node::{NodeTrait, RNode};
+
+// Closures driven by the Scheduler rather than nodes: one-shots that run
+// after a delay, repeats that run every interval, a number of times or
+// forever, and callbacks that run every update.
+//
+// Times are in milliseconds, the unit of the update dt, and are game time
+// by default so timers slow down, speed up and pause with the game. A
+// timer given TimingPriority::System runs in real time instead, see
+// time_control.rs.
+//
+// A timer bound to a node is dropped along with the node, when the node
+// is unscheduled from the Scheduler, or when the node's scene exits.
+
+/// Called with the update's dt, as scaled for the timer's priority.
+pub type TimerCallback = Box<dyn FnMut(f64)>;
+
+enum Kind {
+    Once,
+    Repeat {
+        interval: f64,
+        // None repeats forever.
+        remaining: Option<usize>,
+    },
+    EveryFrame,
+}
+
+// A node's id and the node, which the timer doesn't keep alive.
+type BoundNode = (usize, Weak<RefCell<dyn NodeTrait>>);
+
+// Shared between a Timer and its handles.
+struct TimerState {
+    cancelled: Cell<bool>,
+    finished: Cell<bool>,
+    paused: Cell<bool>,
+    fired: Cell<usize>,
+    priority: Cell<TimingPriority>,
+    node: RefCell<Option<BoundNode>>,
+}
+
+impl TimerState {
+    fn done(&self) -> bool {
+        self.cancelled.get() || self.finished.get()
+    }
+
+    fn bound_to(&self, node_id: usize) -> bool {
+        matches!(*self.node.borrow(), Some((id, _)) if id == node_id)
+    }
+
+    fn node_gone(&self) -> bool {
+        match *self.node.borrow() {
+            Some((_, ref node)) => node.strong_count() == 0,
+            None => false,
+        }
+    }
+}
+
+struct Timer {
+    kind: Kind,
+    // Time left until the next call.
+    wait: f64,
+    callback: TimerCallback,
+    state: Rc<TimerState>,
+}
+
+impl Timer {
+    fn fire(&mut self, dt: f64) {
+        self.state.fired.set(self.state.fired.get() + 1);
+        (self.callback)(dt);
+    }
+
+    fn tick(&mut self, dt: f64) {
+        match self.kind {
+            Kind::EveryFrame => self.fire(dt),
+            Kind::Once => {
+                self.wait -= dt;
+                if self.wait <= 0.0 {
+                    self.state.finished.set(true);
+                    self.fire(dt);
+                }
+            }
+            Kind::Repeat { interval, .. } => {
+                self.wait -= dt;
+                // A long update can be worth several intervals.
+                while self.wait <= 0.0 && !self.state.done() {
+                    if let Kind::Repeat {
+                        remaining: Some(ref mut remaining),
+                        ..
+                    } = self.kind
+                    {
+                        *remaining -= 1;
+                        if *remaining == 0 {
+                            self.state.finished.set(true);
+                        }
+                    }
+                    self.fire(dt);
+
+                    if interval <= 0.0 {
+                        // At most once per update.
+                        self.wait = 0.0;
+                        break;
+                    }
+                    self.wait += interval;
+                }
+            }
+        }
+    }
+}
+
+/// Controls a scheduled callback. Dropping the handle doesn't cancel it.
+#[derive(Clone)]
+pub struct TimerHandle {
+    state: Rc<TimerState>,
+}
+
+impl TimerHandle {
+    /// The callback won't be called again.
+    pub fn cancel(&self) {
+        self.state.cancelled.set(true);
+    }
+
+    /// A paused timer holds its remaining wait until resumed.
+    pub fn pause(&self, paused: bool) {
+        self.state.paused.set(paused);
+    }
+
+    pub fn paused(&self) -> bool {
+        self.state.paused.get()
+    }
+
+    /// False once cancelled, or when a one-shot or counted repeat has
+    /// run its course.
+    pub fn is_active(&self) -> bool {
+        !self.state.done()
+    }
+
+    /// How many times the callback has been called.
+    pub fn fire_count(&self) -> usize {
+        self.state.fired.get()
+    }
+
+    /// Normal (the default) runs on game time, System on real time.
+    pub fn set_priority(&self, priority: TimingPriority) {
+        self.state.priority.set(priority);
+    }
+
+    /// Ties the timer's lifetime to `node`.
+    pub fn bind(&self, node: &RNode) {
+        let id = node.borrow().id();
+        *self.state.node.borrow_mut() = Some((id, Rc::downgrade(node)));
+    }
+}
+
+/// The Scheduler's timers. The handle is shared so callbacks can be
+/// scheduled while the game runs, including from other callbacks.
+#[derive(Clone)]
+pub struct Timers {
+    timers: Rc<RefCell<Vec<Timer>>>,
+    // Nodes cancelled while `update` has the timers taken out, Some only
+    // during an update.
+    cancelled: Rc<RefCell<Option<Vec<usize>>>>,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self {
+            timers: Rc::new(RefCell::new(Vec::new())),
+            cancelled: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    fn add(&self, kind: Kind, wait: f64, callback: TimerCallback) -> TimerHandle {
+        let state = Rc::new(TimerState {
+            cancelled: Cell::new(false),
+            finished: Cell::new(false),
+            paused: Cell::new(false),
+            fired: Cell::new(0),
+            priority: Cell::new(TimingPriority::Normal),
+            node: RefCell::new(None),
+        });
+
+        self.timers.borrow_mut().push(Timer {
+            kind,
+            wait,
+            callback,
+            state: state.clone(),
+        });
+
+        TimerHandle { state }
+    }
+
+    /// Calls `callback` once, `delay` ms from now.
+    pub fn after<F: FnMut(f64) + 'static>(&self, delay: f64, callback: F) -> TimerHandle {
+        self.add(Kind::Once, delay, Box::new(callback))
+    }
+
+    /// Calls `callback` every `interval` ms, `repeats` times or forever if
+    /// None. The first call is one interval from now.
+    pub fn every<F: FnMut(f64) + 'static>(
+        &self,
+        interval: f64,
+        repeats: Option<usize>,
+        callback: F,
+    ) -> TimerHandle {
+        let handle = self.add(
+            Kind::Repeat {
+                interval,
+                remaining: repeats,
+            },
+            interval,
+            Box::new(callback),
+        );
+        if repeats == Some(0) {
+            handle.state.finished.set(true);
+        }
+        handle
+    }
+
+    /// Calls `callback` on every update.
+    pub fn every_frame<F: FnMut(f64) + 'static>(&self, callback: F) -> TimerHandle {
+        self.add(Kind::EveryFrame, 0.0, Box::new(callback))
+    }
+
+    /// Timers not yet finished or cancelled.
+    pub fn len(&self) -> usize {
+        self.timers
+            .borrow()
+            .iter()
+            .filter(|t| !t.state.done())
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Cancels the timers bound to the node.
+    pub fn cancel_bound(&self, node_id: usize) {
+        for timer in self.timers.borrow().iter() {
+            if timer.state.bound_to(node_id) {
+                timer.state.cancelled.set(true);
+            }
+        }
+        if let Some(ref mut cancelled) = *self.cancelled.borrow_mut() {
+            cancelled.push(node_id);
+        }
+    }
+
+    /// Cancels the timers bound to the node or any of its descendants.
+    pub fn cancel_bound_tree(&self, node: &RNode) {
+        let node = node.borrow();
+        self.cancel_bound(node.id());
+        if let Some(children) = node.get_children() {
+            for child in children.borrow().iter() {
+                self.cancel_bound_tree(child);
+            }
+        }
+    }
+
+    /// Runs the timers for one update. `running` is false while game time
+    /// is paused.
+    pub fn update(&self, time: &TimeControl, running: bool, dt: f64) {
+        // Taken out so callbacks can schedule more timers.
+        let mut timers = std::mem::take(&mut *self.timers.borrow_mut());
+        *self.cancelled.borrow_mut() = Some(Vec::new());
+
+        for timer in timers.iter_mut() {
+            if timer.state.node_gone() || self.was_cancelled(&timer.state) {
+                timer.state.cancelled.set(true);
+            }
+            if timer.state.done() || timer.state.paused.get() {
+                continue;
+            }
+
+            let priority = timer.state.priority.get();
+            if let TimingPriority::Normal = priority {
+                if !running {
+                    continue;
+                }
+            }
+            timer.tick(time.scaled_dt(priority, dt));
+        }
+
+        // Cancellations during the update may apply to timers it has
+        // already run.
+        for timer in timers.iter() {
+            if self.was_cancelled(&timer.state) {
+                timer.state.cancelled.set(true);
+            }
+        }
+        *self.cancelled.borrow_mut() = None;
+
+        timers.retain(|t| !t.state.done());
+        let mut shared = self.timers.borrow_mut();
+        timers.append(&mut shared);
+        *shared = timers;
+    }
+
+    fn was_cancelled(&self, state: &TimerState) -> bool {
+        match *self.cancelled.borrow() {
+            Some(ref cancelled) => cancelled.iter().any(|&id| state.bound_to(id)),
+            None => false,
+        }
+    }
+}
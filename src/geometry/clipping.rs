@@ -0,0 +1,238 @@
+use geometry::point::Point;
+use geometry::rectangle::Rectangle;
+
+// Line and polygon clipping against an axis aligned rectangle.
+// The rectangle's edges are inclusive.
+
+// Cohen-Sutherland outcodes
+pub const INSIDE: u8 = 0;
+pub const LEFT: u8 = 1;
+pub const RIGHT: u8 = 2;
+pub const BOTTOM: u8 = 4; // min y
+pub const TOP: u8 = 8; // max y
+
+pub struct Clipping;
+
+impl Clipping {
+    /// The Cohen-Sutherland region code of (x, y).
+    pub fn outcode(rect: &Rectangle, x: f64, y: f64) -> u8 {
+        let mut code = INSIDE;
+
+        if x < rect.min.x {
+            code |= LEFT;
+        } else if x > rect.max.x {
+            code |= RIGHT;
+        }
+
+        if y < rect.min.y {
+            code |= BOTTOM;
+        } else if y > rect.max.y {
+            code |= TOP;
+        }
+
+        code
+    }
+
+    /// True if every point is within `rect`.
+    pub fn contains_all(rect: &Rectangle, points: &[Point]) -> bool {
+        points
+            .iter()
+            .all(|p| Clipping::outcode(rect, p.x, p.y) == INSIDE)
+    }
+
+    /// True if every point is beyond the same edge of `rect`, in which case
+    /// nothing formed by the points can be visible.
+    pub fn outside_all(rect: &Rectangle, points: &[Point]) -> bool {
+        let mut common = LEFT | RIGHT | BOTTOM | TOP;
+        for p in points {
+            common &= Clipping::outcode(rect, p.x, p.y);
+            if common == INSIDE {
+                return false;
+            }
+        }
+        !points.is_empty()
+    }
+
+    /// Clips the line p0->p1 using Cohen-Sutherland. Returns None if the
+    /// line is entirely outside.
+    pub fn cohen_sutherland(rect: &Rectangle, p0: &Point, p1: &Point) -> Option<(Point, Point)> {
+        let (mut x0, mut y0, mut x1, mut y1) = (p0.x, p0.y, p1.x, p1.y);
+        let mut code0 = Clipping::outcode(rect, x0, y0);
+        let mut code1 = Clipping::outcode(rect, x1, y1);
+
+        loop {
+            if code0 | code1 == INSIDE {
+                return Some((Point::from_xy(x0, y0), Point::from_xy(x1, y1)));
+            }
+            if code0 & code1 != INSIDE {
+                return None;
+            }
+
+            // At least one end point is outside, pick it.
+            let code = if code0 != INSIDE { code0 } else { code1 };
+
+            let (x, y) = if code & TOP != 0 {
+                (x0 + (x1 - x0) * (rect.max.y - y0) / (y1 - y0), rect.max.y)
+            } else if code & BOTTOM != 0 {
+                (x0 + (x1 - x0) * (rect.min.y - y0) / (y1 - y0), rect.min.y)
+            } else if code & RIGHT != 0 {
+                (rect.max.x, y0 + (y1 - y0) * (rect.max.x - x0) / (x1 - x0))
+            } else {
+                (rect.min.x, y0 + (y1 - y0) * (rect.min.x - x0) / (x1 - x0))
+            };
+
+            if code == code0 {
+                x0 = x;
+                y0 = y;
+                code0 = Clipping::outcode(rect, x0, y0);
+            } else {
+                x1 = x;
+                y1 = y;
+                code1 = Clipping::outcode(rect, x1, y1);
+            }
+        }
+    }
+
+    /// Clips the line p0->p1 using Liang-Barsky. Returns None if the line
+    /// is entirely outside.
+    pub fn liang_barsky(rect: &Rectangle, p0: &Point, p1: &Point) -> Option<(Point, Point)> {
+        let dx = p1.x - p0.x;
+        let dy = p1.y - p0.y;
+
+        let mut t0 = 0.0;
+        let mut t1 = 1.0;
+
+        // (p, q) pairs for the left, right, bottom and top edges
+        let edges = [
+            (-dx, p0.x - rect.min.x),
+            (dx, rect.max.x - p0.x),
+            (-dy, p0.y - rect.min.y),
+            (dy, rect.max.y - p0.y),
+        ];
+
+        for &(p, q) in edges.iter() {
+            if p == 0.0 {
+                // Parallel to this edge
+                if q < 0.0 {
+                    return None;
+                }
+            } else {
+                let r = q / p;
+                if p < 0.0 {
+                    if r > t1 {
+                        return None;
+                    }
+                    if r > t0 {
+                        t0 = r;
+                    }
+                } else {
+                    if r < t0 {
+                        return None;
+                    }
+                    if r < t1 {
+                        t1 = r;
+                    }
+                }
+            }
+        }
+
+        Some((
+            Point::from_xy(p0.x + t0 * dx, p0.y + t0 * dy),
+            Point::from_xy(p0.x + t1 * dx, p0.y + t1 * dy),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edge {
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+const EDGES: [Edge; 4] = [Edge::Left, Edge::Right, Edge::Bottom, Edge::Top];
+
+/// Sutherland-Hodgman polygon clipping. The clipper keeps a scratch buffer
+/// so that clipping doesn't allocate once warmed up.
+pub struct PolygonClipper {
+    scratch: Vec<Point>,
+}
+
+impl PolygonClipper {
+    pub fn new() -> Self {
+        Self {
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Clips the closed polygon `input` to `rect`, replacing the contents
+    /// of `out`. `out` is empty if nothing remains. A concave polygon may
+    /// produce degenerate edges along the rectangle's sides, which fill
+    /// correctly.
+    pub fn clip(&mut self, rect: &Rectangle, input: &[Point], out: &mut Vec<Point>) {
+        out.clear();
+        out.extend_from_slice(input);
+
+        for &edge in EDGES.iter() {
+            if out.is_empty() {
+                return;
+            }
+
+            self.scratch.clear();
+            self.scratch.extend_from_slice(out);
+            out.clear();
+
+            let count = self.scratch.len();
+            for i in 0..count {
+                let current = self.scratch[i];
+                let previous = self.scratch[(i + count - 1) % count];
+
+                let current_in = inside(rect, edge, &current);
+                let previous_in = inside(rect, edge, &previous);
+
+                if current_in {
+                    if !previous_in {
+                        out.push(intersect(rect, edge, &previous, &current));
+                    }
+                    out.push(current);
+                } else if previous_in {
+                    out.push(intersect(rect, edge, &previous, &current));
+                }
+            }
+        }
+    }
+}
+
+#[inline]
+fn inside(rect: &Rectangle, edge: Edge, p: &Point) -> bool {
+    match edge {
+        Edge::Left => p.x >= rect.min.x,
+        Edge::Right => p.x <= rect.max.x,
+        Edge::Bottom => p.y >= rect.min.y,
+        Edge::Top => p.y <= rect.max.y,
+    }
+}
+
+// The intersection of a->b with an edge. a and b are on opposite sides.
+#[inline]
+fn intersect(rect: &Rectangle, edge: Edge, a: &Point, b: &Point) -> Point {
+    match edge {
+        Edge::Left => Point::from_xy(
+            rect.min.x,
+            a.y + (b.y - a.y) * (rect.min.x - a.x) / (b.x - a.x),
+        ),
+        Edge::Right => Point::from_xy(
+            rect.max.x,
+            a.y + (b.y - a.y) * (rect.max.x - a.x) / (b.x - a.x),
+        ),
+        Edge::Bottom => Point::from_xy(
+            a.x + (b.x - a.x) * (rect.min.y - a.y) / (b.y - a.y),
+            rect.min.y,
+        ),
+        Edge::Top => Point::from_xy(
+            a.x + (b.x - a.x) * (rect.max.y - a.y) / (b.y - a.y),
+            rect.max.y,
+        ),
+    }
+}
@@ -0,0 +1,92 @@
+use geometry::point::Point;
+
+// A 2D triangle mesh, typically loaded as an asset rather than built in
+// code. The file format is the vertex and face subset of Wavefront OBJ:
+//
+//     # comment
+//     v x y [z]      (z is ignored)
+//     f a b c ...    (1 based vertex indices, polygons are fanned)
+//
+// Anything after a '/' in a face index (texture or normal indices) is
+// ignored, as are the other OBJ statements.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mesh {
+    pub vertices: Vec<Point>,
+    /// Indices into `vertices`.
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Self {
+            vertices: Vec::new(),
+            triangles: Vec::new(),
+        }
+    }
+
+    /// Errors name the offending line.
+    pub fn parse(text: &str) -> Result<Mesh, String> {
+        let mut mesh = Mesh::new();
+        // Faces may come before the vertices they use so they are checked
+        // at the end.
+        let mut faces: Vec<(usize, Vec<usize>)> = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("v") => {
+                    let coords: Vec<f64> = fields.filter_map(|f| f.parse().ok()).collect();
+                    if coords.len() < 2 {
+                        return Err(format!("line {}: vertex needs x and y", line_no));
+                    }
+                    mesh.vertices.push(Point::from_xy(coords[0], coords[1]));
+                }
+                Some("f") => {
+                    let mut face = Vec::new();
+                    for f in fields {
+                        let index = f.split('/').next().unwrap_or("");
+                        match index.parse::<usize>() {
+                            Ok(index) if index > 0 => face.push(index - 1),
+                            _ => return Err(format!("line {}: bad face index '{}'", line_no, f)),
+                        }
+                    }
+                    if face.len() < 3 {
+                        return Err(format!("line {}: face needs 3 or more vertices", line_no));
+                    }
+                    faces.push((line_no, face));
+                }
+                // Other OBJ statements (normals, groups...) don't apply.
+                _ => (),
+            }
+        }
+
+        for (line_no, face) in faces.iter() {
+            if face.iter().any(|&index| index >= mesh.vertices.len()) {
+                return Err(format!("line {}: face uses a missing vertex", line_no));
+            }
+            for k in 1..face.len() - 1 {
+                mesh.triangles.push([face[0], face[k], face[k + 1]]);
+            }
+        }
+
+        Ok(mesh)
+    }
+
+    /// The corners of a triangle.
+    pub fn triangle(&self, index: usize) -> Option<[Point; 3]> {
+        self.triangles.get(index).map(|t| {
+            [
+                self.vertices[t[0]],
+                self.vertices[t[1]],
+                self.vertices[t[2]],
+            ]
+        })
+    }
+}
@@ -1,3 +1,6 @@
 pub mod aabb;
+pub mod clipping;
+pub mod mesh;
 pub mod point;
 pub mod rectangle;
+pub mod shapes;
@@ -0,0 +1,136 @@
+use std::f64::consts::PI;
+
+use geometry::point::Point;
+
+// Approximate chord length, in device pixels, when tessellating curves.
+const CHORD_LENGTH: f64 = 4.0;
+const MIN_SEGMENTS: usize = 8;
+const MAX_SEGMENTS: usize = 512;
+
+/// Tessellates curved shapes into polygons/polylines.
+///
+/// Angles are in radians measured from +X towards +Y, which with a
+/// downward Y-axis is clockwise on screen. Each function clears `out`
+/// before appending the shape's vertices. Closed shapes don't repeat the
+/// first vertex.
+pub struct Shapes;
+
+impl Shapes {
+    /// The number of segments needed for a curve of `radius` (in device
+    /// pixels) sweeping through `sweep` radians to look smooth.
+    pub fn segments_for(radius: f64, sweep: f64) -> usize {
+        let full = (2.0 * PI * radius.abs() / CHORD_LENGTH).ceil() as usize;
+        let full = full.max(MIN_SEGMENTS).min(MAX_SEGMENTS);
+        let fraction = (sweep.abs() / (2.0 * PI)).min(1.0);
+
+        ((full as f64 * fraction).ceil() as usize).max(1)
+    }
+
+    pub fn circle(cx: f64, cy: f64, radius: f64, segments: usize, out: &mut Vec<Point>) {
+        Shapes::ellipse(cx, cy, radius, radius, segments, out);
+    }
+
+    pub fn ellipse(cx: f64, cy: f64, rx: f64, ry: f64, segments: usize, out: &mut Vec<Point>) {
+        out.clear();
+        let segments = segments.max(3);
+        for i in 0..segments {
+            let a = 2.0 * PI * i as f64 / segments as f64;
+            out.push(Point::from_xy(cx + rx * a.cos(), cy + ry * a.sin()));
+        }
+    }
+
+    /// An open polyline of `segments + 1` vertices.
+    #[allow(clippy::too_many_arguments)]
+    pub fn arc(
+        cx: f64,
+        cy: f64,
+        rx: f64,
+        ry: f64,
+        start: f64,
+        sweep: f64,
+        segments: usize,
+        out: &mut Vec<Point>,
+    ) {
+        out.clear();
+        append_arc(cx, cy, rx, ry, start, sweep, segments.max(1), out);
+    }
+
+    /// A closed pie slice, the center followed by the arc.
+    #[allow(clippy::too_many_arguments)]
+    pub fn pie(
+        cx: f64,
+        cy: f64,
+        rx: f64,
+        ry: f64,
+        start: f64,
+        sweep: f64,
+        segments: usize,
+        out: &mut Vec<Point>,
+    ) {
+        out.clear();
+        out.push(Point::from_xy(cx, cy));
+        append_arc(cx, cy, rx, ry, start, sweep, segments.max(1), out);
+    }
+
+    /// A rectangle whose corners are quarter circles of `radius`. The
+    /// radius is limited to half the shorter side. `corner_segments` is
+    /// the number of segments per corner.
+    pub fn rounded_rectangle(
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radius: f64,
+        corner_segments: usize,
+        out: &mut Vec<Point>,
+    ) {
+        out.clear();
+        let r = radius
+            .max(0.0)
+            .min(width.abs() / 2.0)
+            .min(height.abs() / 2.0);
+
+        if r == 0.0 {
+            out.push(Point::from_xy(x, y));
+            out.push(Point::from_xy(x + width, y));
+            out.push(Point::from_xy(x + width, y + height));
+            out.push(Point::from_xy(x, y + height));
+            return;
+        }
+
+        let segments = corner_segments.max(1);
+        let half = PI / 2.0;
+
+        // Clockwise on screen starting with the top-right corner.
+        append_arc(x + width - r, y + r, r, r, -half, half, segments, out);
+        append_arc(
+            x + width - r,
+            y + height - r,
+            r,
+            r,
+            0.0,
+            half,
+            segments,
+            out,
+        );
+        append_arc(x + r, y + height - r, r, r, half, half, segments, out);
+        append_arc(x + r, y + r, r, r, PI, half, segments, out);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn append_arc(
+    cx: f64,
+    cy: f64,
+    rx: f64,
+    ry: f64,
+    start: f64,
+    sweep: f64,
+    segments: usize,
+    out: &mut Vec<Point>,
+) {
+    for i in 0..=segments {
+        let a = start + sweep * i as f64 / segments as f64;
+        out.push(Point::from_xy(cx + rx * a.cos(), cy + ry * a.sin()));
+    }
+}
@@ -1,4 +1,6 @@
 pub mod animation;
+pub mod assets;
+pub mod config;
 pub mod engine;
 pub mod geometry;
 pub mod math;
@@ -0,0 +1,440 @@
+use geometry::point::Point;
+
+// Adaptive flattening limits. Each segment is subdivided at least
+// MIN_DEPTH times, which catches curves whose midpoint happens to lie on
+// the chord (e.g. an S shaped cubic), and at most MAX_DEPTH times.
+const MIN_DEPTH: usize = 2;
+const MAX_DEPTH: usize = 16;
+
+// The number of samples per segment used by Curve::length().
+const LENGTH_SAMPLES: usize = 64;
+
+/// A parametric curve over t = 0.0 -> 1.0.
+///
+/// Splines are made of several segments, each covering an equal share of
+/// the t range.
+pub trait Curve {
+    /// The point at `t`.
+    fn evaluate(&self, t: f64) -> Point;
+
+    /// The first derivative (tangent) at `t` with respect to `t`.
+    fn derivative(&self, t: f64) -> Point;
+
+    /// The number of polynomial segments.
+    fn segments(&self) -> usize {
+        1
+    }
+
+    /// Closed curves end where they started.
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    /// Approximates the curve with a polyline, replacing the contents of
+    /// `out`. No point on the polyline strays more than (roughly)
+    /// `tolerance` from the curve. Segment boundaries are always included.
+    fn flatten(&self, tolerance: f64, out: &mut Vec<Point>) {
+        out.clear();
+
+        let segments = self.segments();
+        if segments == 0 {
+            return;
+        }
+
+        let tolerance = tolerance.max(1.0e-6);
+        out.push(self.evaluate(0.0));
+
+        for s in 0..segments {
+            let t0 = s as f64 / segments as f64;
+            let t1 = (s + 1) as f64 / segments as f64;
+            let p0 = self.evaluate(t0);
+            let p1 = self.evaluate(t1);
+            subdivide(self, t0, &p0, t1, &p1, tolerance, 0, out);
+        }
+    }
+
+    /// The approximate length of the curve.
+    fn length(&self) -> f64 {
+        let samples = LENGTH_SAMPLES * self.segments().max(1);
+        let mut length = 0.0;
+        let mut prev = self.evaluate(0.0);
+        for i in 1..=samples {
+            let p = self.evaluate(i as f64 / samples as f64);
+            length += prev.distance_between(&p);
+            prev = p;
+        }
+        length
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn subdivide<C: Curve + ?Sized>(
+    curve: &C,
+    t0: f64,
+    p0: &Point,
+    t1: f64,
+    p1: &Point,
+    tolerance: f64,
+    depth: usize,
+    out: &mut Vec<Point>,
+) {
+    let tm = (t0 + t1) / 2.0;
+    let pm = curve.evaluate(tm);
+
+    let flat = depth >= MIN_DEPTH && distance_to_chord(&pm, p0, p1) <= tolerance;
+
+    if flat || depth >= MAX_DEPTH {
+        out.push(*p1);
+    } else {
+        subdivide(curve, t0, p0, tm, &pm, tolerance, depth + 1, out);
+        subdivide(curve, tm, &pm, t1, p1, tolerance, depth + 1, out);
+    }
+}
+
+// The distance from `p` to the line segment a->b.
+fn distance_to_chord(p: &Point, a: &Point, b: &Point) -> f64 {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let len2 = dx * dx + dy * dy;
+    if len2 == 0.0 {
+        return p.distance_between(a);
+    }
+
+    let s = (((p.x - a.x) * dx + (p.y - a.y) * dy) / len2).clamp(0.0, 1.0);
+    p.distance_between(&Point::from_xy(a.x + dx * s, a.y + dy * s))
+}
+
+#[inline]
+fn weighted(points: &[Point; 4], w: [f64; 4]) -> Point {
+    Point::from_xy(
+        points[0].x * w[0] + points[1].x * w[1] + points[2].x * w[2] + points[3].x * w[3],
+        points[0].y * w[0] + points[1].y * w[1] + points[2].y * w[2] + points[3].y * w[3],
+    )
+}
+
+// Maps a spline's global t to (segment, local t).
+#[inline]
+fn locate(t: f64, segments: usize) -> (usize, f64) {
+    let scaled = t.clamp(0.0, 1.0) * segments as f64;
+    let segment = (scaled.floor() as usize).min(segments - 1);
+    (segment, scaled - segment as f64)
+}
+
+// -----------------------------------------------------------------
+// Bezier
+// -----------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuadraticBezier {
+    pub p0: Point,
+    pub p1: Point,
+    pub p2: Point,
+}
+
+impl QuadraticBezier {
+    pub fn new(p0: Point, p1: Point, p2: Point) -> Self {
+        Self { p0, p1, p2 }
+    }
+}
+
+impl Curve for QuadraticBezier {
+    fn evaluate(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        let pts = [self.p0, self.p1, self.p2, self.p2];
+        weighted(&pts, [mt * mt, 2.0 * mt * t, t * t, 0.0])
+    }
+
+    fn derivative(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        let d0 = self.p1 - self.p0;
+        let d1 = self.p2 - self.p1;
+        Point::from_xy(2.0 * (mt * d0.x + t * d1.x), 2.0 * (mt * d0.y + t * d1.y))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CubicBezier {
+    pub p0: Point,
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+}
+
+impl CubicBezier {
+    pub fn new(p0: Point, p1: Point, p2: Point, p3: Point) -> Self {
+        Self { p0, p1, p2, p3 }
+    }
+}
+
+impl Curve for CubicBezier {
+    fn evaluate(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        let pts = [self.p0, self.p1, self.p2, self.p3];
+        weighted(
+            &pts,
+            [mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t],
+        )
+    }
+
+    fn derivative(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        let d0 = self.p1 - self.p0;
+        let d1 = self.p2 - self.p1;
+        let d2 = self.p3 - self.p2;
+        let pts = [d0, d1, d2, d2];
+        let d = weighted(&pts, [mt * mt, 2.0 * mt * t, t * t, 0.0]);
+        Point::from_xy(3.0 * d.x, 3.0 * d.y)
+    }
+}
+
+// -----------------------------------------------------------------
+// Splines
+// -----------------------------------------------------------------
+
+/// A uniform Catmull-Rom spline. It passes through every control point.
+/// Open splines duplicate the end points to define the end tangents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatmullRom {
+    points: Vec<Point>,
+    closed: bool,
+}
+
+impl CatmullRom {
+    pub fn new(points: Vec<Point>, closed: bool) -> Self {
+        Self { points, closed }
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    // The four control points of a segment.
+    fn controls(&self, segment: usize) -> [Point; 4] {
+        let n = self.points.len() as isize;
+        let i = segment as isize;
+        let at = |k: isize| {
+            if self.closed {
+                self.points[(((k % n) + n) % n) as usize]
+            } else {
+                self.points[k.max(0).min(n - 1) as usize]
+            }
+        };
+        [at(i - 1), at(i), at(i + 1), at(i + 2)]
+    }
+}
+
+impl Curve for CatmullRom {
+    fn evaluate(&self, t: f64) -> Point {
+        match self.points.len() {
+            0 => return Point::new(),
+            1 => return self.points[0],
+            _ => (),
+        }
+
+        let (segment, s) = locate(t, self.segments());
+        let s2 = s * s;
+        let s3 = s2 * s;
+        weighted(
+            &self.controls(segment),
+            [
+                0.5 * (-s3 + 2.0 * s2 - s),
+                0.5 * (3.0 * s3 - 5.0 * s2 + 2.0),
+                0.5 * (-3.0 * s3 + 4.0 * s2 + s),
+                0.5 * (s3 - s2),
+            ],
+        )
+    }
+
+    fn derivative(&self, t: f64) -> Point {
+        if self.points.len() < 2 {
+            return Point::new();
+        }
+
+        let segments = self.segments();
+        let (segment, s) = locate(t, segments);
+        let s2 = s * s;
+        let d = weighted(
+            &self.controls(segment),
+            [
+                0.5 * (-3.0 * s2 + 4.0 * s - 1.0),
+                0.5 * (9.0 * s2 - 10.0 * s),
+                0.5 * (-9.0 * s2 + 8.0 * s + 1.0),
+                0.5 * (3.0 * s2 - 2.0 * s),
+            ],
+        );
+        // Chain rule: d(s)/d(t) is the segment count.
+        Point::from_xy(d.x * segments as f64, d.y * segments as f64)
+    }
+
+    fn segments(&self) -> usize {
+        match self.points.len() {
+            0 | 1 => 0,
+            n if self.closed => n,
+            n => n - 1,
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+/// A uniform cubic B-spline. It is smoother than a Catmull-Rom spline but
+/// only approximates its control points. Open splines are clamped so they
+/// start and end on the first and last points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BSpline {
+    points: Vec<Point>,
+    closed: bool,
+}
+
+impl BSpline {
+    pub fn new(points: Vec<Point>, closed: bool) -> Self {
+        Self { points, closed }
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    // The four control points of a segment. Open splines behave as if the
+    // end points were repeated three times.
+    fn controls(&self, segment: usize) -> [Point; 4] {
+        let n = self.points.len() as isize;
+        let at = |k: isize| {
+            if self.closed {
+                self.points[(((k % n) + n) % n) as usize]
+            } else {
+                self.points[k.max(0).min(n - 1) as usize]
+            }
+        };
+        let i = if self.closed {
+            segment as isize
+        } else {
+            segment as isize - 2
+        };
+        [at(i), at(i + 1), at(i + 2), at(i + 3)]
+    }
+}
+
+impl Curve for BSpline {
+    fn evaluate(&self, t: f64) -> Point {
+        match self.points.len() {
+            0 => return Point::new(),
+            1 => return self.points[0],
+            _ => (),
+        }
+
+        let (segment, s) = locate(t, self.segments());
+        let mt = 1.0 - s;
+        let s2 = s * s;
+        let s3 = s2 * s;
+        weighted(
+            &self.controls(segment),
+            [
+                mt * mt * mt / 6.0,
+                (3.0 * s3 - 6.0 * s2 + 4.0) / 6.0,
+                (-3.0 * s3 + 3.0 * s2 + 3.0 * s + 1.0) / 6.0,
+                s3 / 6.0,
+            ],
+        )
+    }
+
+    fn derivative(&self, t: f64) -> Point {
+        if self.points.len() < 2 {
+            return Point::new();
+        }
+
+        let segments = self.segments();
+        let (segment, s) = locate(t, segments);
+        let mt = 1.0 - s;
+        let s2 = s * s;
+        let d = weighted(
+            &self.controls(segment),
+            [
+                -mt * mt / 2.0,
+                (3.0 * s2 - 4.0 * s) / 2.0,
+                (-3.0 * s2 + 2.0 * s + 1.0) / 2.0,
+                s2 / 2.0,
+            ],
+        );
+        Point::from_xy(d.x * segments as f64, d.y * segments as f64)
+    }
+
+    fn segments(&self) -> usize {
+        match self.points.len() {
+            0 | 1 => 0,
+            n if self.closed => n,
+            n => n + 1,
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+// -----------------------------------------------------------------
+// Arc length parameterization
+// -----------------------------------------------------------------
+
+/// Maps distance along a curve to the curve's parameter so that a point
+/// can travel along it at constant speed. The table is a snapshot, rebuild
+/// it if the curve changes.
+pub struct ArcLength {
+    // (t, distance from the start) pairs in increasing order.
+    table: Vec<(f64, f64)>,
+}
+
+impl ArcLength {
+    /// Builds the table from `samples` chords per curve segment.
+    pub fn new<C: Curve + ?Sized>(curve: &C, samples: usize) -> Self {
+        let count = samples.max(1) * curve.segments().max(1);
+        let mut table = Vec::with_capacity(count + 1);
+
+        let mut length = 0.0;
+        let mut prev = curve.evaluate(0.0);
+        table.push((0.0, 0.0));
+
+        for i in 1..=count {
+            let t = i as f64 / count as f64;
+            let p = curve.evaluate(t);
+            length += prev.distance_between(&p);
+            table.push((t, length));
+            prev = p;
+        }
+
+        Self { table }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.table[self.table.len() - 1].1
+    }
+
+    /// The parameter `t` at `distance` along the curve. Distances are
+    /// clamped to the curve's length.
+    pub fn parameter(&self, distance: f64) -> f64 {
+        let length = self.length();
+        if distance.is_nan() || distance <= 0.0 || length == 0.0 {
+            return 0.0;
+        }
+        if distance >= length {
+            return 1.0;
+        }
+
+        // First entry at or beyond the distance
+        let i = match self.table.binary_search_by(|e| e.1.total_cmp(&distance)) {
+            Ok(i) => return self.table[i].0,
+            Err(i) => i,
+        };
+
+        let (t0, d0) = self.table[i - 1];
+        let (t1, d1) = self.table[i];
+        t0 + (t1 - t0) * (distance - d0) / (d1 - d0)
+    }
+
+    /// The parameter for a normalized distance (0.0 -> 1.0) along the
+    /// curve.
+    pub fn parameter_normalized(&self, u: f64) -> f64 {
+        self.parameter(u * self.length())
+    }
+}
@@ -1,3 +1,7 @@
+use std::f64::consts::PI;
+
+use geometry::point::Point;
+
 pub struct Interpolation {}
 
 impl Interpolation {
@@ -18,4 +22,404 @@ impl Interpolation {
             (value - min) / (max - min)
         }
     }
+
+    // `smoothstep` returns 0->1 for a "value" between edge0 and edge1
+    // with a smooth (zero slope) start and finish.
+    // Values outside of the edges are clamped.
+    #[inline(always)]
+    pub fn smoothstep(edge0: f64, edge1: f64, value: f64) -> f64 {
+        let t = clamp_unit((value - edge0) / (edge1 - edge0));
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    // Ken Perlin's variation of `smoothstep` that also has zero 2nd order
+    // derivatives at the edges.
+    #[inline(always)]
+    pub fn smootherstep(edge0: f64, edge1: f64, value: f64) -> f64 {
+        let t = clamp_unit((value - edge0) / (edge1 - edge0));
+        t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+    }
+
+    // Lerps between min and max after shaping t with an easing function.
+    #[inline(always)]
+    pub fn ease(min: f64, max: f64, t: f64, easing: &Easing) -> f64 {
+        Interpolation::lerp(min, max, easing.ease(t))
+    }
+
+    // ---------------------------------------------------------
+    // Angles
+    // `period` is a full revolution, for example, 360.0 or 2*PI.
+    // ---------------------------------------------------------
+
+    // Wraps an angle into the range [0, period)
+    #[inline(always)]
+    pub fn wrap_angle(angle: f64, period: f64) -> f64 {
+        let wrapped = angle % period;
+        if wrapped < 0.0 {
+            wrapped + period
+        } else {
+            wrapped
+        }
+    }
+
+    // Returns the signed difference (to - from) along the shortest arc.
+    // The result is within [-period/2, period/2).
+    #[inline(always)]
+    pub fn shortest_angle_delta(from: f64, to: f64, period: f64) -> f64 {
+        let half = period / 2.0;
+        Interpolation::wrap_angle(to - from + half, period) - half
+    }
+
+    // Lerps from one angle to another along the shortest arc. The result is
+    // wrapped into [0, period).
+    #[inline(always)]
+    pub fn lerp_angle(from: f64, to: f64, t: f64, period: f64) -> f64 {
+        let delta = Interpolation::shortest_angle_delta(from, to, period);
+        Interpolation::wrap_angle(from + delta * t, period)
+    }
+
+    #[inline(always)]
+    pub fn lerp_degrees(from: f64, to: f64, t: f64) -> f64 {
+        Interpolation::lerp_angle(from, to, t, 360.0)
+    }
+
+    #[inline(always)]
+    pub fn lerp_radians(from: f64, to: f64, t: f64) -> f64 {
+        Interpolation::lerp_angle(from, to, t, 2.0 * PI)
+    }
+}
+
+/// Types that can be linearly interpolated between two values.
+/// t = 0.0 yields `from` and t = 1.0 yields `to`.
+pub trait Lerp: Copy {
+    fn lerp(from: &Self, to: &Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    #[inline(always)]
+    fn lerp(from: &f64, to: &f64, t: f64) -> f64 {
+        Interpolation::lerp(*from, *to, t)
+    }
+}
+
+impl Lerp for Point {
+    #[inline(always)]
+    fn lerp(from: &Point, to: &Point, t: f64) -> Point {
+        Point::from_xy(
+            Interpolation::lerp(from.x, to.x, t),
+            Interpolation::lerp(from.y, to.y, t),
+        )
+    }
+}
+
+// Overshoot constants used by the "Back" and "Elastic" families.
+const BACK_C1: f64 = 1.70158;
+const BACK_C2: f64 = BACK_C1 * 1.525;
+const BACK_C3: f64 = BACK_C1 + 1.0;
+const ELASTIC_C4: f64 = (2.0 * PI) / 3.0;
+const ELASTIC_C5: f64 = (2.0 * PI) / 4.5;
+
+// Cubic bezier solver tuning
+const BEZIER_NEWTON_ITERATIONS: usize = 8;
+const BEZIER_EPSILON: f64 = 1.0e-7;
+
+/// Easing functions shape a normalized time `t` (0.0 -> 1.0) into a
+/// normalized progress. All easings return 0.0 at t = 0 and 1.0 at t = 1.
+///
+/// The `Back` and `Elastic` families overshoot the 0->1 range
+/// in-between the endpoints.
+///
+/// Formulas follow Robert Penner's easing equations:
+/// http://robertpenner.com/easing/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+
+    SineIn,
+    SineOut,
+    SineInOut,
+
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+
+    CircIn,
+    CircOut,
+    CircInOut,
+
+    BackIn,
+    BackOut,
+    BackInOut,
+
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+
+    SmoothStep,
+    SmootherStep,
+
+    /// A custom curve defined the same way as CSS's `cubic-bezier(x1, y1, x2, y2)`.
+    /// The curve starts at (0,0) and ends at (1,1) with the two
+    /// control points in-between. x1 and x2 should be within 0->1.
+    CubicBezier(f64, f64, f64, f64),
+}
+
+impl Easing {
+    /// Returns the eased value for `t`. `t` is clamped to 0.0 -> 1.0
+    pub fn ease(&self, t: f64) -> f64 {
+        let t = clamp_unit(t);
+
+        match *self {
+            Easing::Linear => t,
+
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+
+            Easing::QuartIn => t.powi(4),
+            Easing::QuartOut => 1.0 - (1.0 - t).powi(4),
+            Easing::QuartInOut => {
+                if t < 0.5 {
+                    8.0 * t.powi(4)
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(4) / 2.0
+                }
+            }
+
+            Easing::QuintIn => t.powi(5),
+            Easing::QuintOut => 1.0 - (1.0 - t).powi(5),
+            Easing::QuintInOut => {
+                if t < 0.5 {
+                    16.0 * t.powi(5)
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(5) / 2.0
+                }
+            }
+
+            Easing::SineIn => 1.0 - ((t * PI) / 2.0).cos(),
+            Easing::SineOut => ((t * PI) / 2.0).sin(),
+            Easing::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+
+            Easing::ExpoIn => {
+                if t == 0.0 {
+                    0.0
+                } else {
+                    2.0f64.powf(10.0 * t - 10.0)
+                }
+            }
+            Easing::ExpoOut => {
+                if t == 1.0 {
+                    1.0
+                } else {
+                    1.0 - 2.0f64.powf(-10.0 * t)
+                }
+            }
+            Easing::ExpoInOut => {
+                if t == 0.0 {
+                    0.0
+                } else if t == 1.0 {
+                    1.0
+                } else if t < 0.5 {
+                    2.0f64.powf(20.0 * t - 10.0) / 2.0
+                } else {
+                    (2.0 - 2.0f64.powf(-20.0 * t + 10.0)) / 2.0
+                }
+            }
+
+            Easing::CircIn => 1.0 - (1.0 - t * t).sqrt(),
+            Easing::CircOut => (1.0 - (t - 1.0).powi(2)).sqrt(),
+            Easing::CircInOut => {
+                if t < 0.5 {
+                    (1.0 - (1.0 - (2.0 * t).powi(2)).sqrt()) / 2.0
+                } else {
+                    ((1.0 - (-2.0 * t + 2.0).powi(2)).sqrt() + 1.0) / 2.0
+                }
+            }
+
+            Easing::BackIn => BACK_C3 * t * t * t - BACK_C1 * t * t,
+            Easing::BackOut => {
+                1.0 + BACK_C3 * (t - 1.0).powi(3) + BACK_C1 * (t - 1.0).powi(2)
+            }
+            Easing::BackInOut => {
+                if t < 0.5 {
+                    ((2.0 * t).powi(2) * ((BACK_C2 + 1.0) * 2.0 * t - BACK_C2)) / 2.0
+                } else {
+                    ((2.0 * t - 2.0).powi(2) * ((BACK_C2 + 1.0) * (t * 2.0 - 2.0) + BACK_C2)
+                        + 2.0)
+                        / 2.0
+                }
+            }
+
+            Easing::ElasticIn => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    -(2.0f64.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * ELASTIC_C4).sin()
+                }
+            }
+            Easing::ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2.0f64.powf(-10.0 * t) * ((t * 10.0 - 0.75) * ELASTIC_C4).sin() + 1.0
+                }
+            }
+            Easing::ElasticInOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else if t < 0.5 {
+                    -(2.0f64.powf(20.0 * t - 10.0) * ((20.0 * t - 11.125) * ELASTIC_C5).sin())
+                        / 2.0
+                } else {
+                    (2.0f64.powf(-20.0 * t + 10.0) * ((20.0 * t - 11.125) * ELASTIC_C5).sin())
+                        / 2.0
+                        + 1.0
+                }
+            }
+
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut => {
+                if t < 0.5 {
+                    (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0
+                } else {
+                    (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0
+                }
+            }
+
+            Easing::SmoothStep => Interpolation::smoothstep(0.0, 1.0, t),
+            Easing::SmootherStep => Interpolation::smootherstep(0.0, 1.0, t),
+
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+        }
+    }
+
+    /// Returns `true` if the easing stays within 0.0 -> 1.0 and never
+    /// reverses direction.
+    pub fn is_monotonic(&self) -> bool {
+        match *self {
+            Easing::BackIn
+            | Easing::BackOut
+            | Easing::BackInOut
+            | Easing::ElasticIn
+            | Easing::ElasticOut
+            | Easing::ElasticInOut
+            | Easing::BounceIn
+            | Easing::BounceOut
+            | Easing::BounceInOut => false,
+            Easing::CubicBezier(_, y1, _, y2) => {
+                (0.0..=1.0).contains(&y1) && (0.0..=1.0).contains(&y2)
+            }
+            _ => true,
+        }
+    }
+}
+
+#[inline(always)]
+fn clamp_unit(t: f64) -> f64 {
+    t.clamp(0.0, 1.0)
+}
+
+fn bounce_out(t: f64) -> f64 {
+    const N1: f64 = 7.5625;
+    const D1: f64 = 2.75;
+
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+// One dimension of a cubic bezier whose end points are fixed at 0 and 1.
+#[inline(always)]
+fn bezier_component(p1: f64, p2: f64, s: f64) -> f64 {
+    let is = 1.0 - s;
+    3.0 * is * is * s * p1 + 3.0 * is * s * s * p2 + s * s * s
+}
+
+#[inline(always)]
+fn bezier_component_slope(p1: f64, p2: f64, s: f64) -> f64 {
+    let is = 1.0 - s;
+    3.0 * is * is * p1 + 6.0 * is * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+}
+
+// Finds the curve parameter `s` whose x equals `t` (Newton first and then
+// bisection if Newton fails to converge) and returns the matching y.
+fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64, t: f64) -> f64 {
+    if t == 0.0 || t == 1.0 {
+        return t;
+    }
+
+    let mut s = t;
+    for _ in 0..BEZIER_NEWTON_ITERATIONS {
+        let x = bezier_component(x1, x2, s) - t;
+        if x.abs() < BEZIER_EPSILON {
+            return bezier_component(y1, y2, s);
+        }
+        let slope = bezier_component_slope(x1, x2, s);
+        if slope.abs() < BEZIER_EPSILON {
+            break;
+        }
+        s -= x / slope;
+    }
+
+    // Fallback to bisection
+    let mut lo = 0.0;
+    let mut hi = 1.0;
+    s = t;
+    while hi - lo > BEZIER_EPSILON {
+        let x = bezier_component(x1, x2, s);
+        if x < t {
+            lo = s;
+        } else {
+            hi = s;
+        }
+        s = (lo + hi) / 2.0;
+    }
+
+    bezier_component(y1, y2, s)
 }
@@ -1,3 +1,4 @@
 pub mod affine_transform;
+pub mod curve;
 pub mod interpolation;
 pub mod vector2;
@@ -0,0 +1,184 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use animation::clip::AnimationClip;
+use nodes::{
+    node::{NodeGroup, NodeTrait, NodeType, Nodes, RNode, RONode},
+    node_properties::NodeData,
+};
+use world::World;
+
+// An animator is a headless node that plays an AnimationClip against the
+// nodes found (via path lookup) under a bound root node.
+//
+// The clip is advanced and applied in `update()` which means it is driven
+// by the Scheduler's fixed step. Like any timing target it must be
+// registered with the Scheduler, for example, via Nodes::register_timing_targets.
+
+pub type ClipEventHandler = Box<dyn Fn(&str)>;
+
+pub struct AnimatorNode {
+    data: RefCell<NodeData>,
+
+    // Hierarchy
+    parent: RONode,
+
+    clip: RefCell<AnimationClip>,
+
+    // One binding per NodeTracks of the clip. `None` if the path could
+    // not be resolved.
+    bindings: RefCell<Vec<Option<RNode>>>,
+
+    on_event: RefCell<Option<ClipEventHandler>>,
+
+    // Scratch buffer for fired events.
+    fired: RefCell<Vec<String>>,
+}
+
+impl Drop for AnimatorNode {
+    fn drop(&mut self) {
+        println!("Dropping: '{}'", self.data().borrow().node.name());
+    }
+}
+
+impl AnimatorNode {
+    pub fn new(name: &str, parent: Option<RNode>, clip: AnimationClip, world: &mut World) -> RNode {
+        let mut n = NodeData::new();
+        n.node.set_name(name.to_string());
+        n.node.set_type(NodeType::Node);
+        n.node.set_id(world.gen_id());
+        n.node.make_timing_target(true);
+
+        let an = Self {
+            data: RefCell::new(n),
+            parent: Rc::new(RefCell::new(parent)),
+            clip: RefCell::new(clip),
+            bindings: RefCell::new(Vec::new()),
+            on_event: RefCell::new(None),
+            fired: RefCell::new(Vec::new()),
+        };
+
+        let rc: RNode = Rc::new(RefCell::new(an));
+
+        NodeGroup::attach_parent(&rc);
+
+        rc
+    }
+
+    /// Resolves each track's path relative to `root`. Unresolved paths
+    /// are reported and skipped during playback.
+    pub fn bind(&self, root: &RNode) {
+        let clip = self.clip.borrow();
+        let mut bindings = self.bindings.borrow_mut();
+        bindings.clear();
+
+        for tracks in clip.tracks().iter() {
+            let node = Nodes::find_node_by_path(&tracks.path, root);
+            if node.is_none() {
+                println!(
+                    "AnimatorNode '{}': could not bind path '{}'",
+                    self.name(),
+                    tracks.path
+                );
+            }
+            bindings.push(node);
+        }
+    }
+
+    pub fn clip(&self) -> &RefCell<AnimationClip> {
+        &self.clip
+    }
+
+    pub fn play(&self) {
+        self.clip.borrow_mut().play();
+        self.apply();
+    }
+
+    pub fn stop(&self) {
+        self.clip.borrow_mut().stop();
+    }
+
+    /// `handler` is called with the event's name each time playback
+    /// crosses a clip event.
+    pub fn set_event_handler(&self, handler: ClipEventHandler) {
+        self.on_event.replace(Some(handler));
+    }
+
+    fn apply(&self) {
+        let clip = self.clip.borrow();
+        let bindings = self.bindings.borrow();
+
+        for (index, binding) in bindings.iter().enumerate() {
+            if let Some(ref node) = *binding {
+                let pose = clip.sample(index);
+                let n = node.borrow();
+
+                if let Some(p) = pose.position {
+                    n.set_position(p.x, p.y);
+                }
+                if let Some(degrees) = pose.rotation {
+                    n.set_rotation_degrees(degrees);
+                }
+                if let Some(s) = pose.scale {
+                    n.set_nonuniform_scale(s.x, s.y);
+                }
+                if let Some(visible) = pose.visible {
+                    n.set_visible(visible);
+                }
+                if let Some(color) = pose.color {
+                    n.set_color(color);
+                }
+            }
+        }
+    }
+}
+
+impl NodeTrait for AnimatorNode {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    // --------------------------------------------------------
+    // Node properties
+    // --------------------------------------------------------
+    fn data(&self) -> &RefCell<NodeData> {
+        &self.data
+    }
+
+    // --------------------------------------------------------
+    // Transformations
+    // --------------------------------------------------------
+    fn parent(&self) -> RONode {
+        self.parent.clone()
+    }
+
+    fn set_parent(&self, parent: RNode) {
+        self.parent.borrow_mut().replace(parent);
+    }
+
+    // --------------------------------------------------------
+    // Timing target
+    // --------------------------------------------------------
+    fn update(&self, dt: f64) {
+        if !self.clip.borrow().is_playing() {
+            return;
+        }
+
+        let mut fired = self.fired.borrow_mut();
+        fired.clear();
+
+        self.clip.borrow_mut().advance(dt, &mut fired);
+        self.apply();
+
+        if let Some(ref handler) = *self.on_event.borrow() {
+            for name in fired.iter() {
+                handler(name);
+            }
+        }
+    }
+}
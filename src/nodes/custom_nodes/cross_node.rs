@@ -1,6 +1,6 @@
 use std::any::Any;
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use geometry::point::Point;
@@ -8,7 +8,7 @@ use nodes::{
     node::{NodeGroup, NodeTrait, NodeType, RNode, RONode},
     node_properties::NodeData,
 };
-use rendering::{color::Palette, render_context::Context};
+use rendering::{color::Palette, render_context::Context, stroke::LineStyle};
 use world::World;
 
 // A basic leaf node that renders a "+"
@@ -23,6 +23,9 @@ pub struct CrossNode {
     vertices: Vec<Point>,
     // Transformed vertices
     bucket: RefCell<Vec<Point>>,
+
+    color: Cell<Palette>,
+    line_style: Cell<LineStyle>,
 }
 
 impl Drop for CrossNode {
@@ -43,6 +46,8 @@ impl CrossNode {
             parent: Rc::new(RefCell::new(parent)),
             vertices: Vec::new(),
             bucket: RefCell::new(Vec::new()),
+            color: Cell::new(Palette::WHITE(255)),
+            line_style: Cell::new(LineStyle::Hairline),
         };
 
         CrossNode::construct(&mut tn, world);
@@ -54,6 +59,10 @@ impl CrossNode {
         rc
     }
 
+    pub fn set_line_style(&self, style: LineStyle) {
+        self.line_style.set(style);
+    }
+
     fn construct(node: &mut CrossNode, _world: &mut World) {
         // Horizontal
         node.vertices.push(Point::from_xy(-0.5, 0.0));
@@ -96,15 +105,19 @@ impl NodeTrait for CrossNode {
             self.set_node_dirty(false);
         }
 
-        context.set_draw_color(&Palette::WHITE(255));
+        context.set_draw_color(&self.color.get());
 
-        context.render_lines(&self.bucket);
+        context.render_styled_lines(&self.bucket, &self.line_style.get());
 
         // let b = self.bucket.borrow();
         // context.render_line(b[0].x, b[0].y, b[1].x, b[1].y);
         // context.render_line(b[2].x, b[2].y, b[3].x, b[3].y);
     }
 
+    fn set_color(&self, color: Palette) {
+        self.color.set(color);
+    }
+
     // --------------------------------------------------------
     // Transformations
     // --------------------------------------------------------
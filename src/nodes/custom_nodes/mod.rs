@@ -1,3 +1,7 @@
+pub mod animator_node;
 pub mod cross_node;
 pub mod orbit_anchor_node;
+pub mod render_target_node;
+pub mod shape_node;
+pub mod sprite_node;
 pub mod vector_text_node;
@@ -0,0 +1,291 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use geometry::point::Point;
+use math::affine_transform::AffineTransform;
+use nodes::{
+    node::{NodeGroup, NodeTrait, NodeType, RNode, RONode},
+    node_properties::NodeData,
+    scenes::scene_manager::GlobalSceneData,
+};
+use rendering::{
+    color::Palette,
+    image::ImgRectangle,
+    render_context::{Context, TextureId, TextureReleaser},
+};
+use world::World;
+
+// A group node that renders its children into an offscreen texture and
+// then draws the texture as a quad with the node's own transform.
+//
+// Children are drawn in texel space: the origin is the texture's center
+// and one unit is one texel, regardless of where the node is placed. The
+// quad is sized to the texture in local units, so at a scale of 1.0 one
+// texel covers one view unit.
+//
+// A cached node only re-renders its children after `invalidate()`, which
+// suits static layers. Otherwise they are rendered every frame, which is
+// what minimaps and post-processing want.
+//
+// The texture is freed when the node exits its scene or is dropped, and
+// created again if the node is drawn after that.
+
+pub struct RenderTargetNode {
+    data: RefCell<NodeData>,
+
+    children: Option<RefCell<Vec<RNode>>>,
+
+    // Hierarchy
+    parent: RONode,
+
+    width: u32,
+    height: u32,
+
+    // Created on first use.
+    texture: Cell<Option<TextureId>>,
+    create_failed: Cell<bool>,
+    // Set with the texture.
+    releaser: RefCell<Option<TextureReleaser>>,
+
+    clear_color: Cell<Palette>,
+    cached: Cell<bool>,
+    valid: Cell<bool>,
+
+    // Quad vertices: top-left, top-right, bottom-right, bottom-left
+    vertices: RefCell<Vec<Point>>,
+    // Transformed vertices
+    bucket: RefCell<Vec<Point>>,
+}
+
+impl Drop for RenderTargetNode {
+    fn drop(&mut self) {
+        println!("Dropping: '{}'", self.data().borrow().node.name());
+        self.release_target();
+    }
+}
+
+impl RenderTargetNode {
+    pub fn new(
+        name: &str,
+        parent: Option<RNode>,
+        width: u32,
+        height: u32,
+        world: &mut World,
+    ) -> RNode {
+        let mut n = NodeData::new();
+        n.node.set_name(name.to_string());
+        n.node.set_type(NodeType::Node);
+        n.node.set_id(world.gen_id());
+
+        let w = width as f64;
+        let h = height as f64;
+        let vertices = vec![
+            Point::from_xy(-w / 2.0, -h / 2.0),
+            Point::from_xy(w / 2.0, -h / 2.0),
+            Point::from_xy(w / 2.0, h / 2.0),
+            Point::from_xy(-w / 2.0, h / 2.0),
+        ];
+
+        let rn = Self {
+            data: RefCell::new(n),
+            children: Some(RefCell::new(Vec::new())),
+            parent: Rc::new(RefCell::new(parent)),
+            width,
+            height,
+            texture: Cell::new(None),
+            create_failed: Cell::new(false),
+            releaser: RefCell::new(None),
+            clear_color: Cell::new(Palette::RGBA(0, 0, 0, 0)),
+            cached: Cell::new(false),
+            valid: Cell::new(false),
+            vertices: RefCell::new(vertices),
+            bucket: RefCell::new(vec![Point::new(); 4]),
+        };
+
+        let rc: RNode = Rc::new(RefCell::new(rn));
+
+        NodeGroup::attach_parent(&rc);
+
+        rc
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The texture the children are rendered into, if it has been created.
+    /// Other nodes may draw it too, e.g. as a thumbnail.
+    pub fn texture(&self) -> Option<TextureId> {
+        self.texture.get()
+    }
+
+    /// The texture is cleared to this color before the children are
+    /// drawn. Defaults to transparent.
+    pub fn set_clear_color(&self, color: Palette) {
+        self.clear_color.set(color);
+        self.valid.set(false);
+    }
+
+    /// If cached the children are only rendered when invalidated.
+    pub fn set_cached(&self, cached: bool) {
+        self.cached.set(cached);
+    }
+
+    pub fn is_cached(&self) -> bool {
+        self.cached.get()
+    }
+
+    /// Forces the children to be rendered on the next visit.
+    pub fn invalidate(&self) {
+        self.valid.set(false);
+    }
+
+    fn target(&self, context: &Context) -> Option<TextureId> {
+        if self.texture.get().is_none() && !self.create_failed.get() {
+            match context.create_render_target(self.width, self.height) {
+                Ok(id) => {
+                    self.texture.set(Some(id));
+                    *self.releaser.borrow_mut() = Some(context.texture_releaser());
+                    self.valid.set(false);
+                }
+                Err(err) => {
+                    println!("RenderTargetNode: target creation failed: {}", err);
+                    self.create_failed.set(true);
+                }
+            }
+        }
+
+        self.texture.get()
+    }
+
+    fn release_target(&self) {
+        if let (Some(texture), Some(releaser)) = (self.texture.take(), self.releaser.take()) {
+            releaser.release(texture);
+        }
+    }
+
+    fn render_children(&self, context: &mut Context, interpolation: f64) {
+        if self.cached.get() && self.valid.get() {
+            return;
+        }
+
+        let target = match self.target(context) {
+            Some(target) => target,
+            None => return,
+        };
+
+        if let Err(err) =
+            context.begin_render_target(target, self.width, self.height, self.clear_color.get())
+        {
+            println!("RenderTargetNode: {}", err);
+            return;
+        }
+
+        if let Some(children) = self.get_children() {
+            for child in children.borrow().iter() {
+                child.borrow().visit(context, interpolation);
+            }
+        }
+
+        context.end_render_target();
+
+        self.valid.set(true);
+    }
+}
+
+impl NodeTrait for RenderTargetNode {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    // --------------------------------------------------------
+    // Node properties
+    // --------------------------------------------------------
+    fn data(&self) -> &RefCell<NodeData> {
+        &self.data
+    }
+
+    // --------------------------------------------------------
+    // Rendering: visiting and drawing
+    // --------------------------------------------------------
+    // The children are not visited in the node's space but rendered into
+    // the texture first.
+    fn visit(&self, context: &mut Context, interpolation: f64) {
+        if !self.is_visible() || self.opacity() == 0.0 {
+            return;
+        }
+
+        self.interpolate(interpolation);
+
+        self.render_children(context, interpolation);
+
+        context.save();
+
+        {
+            let mut data = self.data().borrow_mut();
+            let aft: &AffineTransform = if data.node.is_dirty() {
+                data.transform.calc_transform()
+            } else {
+                data.transform.get_transform()
+            };
+
+            context.apply(aft);
+            context.apply_opacity(data.node.opacity());
+        }
+
+        self.draw(context);
+
+        context.restore();
+    }
+
+    fn draw(&self, context: &mut Context) {
+        let texture = match self.texture.get() {
+            Some(texture) => texture,
+            None => return,
+        };
+
+        // The parent's transform may have changed so always transform.
+        context.transform(&self.vertices.borrow(), &self.bucket);
+
+        let region = ImgRectangle::from_size(self.width as usize, self.height as usize);
+        context.render_texture(texture, &region, &self.bucket);
+    }
+
+    // --------------------------------------------------------
+    // Transformations
+    // --------------------------------------------------------
+    fn parent(&self) -> RONode {
+        self.parent.clone()
+    }
+
+    fn set_parent(&self, parent: RNode) {
+        self.parent.borrow_mut().replace(parent);
+    }
+
+    // --------------------------------------------------------
+    // Grouping
+    // --------------------------------------------------------
+    fn get_children(&self) -> &Option<RefCell<Vec<RNode>>> {
+        &self.children
+    }
+
+    // --------------------------------------------------------
+    // Life cycle events
+    // --------------------------------------------------------
+    fn exit(&self, data: &mut GlobalSceneData) {
+        self.release_target();
+
+        if let Some(children) = self.get_children() {
+            self.sub_exit(data, children);
+        }
+    }
+}
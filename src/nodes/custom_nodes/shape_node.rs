@@ -0,0 +1,173 @@
+use std::any::Any;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use nodes::{
+    node::{NodeGroup, NodeTrait, NodeType, RNode, RONode},
+    node_properties::NodeData,
+};
+use rendering::{
+    color::Palette,
+    render_context::{Context, RenderStyle},
+};
+use world::World;
+
+// A leaf node that renders a curved shape. Shapes are unit sized and
+// centered on the node's origin, use the node's scale to size them.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Circle,
+    /// `ratio` is the height relative to the width.
+    Ellipse {
+        ratio: f64,
+    },
+    /// An outline only arc. Angles are in radians.
+    Arc {
+        start: f64,
+        sweep: f64,
+    },
+    /// Angles are in radians.
+    Pie {
+        start: f64,
+        sweep: f64,
+    },
+    /// `radius` is relative to the shape's unit size.
+    RoundedRectangle {
+        radius: f64,
+    },
+}
+
+pub struct ShapeNode {
+    data: RefCell<NodeData>,
+
+    // Hierarchy
+    parent: RONode,
+
+    shape: Cell<Shape>,
+    style: Cell<RenderStyle>,
+
+    fill_color: Cell<Palette>,
+    outline_color: Cell<Palette>,
+}
+
+impl Drop for ShapeNode {
+    fn drop(&mut self) {
+        println!("Dropping: '{}'", self.data().borrow().node.name());
+    }
+}
+
+impl ShapeNode {
+    pub fn new(name: &str, parent: Option<RNode>, shape: Shape, world: &mut World) -> RNode {
+        let mut n = NodeData::new();
+        n.node.set_name(name.to_string());
+        n.node.set_type(NodeType::Node);
+        n.node.set_id(world.gen_id());
+
+        let sn = Self {
+            data: RefCell::new(n),
+            parent: Rc::new(RefCell::new(parent)),
+            shape: Cell::new(shape),
+            style: Cell::new(RenderStyle::FILLED),
+            fill_color: Cell::new(Palette::WHITE(255)),
+            outline_color: Cell::new(Palette::WHITE(255)),
+        };
+
+        let rc: Rc<RefCell<NodeTrait>> = Rc::new(RefCell::new(sn));
+
+        NodeGroup::attach_parent(&rc);
+
+        rc
+    }
+
+    pub fn set_shape(&self, shape: Shape) {
+        self.shape.set(shape);
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape.get()
+    }
+
+    pub fn set_style(&self, style: RenderStyle) {
+        self.style.set(style);
+    }
+
+    pub fn set_fill_color(&self, color: Palette) {
+        self.fill_color.set(color);
+    }
+
+    pub fn set_outline_color(&self, color: Palette) {
+        self.outline_color.set(color);
+    }
+
+    fn render(&self, context: &Context, style: RenderStyle) {
+        match self.shape.get() {
+            Shape::Circle => context.render_circle(0.0, 0.0, 0.5, style),
+            Shape::Ellipse { ratio } => context.render_ellipse(0.0, 0.0, 0.5, 0.5 * ratio, style),
+            Shape::Arc { start, sweep } => context.render_arc(0.0, 0.0, 0.5, 0.5, start, sweep),
+            Shape::Pie { start, sweep } => {
+                context.render_pie(0.0, 0.0, 0.5, 0.5, start, sweep, style)
+            }
+            Shape::RoundedRectangle { radius } => {
+                context.render_rounded_rectangle(-0.5, -0.5, 1.0, 1.0, radius, style)
+            }
+        }
+    }
+}
+
+impl NodeTrait for ShapeNode {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    // --------------------------------------------------------
+    // Node properties
+    // --------------------------------------------------------
+    fn data(&self) -> &RefCell<NodeData> {
+        &self.data
+    }
+
+    // --------------------------------------------------------
+    // Rendering
+    // --------------------------------------------------------
+    fn draw(&self, context: &mut Context) {
+        // The fill and outline have their own colors so BOTH is drawn
+        // as two passes.
+        let style = match self.shape.get() {
+            Shape::Arc { .. } => RenderStyle::OUTLINE,
+            _ => self.style.get(),
+        };
+
+        if style != RenderStyle::OUTLINE {
+            context.set_draw_color(&self.fill_color.get());
+            self.render(context, RenderStyle::FILLED);
+        }
+
+        if style != RenderStyle::FILLED {
+            context.set_draw_color(&self.outline_color.get());
+            self.render(context, RenderStyle::OUTLINE);
+        }
+    }
+
+    /// Sets both the fill and outline colors.
+    fn set_color(&self, color: Palette) {
+        self.fill_color.set(color);
+        self.outline_color.set(color);
+    }
+
+    // --------------------------------------------------------
+    // Transformations
+    // --------------------------------------------------------
+    fn parent(&self) -> RONode {
+        self.parent.clone()
+    }
+
+    fn set_parent(&self, parent: RNode) {
+        self.parent.borrow_mut().replace(parent);
+    }
+}
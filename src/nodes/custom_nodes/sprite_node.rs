@@ -0,0 +1,199 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use animation::sprite::SpriteAnimator;
+use geometry::point::Point;
+use nodes::{
+    node::{NodeGroup, NodeTrait, NodeType, RNode, RONode},
+    node_properties::NodeData,
+};
+use rendering::{render_context::Context, sprite_sheet::SpriteSheet};
+use world::World;
+
+// A leaf node that draws a frame of a SpriteSheet.
+//
+// The quad is sized to the frame in local units, so at a scale of 1.0 one
+// texel covers one view unit. The anchor, in normalized frame
+// coordinates, is the point that sits at the node's position; the
+// default (0.5, 0.5) is the frame's center.
+//
+// If the animator has an active clip its frame is shown, otherwise the
+// static frame set via `set_frame`. The animator is advanced in `update()`
+// so the node must be registered as a timing target with the Scheduler.
+
+pub struct SpriteNode {
+    data: RefCell<NodeData>,
+
+    // Hierarchy
+    parent: RONode,
+
+    sheet: Rc<SpriteSheet>,
+    animator: RefCell<SpriteAnimator>,
+
+    frame: Cell<usize>,
+    anchor: Cell<Point>,
+
+    // The frame the vertices were last built for.
+    shown: Cell<Option<usize>>,
+
+    // Quad vertices: top-left, top-right, bottom-right, bottom-left
+    vertices: RefCell<Vec<Point>>,
+    // Transformed vertices
+    bucket: RefCell<Vec<Point>>,
+}
+
+impl Drop for SpriteNode {
+    fn drop(&mut self) {
+        println!("Dropping: '{}'", self.data().borrow().node.name());
+    }
+}
+
+impl SpriteNode {
+    pub fn new(
+        name: &str,
+        parent: Option<RNode>,
+        sheet: Rc<SpriteSheet>,
+        world: &mut World,
+    ) -> RNode {
+        let mut n = NodeData::new();
+        n.node.set_name(name.to_string());
+        n.node.set_type(NodeType::Node);
+        n.node.set_id(world.gen_id());
+        n.node.make_timing_target(true);
+
+        let sn = Self {
+            data: RefCell::new(n),
+            parent: Rc::new(RefCell::new(parent)),
+            sheet,
+            animator: RefCell::new(SpriteAnimator::new()),
+            frame: Cell::new(0),
+            anchor: Cell::new(Point::from_xy(0.5, 0.5)),
+            shown: Cell::new(None),
+            vertices: RefCell::new(vec![Point::new(); 4]),
+            bucket: RefCell::new(vec![Point::new(); 4]),
+        };
+
+        let rc: RNode = Rc::new(RefCell::new(sn));
+
+        NodeGroup::attach_parent(&rc);
+
+        rc
+    }
+
+    pub fn sheet(&self) -> &Rc<SpriteSheet> {
+        &self.sheet
+    }
+
+    pub fn animator(&self) -> &RefCell<SpriteAnimator> {
+        &self.animator
+    }
+
+    /// Sets the frame shown when no clip is active.
+    pub fn set_frame(&self, index: usize) {
+        self.frame.set(index);
+    }
+
+    pub fn set_anchor(&self, x: f64, y: f64) {
+        self.anchor.set(Point::from_xy(x, y));
+        // Force the vertices to be rebuilt
+        self.shown.set(None);
+    }
+
+    fn current_frame(&self) -> usize {
+        match self.animator.borrow().current_frame() {
+            Some(index) => index,
+            None => self.frame.get(),
+        }
+    }
+
+    fn build_vertices(&self, index: usize) {
+        let (w, h) = match self.sheet.frame_size(index) {
+            Some((w, h)) => (w as f64, h as f64),
+            None => return,
+        };
+
+        let anchor = self.anchor.get();
+        let left = -anchor.x * w;
+        let top = -anchor.y * h;
+
+        let mut v = self.vertices.borrow_mut();
+        if self.sheet.is_rotated(index) {
+            // The region's top-left holds the frame's bottom-left, so the
+            // quad starts there and runs up the frame's left edge.
+            v[0].set_xy(left, top + h);
+            v[1].set_xy(left, top);
+            v[2].set_xy(left + w, top);
+            v[3].set_xy(left + w, top + h);
+        } else {
+            v[0].set_xy(left, top);
+            v[1].set_xy(left + w, top);
+            v[2].set_xy(left + w, top + h);
+            v[3].set_xy(left, top + h);
+        }
+    }
+}
+
+impl NodeTrait for SpriteNode {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    // --------------------------------------------------------
+    // Node properties
+    // --------------------------------------------------------
+    fn data(&self) -> &RefCell<NodeData> {
+        &self.data
+    }
+
+    // --------------------------------------------------------
+    // Rendering
+    // --------------------------------------------------------
+    fn draw(&self, context: &mut Context) {
+        let index = self.current_frame();
+        let region = match self.sheet.frame(index) {
+            Some(region) => *region,
+            None => return,
+        };
+
+        let texture = match self.sheet.texture(context) {
+            Some(texture) => texture,
+            None => return,
+        };
+
+        let rebuild = self.shown.get() != Some(index);
+        if rebuild {
+            self.build_vertices(index);
+            self.shown.set(Some(index));
+        }
+
+        if rebuild || self.is_node_dirty() {
+            context.transform(&self.vertices.borrow(), &self.bucket);
+            self.set_node_dirty(false);
+        }
+
+        context.render_texture(texture, &region, &self.bucket);
+    }
+
+    // --------------------------------------------------------
+    // Transformations
+    // --------------------------------------------------------
+    fn parent(&self) -> RONode {
+        self.parent.clone()
+    }
+
+    fn set_parent(&self, parent: RNode) {
+        self.parent.borrow_mut().replace(parent);
+    }
+
+    // --------------------------------------------------------
+    // Timing target
+    // --------------------------------------------------------
+    fn update(&self, dt: f64) {
+        self.animator.borrow_mut().update(dt);
+    }
+}
@@ -1,5 +1,5 @@
 use std::any::Any;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use geometry::point::Point;
@@ -7,7 +7,9 @@ use nodes::{
     node::{NodeGroup, NodeTrait, NodeType, RNode, RONode},
     node_properties::NodeData,
 };
-use rendering::{color::Palette, render_context::Context, vector_font::VectorFont};
+use rendering::{
+    color::Palette, render_context::Context, stroke::LineStyle, vector_font::VectorFont,
+};
 use world::World;
 
 pub struct VectorTextNode {
@@ -18,6 +20,8 @@ pub struct VectorTextNode {
 
     text: RefCell<String>,
     font: VectorFont,
+    color: Cell<Palette>,
+    line_style: Cell<LineStyle>,
 
     // Original model vertices
     vertices: RefCell<Vec<Point>>,
@@ -46,6 +50,8 @@ impl VectorTextNode {
             bucket: RefCell::new(Vec::new()),
             text: RefCell::new(String::from("")),
             font: VectorFont::new(),
+            color: Cell::new(Palette::WHITE(127)),
+            line_style: Cell::new(LineStyle::Hairline),
         };
 
         let rc: Rc<RefCell<NodeTrait>> = Rc::new(RefCell::new(n));
@@ -82,6 +88,10 @@ impl VectorTextNode {
 
         self.set_node_dirty(true);
     }
+
+    pub fn set_line_style(&self, style: LineStyle) {
+        self.line_style.set(style);
+    }
 }
 
 impl NodeTrait for VectorTextNode {
@@ -110,9 +120,13 @@ impl NodeTrait for VectorTextNode {
             self.set_node_dirty(false);
         }
 
-        context.set_draw_color(&Palette::WHITE(127));
+        context.set_draw_color(&self.color.get());
+
+        context.render_styled_lines(&self.bucket, &self.line_style.get());
+    }
 
-        context.render_lines(&self.bucket);
+    fn set_color(&self, color: Palette) {
+        self.color.set(color);
     }
 
     // --------------------------------------------------------
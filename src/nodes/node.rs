@@ -12,6 +12,7 @@ use nodes::{
 };
 use rendering::{
     color::Palette,
+    filters::FilterChain,
     render_context::{Context, RenderStyle},
 };
 
@@ -114,6 +115,31 @@ impl Nodes {
         NodeNil::new()
     }
 
+    /// Finds a descendant of `root` by a '/' separated path of node names,
+    /// for example, "layer/ship/engine". The path is relative to `root`,
+    /// i.e. the first name is matched against `root`'s children.
+    pub fn find_node_by_path(path: &str, root: &RNode) -> Option<RNode> {
+        let mut current = root.clone();
+
+        for name in path.split('/').filter(|n| !n.is_empty()) {
+            let found = match current.borrow().get_children() {
+                Some(children) => children
+                    .borrow()
+                    .iter()
+                    .find(|child| child.borrow().name() == name)
+                    .cloned(),
+                None => None,
+            };
+
+            match found {
+                Some(child) => current = child,
+                None => return None,
+            }
+        }
+
+        Some(current)
+    }
+
     pub fn register_timing_targets(node: &RNode, sch: &mut Scheduler) {
         let no = node.borrow();
         if no.data().borrow().node.canbe_timing_target() {
@@ -269,6 +295,24 @@ pub trait NodeTrait {
         self.data().borrow().node.visible()
     }
 
+    fn set_visible(&self, visible: bool) {
+        self.data().borrow_mut().node.set_visible(visible);
+    }
+
+    fn opacity(&self) -> f64 {
+        self.data().borrow().node.opacity()
+    }
+
+    /// 0.0 (transparent) -> 1.0 (opaque). A node's effective opacity is
+    /// multiplied by its ancestors'.
+    fn set_opacity(&self, opacity: f64) {
+        self.data().borrow_mut().node.set_opacity(opacity);
+    }
+
+    // Nodes that render with a single color override this, for example,
+    // so that animation color tracks can drive them.
+    fn set_color(&self, _color: Palette) {}
+
     // --------------------------------------------------------
     // Rendering: visiting, modification and drawing
     // --------------------------------------------------------
@@ -283,7 +327,8 @@ pub trait NodeTrait {
         //     self.name(),
         //     context.top_index()
         // );
-        if !self.is_visible() {
+        // A fully transparent node hides its children too.
+        if !self.is_visible() || self.opacity() == 0.0 {
             return;
         }
 
@@ -307,6 +352,7 @@ pub trait NodeTrait {
             }
 
             context.apply(aft);
+            context.apply_opacity(data.node.opacity());
             // println!("context.applied : {:?}", context.current());
             // context.print_stack(10);
         }
@@ -617,6 +663,15 @@ pub trait NodeTrait {
         None
     }
 
+    // --------------------------------------------------------
+    // Post-processing
+    // --------------------------------------------------------
+    // A scene returns its filters to have them applied to the frame
+    // after it is drawn.
+    fn filters(&self) -> Option<&RefCell<FilterChain>> {
+        None
+    }
+
     // --------------------------------------------------------
     // Misc
     // --------------------------------------------------------
@@ -38,6 +38,8 @@ pub struct NodeProperties {
 
     // Rendering
     visible: bool,
+    // 0.0 (transparent) -> 1.0 (opaque). Multiplies down the tree.
+    opacity: f64,
 
     // Timing
     canbe_timing_target: bool,
@@ -53,6 +55,7 @@ impl NodeProperties {
             name: String::from(""),
             n_type: NodeType::Nil,
             visible: true,
+            opacity: 1.0,
             canbe_timing_target: false,
             dirty: true,
         }
@@ -102,6 +105,14 @@ impl NodeProperties {
         self.visible = visible;
     }
 
+    pub fn opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    pub fn set_opacity(&mut self, opacity: f64) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
     pub fn canbe_timing_target(&self) -> bool {
         self.canbe_timing_target
     }
@@ -3,11 +3,12 @@ extern crate sdl2;
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
+use engine::timing::timers::Timers;
 use nodes::{
     node::{NodeType, Nodes, RNode},
     node_nil::NodeNil,
 };
-use rendering::{color::Palette, render_context::Context};
+use rendering::{clear_mode::ClearMode, color::Palette, render_context::Context};
 use world::{RCCanvas, WorldProperties};
 
 pub type RSceneManager = Rc<RefCell<SceneManager>>;
@@ -130,8 +131,6 @@ impl GlobalSceneData {
 pub struct SceneManager {
     world_properties: WorldProperties,
 
-    perform_clear: bool,
-
     context: Context,
 
     // Scenes
@@ -140,6 +139,9 @@ pub struct SceneManager {
     // Global Scene data
     global_data: GlobalSceneData,
 
+    // The Scheduler's timers, those bound to an exiting scene are cancelled.
+    timers: Timers,
+
     // DEBUG
     fps_color: Palette,
     coords_color: Palette,
@@ -155,10 +157,10 @@ impl SceneManager {
     pub fn new(canvas: RCCanvas) -> Self {
         Self {
             world_properties: WorldProperties::new(),
-            perform_clear: false,
             context: Context::new(canvas),
             scenes: SceneStack::new(),
             global_data: GlobalSceneData::new(),
+            timers: Timers::new(),
             fps_color: Palette::WHITE(127),
             coords_color: Palette::LIME(),
         }
@@ -166,7 +168,6 @@ impl SceneManager {
 
     pub fn initialize(&mut self, world_props: &WorldProperties) {
         self.world_properties.set(&world_props);
-        self.perform_clear = world_props.perform_clear;
 
         self.context.initialize(world_props);
     }
@@ -175,14 +176,25 @@ impl SceneManager {
         &mut self.global_data
     }
 
+    pub fn set_timers(&mut self, timers: Timers) {
+        self.timers = timers;
+    }
+
     pub fn pre_process(&self) {
-        // Typically Scenes/Layers will clear the background themselves so the default
-        // is to NOT perform a clear here.
-        if self.perform_clear {
-            // If vsync is enabled then this takes nearly 1/fps milliseconds.
-            // For example, 60fps -> 1/60 = 16.666~ms
-            self.context.clear();
-        }
+        // The Context honors WorldProperties::perform_clear.
+        // If vsync is enabled then this takes nearly 1/fps milliseconds.
+        // For example, 60fps -> 1/60 = 16.666~ms
+        self.context.clear();
+    }
+
+    pub fn set_clear_mode(&mut self, mode: ClearMode) {
+        self.world_properties.clear_mode = mode.clone();
+        self.context.set_clear_mode(mode);
+    }
+
+    pub fn set_perform_clear(&mut self, perform: bool) {
+        self.world_properties.perform_clear = perform;
+        self.context.set_perform_clear(perform);
     }
 
     pub fn visit(&mut self, interpolation: f64) -> bool {
@@ -216,6 +228,10 @@ impl SceneManager {
             }
 
             rfc.borrow().visit(&mut self.context, interpolation);
+
+            if let Some(filters) = r.filters() {
+                self.context.apply_filters(&mut filters.borrow_mut());
+            }
         }
 
         // Process view after visiting Nodes.
@@ -241,6 +257,7 @@ impl SceneManager {
                 r.start_exit_transition(&mut self.global_data);
                 // Transition is complete signal the scene to complete its exit.
                 r.exit(&mut self.global_data);
+                self.timers.cancel_bound_tree(&rfc);
 
                 if self.scenes.signal_flush() {
                     // Some scenes may need to release resources.
@@ -342,6 +359,10 @@ impl SceneManager {
         avg_up_time: f64,
         avg_blit_time: f64,
     ) {
+        if !self.world_properties.show_stats {
+            return;
+        }
+
         // Draws to device space (aka window space)
         self.context.set_draw_color(&self.fps_color);
         self.context.text(
@@ -357,6 +378,10 @@ impl SceneManager {
     }
 
     pub fn render_coordinates(&mut self) {
+        if !self.world_properties.show_coordinates {
+            return;
+        }
+
         // Draws to device space (aka window space)
         self.context.set_draw_color(&self.coords_color);
         self.context.text(
@@ -0,0 +1,569 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rendering::blend::BlendMode;
+use rendering::codecs::{self, ImageError};
+use rendering::image::{ImgRectangle, RGBA};
+use rendering::sprite_sheet::SpriteSheet;
+
+// Texture atlas packing. An AtlasPacker packs named images into one or
+// more pages using max-rects (best short side fit), with optional padding
+// between images and optional 90 degree rotation. The resulting Atlas is
+// saved as a metadata file plus one TGA image per page and loaded back at
+// runtime as SpriteSheets, one per page.
+//
+// Rotated images are stored rotated clockwise; SpriteSheet and SpriteNode
+// undo the rotation when drawing.
+//
+// Metadata is line based text. Page files are relative to the metadata
+// file and regions give their page, their rectangle within it, as stored,
+// whether they are rotated and then their name, which runs to the end of
+// the line:
+//
+//     # ranger atlas
+//     page sprites-0.tga
+//     region 0 0 0 32 48 0 hero walk 0
+
+const HEADER: &str = "# ranger atlas";
+
+#[derive(Debug)]
+pub enum AtlasError {
+    Io(io::Error),
+    Image(ImageError),
+    /// The image is larger than a page even when rotated.
+    TooLarge(String),
+    /// A metadata line couldn't be parsed, lines count from 1.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AtlasError::Io(ref err) => write!(f, "I/O error: {}", err),
+            AtlasError::Image(ref err) => write!(f, "Atlas page: {}", err),
+            AtlasError::TooLarge(ref name) => {
+                write!(f, "Image '{}' doesn't fit on an atlas page", name)
+            }
+            AtlasError::Parse { line, ref message } => {
+                write!(f, "Atlas metadata line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl Error for AtlasError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            AtlasError::Io(ref err) => Some(err),
+            AtlasError::Image(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AtlasError {
+    fn from(err: io::Error) -> Self {
+        AtlasError::Io(err)
+    }
+}
+
+impl From<ImageError> for AtlasError {
+    fn from(err: ImageError) -> Self {
+        AtlasError::Image(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasRegion {
+    pub name: String,
+    pub page: usize,
+    /// Where the image is on its page, as stored.
+    pub rect: ImgRectangle,
+    /// Stored rotated 90 degrees clockwise.
+    pub rotated: bool,
+}
+
+impl AtlasRegion {
+    /// The image's original size.
+    pub fn size(&self) -> (usize, usize) {
+        if self.rotated {
+            (self.rect.h(), self.rect.w())
+        } else {
+            (self.rect.w(), self.rect.h())
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+// Packing
+// ----------------------------------------------------------------
+pub struct AtlasPacker {
+    page_width: usize,
+    page_height: usize,
+    padding: usize,
+    allow_rotation: bool,
+
+    images: Vec<(String, RGBA)>,
+}
+
+impl AtlasPacker {
+    /// Pages are at most `page_width` x `page_height`. They are cropped to
+    /// what is used once packed.
+    pub fn new(page_width: usize, page_height: usize) -> Self {
+        Self {
+            page_width,
+            page_height,
+            padding: 0,
+            allow_rotation: false,
+            images: Vec::new(),
+        }
+    }
+
+    /// Empty pixels kept between images, which stops neighbors bleeding
+    /// in when sampling with filtering.
+    pub fn set_padding(&mut self, padding: usize) {
+        self.padding = padding;
+    }
+
+    /// Lets images be rotated 90 degrees when that packs tighter.
+    pub fn set_allow_rotation(&mut self, allow: bool) {
+        self.allow_rotation = allow;
+    }
+
+    pub fn add(&mut self, name: &str, image: RGBA) {
+        self.images.push((name.to_string(), image));
+    }
+
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    pub fn pack(&self) -> Result<Atlas, AtlasError> {
+        // Each image reserves its size plus padding. The bins are padded
+        // too so an image can sit against a page's right or bottom edge.
+        let pad = self.padding;
+        let bin_w = self.page_width + pad;
+        let bin_h = self.page_height + pad;
+
+        // Largest first packs best. Ties keep the order images were added.
+        let mut order: Vec<usize> = (0..self.images.len()).collect();
+        order.sort_by_key(|&i| {
+            let b = self.images[i].1.bounds();
+            (
+                std::cmp::Reverse(b.w().max(b.h())),
+                std::cmp::Reverse(b.size()),
+            )
+        });
+
+        let mut bins: Vec<MaxRects> = Vec::new();
+        let mut placed: Vec<Option<(usize, Rect, bool)>> = vec![None; self.images.len()];
+
+        for &i in order.iter() {
+            let (ref name, ref image) = self.images[i];
+            let w = image.bounds().w() + pad;
+            let h = image.bounds().h() + pad;
+
+            let mut found = None;
+            for (page, bin) in bins.iter_mut().enumerate() {
+                if let Some((rect, rotated)) = bin.insert(w, h, self.allow_rotation) {
+                    found = Some((page, rect, rotated));
+                    break;
+                }
+            }
+
+            if found.is_none() {
+                let mut bin = MaxRects::new(bin_w, bin_h);
+                match bin.insert(w, h, self.allow_rotation) {
+                    Some((rect, rotated)) => found = Some((bins.len(), rect, rotated)),
+                    None => return Err(AtlasError::TooLarge(name.clone())),
+                }
+                bins.push(bin);
+            }
+
+            placed[i] = found;
+        }
+
+        // Crop the pages to what was used.
+        let mut sizes = vec![(0, 0); bins.len()];
+        for &(page, rect, _) in placed.iter().flatten() {
+            sizes[page].0 = sizes[page].0.max(rect.x + rect.w - pad);
+            sizes[page].1 = sizes[page].1.max(rect.y + rect.h - pad);
+        }
+
+        let mut pages: Vec<RGBA> = sizes.iter().map(|&(w, h)| RGBA::new(w, h)).collect();
+        let mut regions = Vec::with_capacity(self.images.len());
+
+        for (i, (name, image)) in self.images.iter().enumerate() {
+            let (page, rect, rotated) = match placed[i] {
+                Some(placement) => placement,
+                None => continue,
+            };
+
+            if rotated {
+                pages[page].blit(&image.rotate_cw(), rect.x, rect.y, BlendMode::None);
+            } else {
+                pages[page].blit(image, rect.x, rect.y, BlendMode::None);
+            }
+
+            regions.push(AtlasRegion {
+                name: name.clone(),
+                page,
+                rect: ImgRectangle::from_xywh(rect.x, rect.y, rect.w - pad, rect.h - pad),
+                rotated,
+            });
+        }
+
+        Ok(Atlas { pages, regions })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rect {
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+}
+
+impl Rect {
+    fn right(&self) -> usize {
+        self.x + self.w
+    }
+
+    fn bottom(&self) -> usize {
+        self.y + self.h
+    }
+
+    fn contains(&self, other: &Rect) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.right() <= self.right()
+            && other.bottom() <= self.bottom()
+    }
+
+    fn overlaps(&self, other: &Rect) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+}
+
+// The free space of a bin as maximal, possibly overlapping, rectangles.
+struct MaxRects {
+    free: Vec<Rect>,
+}
+
+impl MaxRects {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            free: vec![Rect {
+                x: 0,
+                y: 0,
+                w: width,
+                h: height,
+            }],
+        }
+    }
+
+    // Best short side fit: the free rectangle that leaves the least on its
+    // shorter leftover side wins, the longer side breaks ties.
+    fn insert(&mut self, w: usize, h: usize, allow_rotation: bool) -> Option<(Rect, bool)> {
+        let mut best: Option<(Rect, bool)> = None;
+        let mut best_score = (usize::MAX, usize::MAX);
+
+        for free in self.free.iter() {
+            let mut candidates = vec![(w, h, false)];
+            if allow_rotation && w != h {
+                candidates.push((h, w, true));
+            }
+
+            for &(cw, ch, rotated) in candidates.iter() {
+                if cw > free.w || ch > free.h {
+                    continue;
+                }
+                let dw = free.w - cw;
+                let dh = free.h - ch;
+                let score = (dw.min(dh), dw.max(dh));
+                if score < best_score {
+                    best_score = score;
+                    best = Some((
+                        Rect {
+                            x: free.x,
+                            y: free.y,
+                            w: cw,
+                            h: ch,
+                        },
+                        rotated,
+                    ));
+                }
+            }
+        }
+
+        if let Some((rect, _)) = best {
+            self.place(&rect);
+        }
+
+        best
+    }
+
+    fn place(&mut self, used: &Rect) {
+        let mut split = Vec::new();
+
+        self.free.retain(|free| {
+            if !free.overlaps(used) {
+                return true;
+            }
+
+            // Up to four maximal rectangles around the used area.
+            if used.x > free.x {
+                split.push(Rect {
+                    w: used.x - free.x,
+                    ..*free
+                });
+            }
+            if used.right() < free.right() {
+                split.push(Rect {
+                    x: used.right(),
+                    w: free.right() - used.right(),
+                    ..*free
+                });
+            }
+            if used.y > free.y {
+                split.push(Rect {
+                    h: used.y - free.y,
+                    ..*free
+                });
+            }
+            if used.bottom() < free.bottom() {
+                split.push(Rect {
+                    y: used.bottom(),
+                    h: free.bottom() - used.bottom(),
+                    ..*free
+                });
+            }
+            false
+        });
+
+        self.free.extend(split);
+
+        // Drop rectangles contained in others.
+        let mut i = 0;
+        while i < self.free.len() {
+            let contained = self.free.iter().enumerate().any(|(j, other)| {
+                j != i && other.contains(&self.free[i]) && (other != &self.free[i] || j < i)
+            });
+            if contained {
+                self.free.swap_remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+// Atlas
+// ----------------------------------------------------------------
+pub struct Atlas {
+    pages: Vec<RGBA>,
+    regions: Vec<AtlasRegion>,
+}
+
+impl Atlas {
+    pub fn pages(&self) -> &Vec<RGBA> {
+        &self.pages
+    }
+
+    /// In the order the images were added to the packer.
+    pub fn regions(&self) -> &Vec<AtlasRegion> {
+        &self.regions
+    }
+
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        self.regions.iter().find(|r| r.name == name)
+    }
+
+    /// Copies a region back out, with any rotation undone.
+    pub fn extract(&self, name: &str) -> Option<RGBA> {
+        let region = self.region(name)?;
+        let image = self.pages.get(region.page)?.crop(&region.rect);
+        if region.rotated {
+            Some(image.rotate_ccw())
+        } else {
+            Some(image)
+        }
+    }
+
+    /// Writes the metadata to `path` and each page next to it as
+    /// "<stem>-<page>.tga".
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AtlasError> {
+        let path = path.as_ref();
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("atlas")
+            .to_string();
+
+        let mut metadata = AtlasMetadata {
+            pages: Vec::new(),
+            regions: self.regions.clone(),
+        };
+
+        for (i, page) in self.pages.iter().enumerate() {
+            let file = format!("{}-{}.tga", stem, i);
+            codecs::save(page, path.with_file_name(&file))?;
+            metadata.pages.push(file);
+        }
+
+        fs::write(path, metadata.to_string())?;
+        Ok(())
+    }
+
+    /// Loads the metadata and the pages it names.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Atlas, AtlasError> {
+        let path = path.as_ref();
+        let metadata = AtlasMetadata::load(path)?;
+
+        let mut pages = Vec::with_capacity(metadata.pages.len());
+        for file in metadata.pages.iter() {
+            pages.push(codecs::load(path.with_file_name(file))?);
+        }
+
+        Ok(Atlas {
+            pages,
+            regions: metadata.regions,
+        })
+    }
+
+    /// One SpriteSheet per page with a named frame for each of the page's
+    /// regions.
+    pub fn into_sheets(self) -> Vec<SpriteSheet> {
+        let mut sheets: Vec<SpriteSheet> = self.pages.into_iter().map(SpriteSheet::new).collect();
+
+        for region in self.regions.iter() {
+            if let Some(sheet) = sheets.get_mut(region.page) {
+                sheet.add_named_frame(&region.name, region.rect, region.rotated);
+            }
+        }
+
+        sheets
+    }
+}
+
+// ----------------------------------------------------------------
+// Metadata
+// ----------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasMetadata {
+    /// Page image files, relative to the metadata file.
+    pub pages: Vec<String>,
+    pub regions: Vec<AtlasRegion>,
+}
+
+impl AtlasMetadata {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<AtlasMetadata, AtlasError> {
+        let text = fs::read_to_string(path)?;
+        AtlasMetadata::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<AtlasMetadata, AtlasError> {
+        let mut metadata = AtlasMetadata {
+            pages: Vec::new(),
+            regions: Vec::new(),
+        };
+
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let error = |message: &str| AtlasError::Parse {
+                line: line_no,
+                message: message.to_string(),
+            };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (keyword, rest) = match line.find(char::is_whitespace) {
+                Some(at) => (&line[..at], line[at..].trim_start()),
+                None => (line, ""),
+            };
+
+            match keyword {
+                "page" => {
+                    if rest.is_empty() {
+                        return Err(error("page without a file"));
+                    }
+                    metadata.pages.push(rest.to_string());
+                }
+                "region" => {
+                    // Six numbers then the name.
+                    let mut fields = rest.splitn(7, char::is_whitespace);
+                    let mut numbers = [0usize; 6];
+                    for n in numbers.iter_mut() {
+                        *n = fields
+                            .next()
+                            .and_then(|f| f.parse().ok())
+                            .ok_or_else(|| error("expected page x y w h rotated name"))?;
+                    }
+                    let name = fields.next().map(str::trim).unwrap_or("");
+                    if name.is_empty() {
+                        return Err(error("region without a name"));
+                    }
+                    if numbers[0] >= metadata.pages.len() {
+                        return Err(error("region on an undeclared page"));
+                    }
+                    if numbers[5] > 1 {
+                        return Err(error("rotated must be 0 or 1"));
+                    }
+
+                    metadata.regions.push(AtlasRegion {
+                        name: name.to_string(),
+                        page: numbers[0],
+                        rect: ImgRectangle::from_xywh(
+                            numbers[1], numbers[2], numbers[3], numbers[4],
+                        ),
+                        rotated: numbers[5] == 1,
+                    });
+                }
+                _ => return Err(error(&format!("unknown entry '{}'", keyword))),
+            }
+        }
+
+        Ok(metadata)
+    }
+}
+
+impl fmt::Display for AtlasMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for page in self.pages.iter() {
+            writeln!(f, "page {}", page)?;
+        }
+        for r in self.regions.iter() {
+            writeln!(
+                f,
+                "region {} {} {} {} {} {} {}",
+                r.page,
+                r.rect.min.x,
+                r.rect.min.y,
+                r.rect.w(),
+                r.rect.h(),
+                r.rotated as u8,
+                r.name
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+// Blend modes shared by the SDL backend and the software (RGBA) path.
+//
+// Colors are non-premultiplied. The source alpha scales the source's
+// contribution in every mode except None.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+    /// The source replaces the destination, alpha included.
+    None,
+    /// Classic "over" compositing.
+    Alpha,
+    /// dst + src * src_alpha. Brightens, useful for glows and particles.
+    Additive,
+    /// dst * src. Darkens, useful for shadows and tinting.
+    Multiply,
+    /// 1 - (1 - dst) * (1 - src). Brightens without blowing out.
+    Screen,
+}
+
+impl BlendMode {
+    /// Blends `src` onto `dst`.
+    pub fn blend(&self, src: [u8; 4], dst: [u8; 4]) -> [u8; 4] {
+        let sa = src[3] as u32;
+
+        match *self {
+            BlendMode::None => src,
+            BlendMode::Alpha => {
+                let ia = 255 - sa;
+                [
+                    div255(src[0] as u32 * sa + dst[0] as u32 * ia),
+                    div255(src[1] as u32 * sa + dst[1] as u32 * ia),
+                    div255(src[2] as u32 * sa + dst[2] as u32 * ia),
+                    (sa + div255(dst[3] as u32 * ia) as u32) as u8,
+                ]
+            }
+            BlendMode::Additive => {
+                let add = |s: u8, d: u8| (d as u32 + div255(s as u32 * sa) as u32).min(255) as u8;
+                [
+                    add(src[0], dst[0]),
+                    add(src[1], dst[1]),
+                    add(src[2], dst[2]),
+                    dst[3],
+                ]
+            }
+            BlendMode::Multiply => {
+                let src = fade_to_white(src);
+                let mul = |s: u8, d: u8| div255(s as u32 * d as u32);
+                [
+                    mul(src[0], dst[0]),
+                    mul(src[1], dst[1]),
+                    mul(src[2], dst[2]),
+                    dst[3],
+                ]
+            }
+            BlendMode::Screen => {
+                let screen = |s: u8, d: u8| {
+                    let s = div255(s as u32 * sa) as u32;
+                    255 - div255((255 - s) * (255 - d as u32))
+                };
+                [
+                    screen(src[0], dst[0]),
+                    screen(src[1], dst[1]),
+                    screen(src[2], dst[2]),
+                    dst[3],
+                ]
+            }
+        }
+    }
+}
+
+/// Fades `src` towards white (no effect when multiplied) as its alpha
+/// drops, returning an opaque color. This is what Multiply multiplies by,
+/// backends whose multiply ignores alpha are given this color instead.
+pub fn fade_to_white(src: [u8; 4]) -> [u8; 4] {
+    let sa = src[3] as u32;
+    let fade = |s: u8| (div255(s as u32 * sa) as u32 + (255 - sa)) as u8;
+    [fade(src[0]), fade(src[1]), fade(src[2]), 255]
+}
+
+// x / 255 rounded, for x in [0, 255 * 255].
+#[inline]
+fn div255(x: u32) -> u8 {
+    ((x + 128 + ((x + 128) >> 8)) >> 8) as u8
+}
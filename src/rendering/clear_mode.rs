@@ -0,0 +1,63 @@
+use std::rc::Rc;
+
+use math::interpolation::Lerp;
+use rendering::color::Palette;
+use rendering::image::RGBA;
+
+// How Context::clear paints the background at the start of a frame.
+// The mode is part of WorldProperties and can be changed at runtime via
+// the SceneManager.
+
+#[derive(Clone)]
+pub enum ClearMode {
+    Solid(Palette),
+    /// Alternating squares of `size` pixels starting with `dark` at the
+    /// top-left. This is the default, handy for spotting undrawn areas.
+    Checkerboard {
+        size: u32,
+        dark: Palette,
+        light: Palette,
+    },
+    /// Blends from `top` to `bottom` across the window's height.
+    VerticalGradient {
+        top: Palette,
+        bottom: Palette,
+    },
+    /// The image is stretched to fill the window.
+    Image(Rc<RGBA>),
+}
+
+impl ClearMode {
+    pub fn checkerboard() -> Self {
+        ClearMode::Checkerboard {
+            size: 200,
+            dark: Palette::RGB(80, 80, 80),
+            light: Palette::RGB(100, 100, 100),
+        }
+    }
+
+    /// The color of the device pixel (x, y) in a window `height` pixels
+    /// high. Image backgrounds have no analytic color and return None.
+    pub fn color_at(&self, x: i32, y: i32, height: i32) -> Option<Palette> {
+        match *self {
+            ClearMode::Solid(color) => Some(color),
+            ClearMode::Checkerboard { size, dark, light } => {
+                let size = size.max(1) as i32;
+                if (x / size + y / size) % 2 == 0 {
+                    Some(dark)
+                } else {
+                    Some(light)
+                }
+            }
+            ClearMode::VerticalGradient { top, bottom } => {
+                let t = if height > 1 {
+                    y as f64 / (height - 1) as f64
+                } else {
+                    0.0
+                };
+                Some(Palette::lerp(&top, &bottom, t.clamp(0.0, 1.0)))
+            }
+            ClearMode::Image(_) => None,
+        }
+    }
+}
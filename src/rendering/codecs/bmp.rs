@@ -0,0 +1,268 @@
+use rendering::codecs::{new_image, put_u16, put_u32, ImageError, Reader};
+use rendering::image::RGBA;
+
+// Windows bitmaps. Reads uncompressed 8 (palette), 24 and 32 bit images,
+// including 32 bit images with channel masks (BI_BITFIELDS), with any of
+// the common info headers. RLE compressed bitmaps aren't supported.
+//
+// 32 bit images written without masks often leave alpha at zero. If every
+// pixel's alpha is zero the image is treated as opaque.
+
+const FILE_HEADER_SIZE: usize = 14;
+const INFO_HEADER_SIZE: usize = 40;
+const V4_HEADER_SIZE: usize = 108;
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+// Channel masks, in the order red, green, blue and alpha.
+type Masks = [u32; 4];
+
+pub fn decode(data: &[u8]) -> Result<RGBA, ImageError> {
+    let mut r = Reader::new(data);
+
+    if r.bytes(2)? != b"BM" {
+        return Err(ImageError::Malformed(String::from("missing BM signature")));
+    }
+    r.skip(8)?;
+    let offset = r.u32()? as usize;
+
+    let header_size = r.u32()? as usize;
+    match header_size {
+        40 | 52 | 56 | 108 | 124 => (),
+        12 => return Err(ImageError::Unsupported(String::from("OS/2 bitmap header"))),
+        _ => {
+            return Err(ImageError::Malformed(format!(
+                "unknown header size {}",
+                header_size
+            )))
+        }
+    }
+
+    let width = r.i32()?;
+    let height = r.i32()?;
+    let _planes = r.u16()?;
+    let bpp = r.u16()?;
+    let compression = r.u32()?;
+    r.skip(12)?;
+    let colors_used = r.u32()? as usize;
+
+    if width <= 0 || height == 0 {
+        return Err(ImageError::Malformed(format!(
+            "bad dimensions {}x{}",
+            width, height
+        )));
+    }
+
+    let masks = match compression {
+        BI_RGB => None,
+        BI_BITFIELDS | BI_ALPHABITFIELDS => {
+            if bpp != 32 {
+                return Err(ImageError::Unsupported(format!("{} bit bitfields", bpp)));
+            }
+            // The masks directly follow the basic info header, either as
+            // part of a larger header or on their own.
+            r.seek(FILE_HEADER_SIZE + INFO_HEADER_SIZE)?;
+            let red = r.u32()?;
+            let green = r.u32()?;
+            let blue = r.u32()?;
+            let alpha = if header_size >= 56 || compression == BI_ALPHABITFIELDS {
+                r.u32()?
+            } else {
+                0
+            };
+            Some([red, green, blue, alpha])
+        }
+        1 | 2 => return Err(ImageError::Unsupported(String::from("RLE compression"))),
+        _ => {
+            return Err(ImageError::Unsupported(format!(
+                "compression {}",
+                compression
+            )))
+        }
+    };
+
+    let palette = match bpp {
+        8 => {
+            let count = if colors_used == 0 || colors_used > 256 {
+                256
+            } else {
+                colors_used
+            };
+            r.seek(FILE_HEADER_SIZE + header_size)?;
+            let entries = r.bytes(count * 4)?;
+            entries
+                .chunks(4)
+                .map(|e| [e[2], e[1], e[0], 255])
+                .collect::<Vec<[u8; 4]>>()
+        }
+        24 | 32 => Vec::new(),
+        _ => return Err(ImageError::Unsupported(format!("{} bits per pixel", bpp))),
+    };
+
+    let w = width as usize;
+    let h = height.unsigned_abs() as usize;
+    let top_down = height < 0;
+
+    // Rows are padded to 4 bytes. The size is checked before allocating
+    // so a large header over a short file fails fast.
+    let row_size = (bpp as usize * w).div_ceil(32) * 4;
+    r.seek(offset)?;
+    let size = row_size.checked_mul(h).ok_or(ImageError::Truncated)?;
+    if size > data.len() - r.pos {
+        return Err(ImageError::Truncated);
+    }
+    let mut image = new_image(w, h)?;
+    let pixels = r.bytes(size)?;
+
+    for (row, src) in pixels.chunks(row_size).enumerate() {
+        let y = if top_down { row } else { h - 1 - row };
+        let o = y * image.stride;
+        let dst = &mut image.pix[o..o + w * 4];
+
+        match bpp {
+            8 => {
+                for (x, &index) in src[..w].iter().enumerate() {
+                    let color = palette.get(index as usize).ok_or_else(|| {
+                        ImageError::Malformed(format!("palette index {} out of range", index))
+                    })?;
+                    dst[x * 4..x * 4 + 4].copy_from_slice(color);
+                }
+            }
+            24 => {
+                for (p, s) in dst.chunks_mut(4).zip(src.chunks(3)) {
+                    p.copy_from_slice(&[s[2], s[1], s[0], 255]);
+                }
+            }
+            _ => {
+                for (p, s) in dst.chunks_mut(4).zip(src.chunks(4)) {
+                    match masks {
+                        Some(ref masks) => {
+                            let pixel = u32::from_le_bytes([s[0], s[1], s[2], s[3]]);
+                            p.copy_from_slice(&unpack(pixel, masks));
+                        }
+                        None => p.copy_from_slice(&[s[2], s[1], s[0], s[3]]),
+                    }
+                }
+            }
+        }
+    }
+
+    let alpha_unused = match masks {
+        Some(ref masks) => masks[3] == 0,
+        None => true,
+    };
+    if bpp == 32 && alpha_unused && image.pix.chunks(4).all(|p| p[3] == 0) {
+        for p in image.pix.chunks_mut(4) {
+            p[3] = 255;
+        }
+    }
+
+    Ok(image)
+}
+
+// Extracts the channels from a pixel, scaling each to 8 bits. A channel
+// without a mask is fully on.
+fn unpack(pixel: u32, masks: &Masks) -> [u8; 4] {
+    let mut out = [0u8; 4];
+    for (c, &mask) in masks.iter().enumerate() {
+        out[c] = if mask == 0 {
+            255
+        } else {
+            let shift = mask.trailing_zeros();
+            let bits = (mask >> shift).count_ones();
+            let value = (pixel & mask) >> shift;
+            if bits >= 8 {
+                (value >> (bits - 8)) as u8
+            } else {
+                (value * 255 / ((1 << bits) - 1)) as u8
+            }
+        };
+    }
+    out
+}
+
+/// Encodes a 32 bit, bottom-up, bitmap with alpha.
+pub fn encode(image: &RGBA) -> Vec<u8> {
+    let w = image.bounds().w();
+    let h = image.bounds().h();
+    let pixel_bytes = w * h * 4;
+    let offset = FILE_HEADER_SIZE + V4_HEADER_SIZE;
+
+    let mut out = Vec::with_capacity(offset + pixel_bytes);
+    write_file_header(&mut out, offset, pixel_bytes);
+
+    // BITMAPV4HEADER
+    put_u32(&mut out, V4_HEADER_SIZE as u32);
+    put_u32(&mut out, w as u32);
+    put_u32(&mut out, h as u32);
+    put_u16(&mut out, 1);
+    put_u16(&mut out, 32);
+    put_u32(&mut out, BI_BITFIELDS);
+    put_u32(&mut out, pixel_bytes as u32);
+    put_u32(&mut out, 2835); // 72 DPI
+    put_u32(&mut out, 2835);
+    put_u32(&mut out, 0);
+    put_u32(&mut out, 0);
+    put_u32(&mut out, 0x00ff_0000);
+    put_u32(&mut out, 0x0000_ff00);
+    put_u32(&mut out, 0x0000_00ff);
+    put_u32(&mut out, 0xff00_0000);
+    put_u32(&mut out, 0x7352_4742); // 'sRGB'
+
+    // Endpoints and gamma, unused for sRGB.
+    out.resize(offset, 0);
+
+    for y in (0..h).rev() {
+        for x in 0..w {
+            let p = image.at(image.bounds().min.x + x, image.bounds().min.y + y);
+            out.extend_from_slice(&[p[2], p[1], p[0], p[3]]);
+        }
+    }
+
+    out
+}
+
+/// Encodes a 24 bit, bottom-up, bitmap. Alpha is dropped.
+pub fn encode_rgb(image: &RGBA) -> Vec<u8> {
+    let w = image.bounds().w();
+    let h = image.bounds().h();
+    let row_size = (w * 3).div_ceil(4) * 4;
+    let pixel_bytes = row_size * h;
+    let offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+
+    let mut out = Vec::with_capacity(offset + pixel_bytes);
+    write_file_header(&mut out, offset, pixel_bytes);
+
+    // BITMAPINFOHEADER
+    put_u32(&mut out, INFO_HEADER_SIZE as u32);
+    put_u32(&mut out, w as u32);
+    put_u32(&mut out, h as u32);
+    put_u16(&mut out, 1);
+    put_u16(&mut out, 24);
+    put_u32(&mut out, BI_RGB);
+    put_u32(&mut out, pixel_bytes as u32);
+    put_u32(&mut out, 2835);
+    put_u32(&mut out, 2835);
+    put_u32(&mut out, 0);
+    put_u32(&mut out, 0);
+
+    for y in (0..h).rev() {
+        let start = out.len();
+        for x in 0..w {
+            let p = image.at(image.bounds().min.x + x, image.bounds().min.y + y);
+            out.extend_from_slice(&[p[2], p[1], p[0]]);
+        }
+        out.resize(start + row_size, 0);
+    }
+
+    out
+}
+
+fn write_file_header(out: &mut Vec<u8>, offset: usize, pixel_bytes: usize) {
+    out.extend_from_slice(b"BM");
+    put_u32(out, (offset + pixel_bytes) as u32);
+    put_u32(out, 0);
+    put_u32(out, offset as u32);
+}
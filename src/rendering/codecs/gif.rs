@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use rendering::codecs::{put_u16, ImageError};
+use rendering::filters::{median_cut, Dither, Filter, Quantize};
+use rendering::image::RGBA;
+
+// Animated GIF export of captured frames, e.g. from
+// Context::capture_frame(). Runs entirely on the CPU so frames can be
+// encoded offline, without a display.
+//
+// Every frame gets its own palette (median cut) and is quantized to it,
+// optionally dithered. Pixels with alpha below 128 become transparent.
+//
+// GIF delays are in centiseconds and viewers treat anything below 2 as
+// much slower, so when frames are captured faster than that only every
+// n-th frame is kept. The delays follow the capture timeline without
+// drifting from rounding.
+
+const MIN_DELAY: f64 = 2.0; // centiseconds
+const MAX_CODE: u16 = 4096;
+
+pub struct GifEncoder {
+    out: Vec<u8>,
+
+    // Logical screen size, taken from the first frame.
+    width: usize,
+    height: usize,
+
+    // Capture period and the encoded timeline, in centiseconds.
+    frame_period: f64,
+    elapsed: f64,
+    written: u64,
+    // Every stride'th offered frame is kept.
+    stride: usize,
+    offered: usize,
+    frames: usize,
+
+    scale: f64,
+    colors: usize,
+    dither: Dither,
+    loop_count: Option<u16>,
+}
+
+impl GifEncoder {
+    /// `frame_period` is the time between offered frames, typically
+    /// Core::frame_period().
+    pub fn new(frame_period: Duration) -> Self {
+        let period = frame_period.as_secs_f64() * 100.0;
+        let stride = if period > 0.0 {
+            (MIN_DELAY / period).ceil().max(1.0) as usize
+        } else {
+            1
+        };
+
+        Self {
+            out: Vec::new(),
+            width: 0,
+            height: 0,
+            frame_period: period,
+            elapsed: 0.0,
+            written: 0,
+            stride,
+            offered: 0,
+            frames: 0,
+            scale: 1.0,
+            colors: 256,
+            dither: Dither::FloydSteinberg,
+            loop_count: Some(0),
+        }
+    }
+
+    /// Frames are scaled by this before encoding. Only downscaling is
+    /// supported, values of 1.0 or more keep the original size.
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+    }
+
+    /// Palette size per frame, clamped to [2, 256].
+    pub fn set_colors(&mut self, colors: usize) {
+        self.colors = colors.clamp(2, 256);
+    }
+
+    pub fn set_dither(&mut self, dither: Dither) {
+        self.dither = dither;
+    }
+
+    /// Some(0) loops forever (the default), None plays once.
+    pub fn set_loop_count(&mut self, count: Option<u16>) {
+        self.loop_count = count;
+    }
+
+    /// Offered frames are kept one in this many.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Frames encoded so far.
+    pub fn frame_count(&self) -> usize {
+        self.frames
+    }
+
+    /// Offers the next captured frame. All frames must be the same size.
+    pub fn add_frame(&mut self, frame: &RGBA) -> Result<(), ImageError> {
+        let index = self.offered;
+        self.offered += 1;
+        if !index.is_multiple_of(self.stride) {
+            return Ok(());
+        }
+
+        let mut image = if self.scale < 1.0 {
+            let w = (frame.bounds().w() as f64 * self.scale).round().max(1.0) as usize;
+            let h = (frame.bounds().h() as f64 * self.scale).round().max(1.0) as usize;
+            frame.resize_bilinear(w, h)
+        } else {
+            frame.crop(frame.bounds())
+        };
+        let (w, h) = (image.bounds().w(), image.bounds().h());
+
+        if self.frames == 0 {
+            if w == 0 || h == 0 || w > u16::MAX as usize || h > u16::MAX as usize {
+                return Err(ImageError::Unsupported(format!("frame size {}x{}", w, h)));
+            }
+            self.width = w;
+            self.height = h;
+            self.write_header();
+        } else if w != self.width || h != self.height {
+            return Err(ImageError::Unsupported(format!(
+                "frame is {}x{} but the animation is {}x{}",
+                w, h, self.width, self.height
+            )));
+        }
+
+        let transparent = image.pix.chunks(4).any(|p| p[3] < 128);
+        let reserved = if transparent { 1 } else { 0 };
+        let palette = median_cut(&image, self.colors - reserved);
+        Quantize::new(palette.clone(), self.dither).apply(&mut image);
+
+        // Quantized pixels are exact palette colors.
+        let mut lookup = HashMap::new();
+        for (i, c) in palette.iter().enumerate().rev() {
+            lookup.insert([c.r, c.g, c.b], i as u8);
+        }
+        let clear_index = palette.len() as u8;
+        let mut indices = Vec::with_capacity(w * h);
+        for y in 0..h {
+            let row = y * image.stride;
+            for p in image.pix[row..row + w * 4].chunks(4) {
+                if p[3] < 128 {
+                    indices.push(clear_index);
+                } else {
+                    indices.push(*lookup.get(&[p[0], p[1], p[2]]).unwrap_or(&0));
+                }
+            }
+        }
+
+        // Smallest power of two table that fits, at least 2 entries.
+        let used = palette.len() + reserved;
+        let mut bits = 1;
+        while (1 << bits) < used {
+            bits += 1;
+        }
+
+        self.elapsed += self.stride as f64 * self.frame_period;
+        let delay = (self.elapsed.round() as u64).saturating_sub(self.written);
+        self.written += delay;
+
+        // Graphic control extension. Transparent frames restore to the
+        // background so earlier frames don't show through.
+        let disposal = if transparent { 2 } else { 1 };
+        self.out.extend_from_slice(&[0x21, 0xf9, 0x04]);
+        self.out.push(disposal << 2 | reserved as u8);
+        put_u16(&mut self.out, delay.min(u16::MAX as u64) as u16);
+        self.out.push(if transparent { clear_index } else { 0 });
+        self.out.push(0);
+
+        // Image descriptor with a local color table.
+        self.out.push(0x2c);
+        put_u16(&mut self.out, 0);
+        put_u16(&mut self.out, 0);
+        put_u16(&mut self.out, w as u16);
+        put_u16(&mut self.out, h as u16);
+        self.out.push(0x80 | (bits - 1) as u8);
+        for c in palette.iter() {
+            self.out.extend_from_slice(&[c.r, c.g, c.b]);
+        }
+        for _ in palette.len()..1 << bits {
+            self.out.extend_from_slice(&[0, 0, 0]);
+        }
+
+        let min_code_size = bits.max(2) as u8;
+        self.out.push(min_code_size);
+        lzw_encode(&indices, min_code_size, &mut self.out);
+
+        self.frames += 1;
+
+        Ok(())
+    }
+
+    fn write_header(&mut self) {
+        self.out.extend_from_slice(b"GIF89a");
+        put_u16(&mut self.out, self.width as u16);
+        put_u16(&mut self.out, self.height as u16);
+        // No global color table, background 0, square pixels.
+        self.out.extend_from_slice(&[0, 0, 0]);
+
+        if let Some(count) = self.loop_count {
+            self.out.extend_from_slice(&[0x21, 0xff, 0x0b]);
+            self.out.extend_from_slice(b"NETSCAPE2.0");
+            self.out.extend_from_slice(&[0x03, 0x01]);
+            put_u16(&mut self.out, count);
+            self.out.push(0);
+        }
+    }
+
+    /// Completes the file.
+    pub fn finish(mut self) -> Result<Vec<u8>, ImageError> {
+        if self.frames == 0 {
+            return Err(ImageError::Malformed(String::from("no frames")));
+        }
+        self.out.push(0x3b);
+        Ok(self.out)
+    }
+
+    pub fn save<P: AsRef<Path>>(self, path: P) -> Result<(), ImageError> {
+        let data = self.finish()?;
+        fs::write(path, data)?;
+        Ok(())
+    }
+}
+
+/// Encodes captured frames with the default settings.
+pub fn encode(frames: &[RGBA], frame_period: Duration) -> Result<Vec<u8>, ImageError> {
+    let mut encoder = GifEncoder::new(frame_period);
+    for frame in frames {
+        encoder.add_frame(frame)?;
+    }
+    encoder.finish()
+}
+
+// Variable width LZW as GIF uses it, written as data sub-blocks.
+fn lzw_encode(indices: &[u8], min_code_size: u8, out: &mut Vec<u8>) {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut width = min_code_size as u32 + 1;
+
+    let mut bits = BitWriter::new(out);
+    bits.write(clear, width);
+
+    let mut prefix: Option<u16> = None;
+    for &k in indices {
+        let p = match prefix {
+            Some(p) => p,
+            None => {
+                prefix = Some(k as u16);
+                continue;
+            }
+        };
+
+        if let Some(&code) = codes.get(&(p, k)) {
+            prefix = Some(code);
+            continue;
+        }
+
+        bits.write(p, width);
+        if next < MAX_CODE {
+            codes.insert((p, k), next);
+            // The decoder widens one code later than the encoder adds.
+            if next == 1 << width && width < 12 {
+                width += 1;
+            }
+            next += 1;
+        } else {
+            bits.write(clear, width);
+            codes.clear();
+            next = end + 1;
+            width = min_code_size as u32 + 1;
+        }
+        prefix = Some(k as u16);
+    }
+
+    if let Some(p) = prefix {
+        bits.write(p, width);
+    }
+    bits.write(end, width);
+    bits.finish();
+}
+
+// Packs codes least significant bit first into sub-blocks of at most 255
+// bytes.
+struct BitWriter<'a> {
+    out: &'a mut Vec<u8>,
+    block: Vec<u8>,
+    acc: u32,
+    count: u32,
+}
+
+impl<'a> BitWriter<'a> {
+    fn new(out: &'a mut Vec<u8>) -> Self {
+        Self {
+            out,
+            block: Vec::with_capacity(255),
+            acc: 0,
+            count: 0,
+        }
+    }
+
+    fn write(&mut self, code: u16, width: u32) {
+        self.acc |= (code as u32) << self.count;
+        self.count += width;
+        while self.count >= 8 {
+            let byte = self.acc as u8;
+            self.push(byte);
+            self.acc >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn push(&mut self, byte: u8) {
+        self.block.push(byte);
+        if self.block.len() == 255 {
+            self.flush_block();
+        }
+    }
+
+    fn flush_block(&mut self) {
+        self.out.push(self.block.len() as u8);
+        self.out.extend_from_slice(&self.block);
+        self.block.clear();
+    }
+
+    fn finish(mut self) {
+        if self.count > 0 {
+            let byte = self.acc as u8;
+            self.push(byte);
+        }
+        if !self.block.is_empty() {
+            self.flush_block();
+        }
+        self.out.push(0);
+    }
+}
@@ -0,0 +1,216 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rendering::image::RGBA;
+
+pub mod bmp;
+pub mod gif;
+pub mod pnm;
+pub mod tga;
+
+// Readers and writers for simple, uncompressed or RLE, image formats.
+// Decoders take the whole file as bytes and produce an RGBA image,
+// encoders do the reverse.
+
+/// Decoders refuse images larger than this in either dimension.
+pub const MAX_DIMENSION: usize = 1 << 15;
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    /// The data ended before the image did.
+    Truncated,
+    /// The data isn't valid for the format, e.g. a bad signature.
+    Malformed(String),
+    /// Valid, but a variant of the format that isn't supported.
+    Unsupported(String),
+    /// The format couldn't be determined from the name or contents.
+    UnknownFormat,
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ImageError::Io(ref err) => write!(f, "I/O error: {}", err),
+            ImageError::Truncated => write!(f, "Image data is truncated"),
+            ImageError::Malformed(ref what) => write!(f, "Malformed image: {}", what),
+            ImageError::Unsupported(ref what) => write!(f, "Unsupported image: {}", what),
+            ImageError::UnknownFormat => write!(f, "Unknown image format"),
+        }
+    }
+}
+
+impl Error for ImageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ImageError::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(err: io::Error) -> Self {
+        ImageError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Bmp,
+    Tga,
+    /// PPM (color) or PGM (gray).
+    Pnm,
+}
+
+impl ImageFormat {
+    /// Picks the format from a file extension, ignoring case.
+    pub fn from_extension(path: &Path) -> Option<ImageFormat> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "bmp" => Some(ImageFormat::Bmp),
+            "tga" => Some(ImageFormat::Tga),
+            "ppm" | "pgm" | "pnm" => Some(ImageFormat::Pnm),
+            _ => None,
+        }
+    }
+
+    /// Picks the format from the file's signature. TGA has none so it is
+    /// never detected this way.
+    pub fn from_signature(data: &[u8]) -> Option<ImageFormat> {
+        if data.starts_with(b"BM") {
+            return Some(ImageFormat::Bmp);
+        }
+        if data.len() >= 2 && data[0] == b'P' && b"2356".contains(&data[1]) {
+            return Some(ImageFormat::Pnm);
+        }
+        None
+    }
+}
+
+pub fn decode(data: &[u8], format: ImageFormat) -> Result<RGBA, ImageError> {
+    match format {
+        ImageFormat::Bmp => bmp::decode(data),
+        ImageFormat::Tga => tga::decode(data),
+        ImageFormat::Pnm => pnm::decode(data),
+    }
+}
+
+/// Loads an image, detecting the format from its contents and falling
+/// back to the extension.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<RGBA, ImageError> {
+    let path = path.as_ref();
+    let data = fs::read(path)?;
+
+    let format = match ImageFormat::from_signature(&data) {
+        Some(format) => format,
+        None => ImageFormat::from_extension(path).ok_or(ImageError::UnknownFormat)?,
+    };
+
+    decode(&data, format)
+}
+
+/// Saves an image in the format given by the extension: 32 bit BMP, RLE
+/// TGA, or binary PPM/PGM.
+pub fn save<P: AsRef<Path>>(image: &RGBA, path: P) -> Result<(), ImageError> {
+    let path = path.as_ref();
+
+    let data = match ImageFormat::from_extension(path) {
+        Some(ImageFormat::Bmp) => bmp::encode(image),
+        Some(ImageFormat::Tga) => tga::encode(image, true)?,
+        Some(ImageFormat::Pnm) => {
+            let gray = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("pgm"));
+            if gray {
+                pnm::encode_pgm(image)
+            } else {
+                pnm::encode_ppm(image)
+            }
+        }
+        None => return Err(ImageError::UnknownFormat),
+    };
+
+    fs::write(path, data)?;
+    Ok(())
+}
+
+// Checks the dimensions and allocates the image.
+fn new_image(width: usize, height: usize) -> Result<RGBA, ImageError> {
+    if width == 0 || height == 0 {
+        return Err(ImageError::Malformed(format!(
+            "empty image {}x{}",
+            width, height
+        )));
+    }
+    if width > MAX_DIMENSION || height > MAX_DIMENSION {
+        return Err(ImageError::Unsupported(format!(
+            "image too large {}x{}",
+            width, height
+        )));
+    }
+    Ok(RGBA::new(width, height))
+}
+
+// A cursor over little endian binary data.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn seek(&mut self, pos: usize) -> Result<(), ImageError> {
+        if pos > self.data.len() {
+            return Err(ImageError::Truncated);
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), ImageError> {
+        let pos = self.pos + count;
+        self.seek(pos)
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], ImageError> {
+        if self.data.len() - self.pos < count {
+            return Err(ImageError::Truncated);
+        }
+        let bytes = &self.data[self.pos..self.pos + count];
+        self.pos += count;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ImageError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ImageError> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, ImageError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn i32(&mut self) -> Result<i32, ImageError> {
+        Ok(self.u32()? as i32)
+    }
+}
+
+fn put_u16(out: &mut Vec<u8>, v: u16) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, v: u32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
@@ -0,0 +1,170 @@
+use rendering::codecs::{new_image, ImageError};
+use rendering::filters::luminance;
+use rendering::image::RGBA;
+
+// Netpbm PPM (color) and PGM (gray) images, both the ASCII (P3, P2) and
+// binary (P6, P5) variants. Samples of up to 16 bits are scaled to 8 bits.
+// The formats have no alpha so decoded images are opaque.
+
+const MAX_VALUE: usize = 65535;
+
+pub fn decode(data: &[u8]) -> Result<RGBA, ImageError> {
+    if data.len() < 2 {
+        return Err(ImageError::Truncated);
+    }
+
+    let (channels, ascii) = match &data[..2] {
+        b"P2" => (1, true),
+        b"P3" => (3, true),
+        b"P5" => (1, false),
+        b"P6" => (3, false),
+        b"P1" | b"P4" => return Err(ImageError::Unsupported(String::from("bitmap (PBM)"))),
+        b"P7" => return Err(ImageError::Unsupported(String::from("PAM"))),
+        _ => {
+            return Err(ImageError::Malformed(String::from(
+                "missing P2/P3/P5/P6 signature",
+            )))
+        }
+    };
+
+    let mut tokens = Tokens { data, pos: 2 };
+    let w = tokens.number()?;
+    let h = tokens.number()?;
+    let max = tokens.number()?;
+    if max == 0 || max > MAX_VALUE {
+        return Err(ImageError::Malformed(format!("maximum value {}", max)));
+    }
+
+    let count = w
+        .checked_mul(h)
+        .and_then(|n| n.checked_mul(channels))
+        .ok_or(ImageError::Truncated)?;
+
+    // Checked before allocating so a large header over a short file fails
+    // fast. ASCII samples take at least a separator and a digit each.
+    let available = data.len() - tokens.pos;
+    let size = if max < 256 { 1 } else { 2 };
+    let needed = if ascii {
+        count.checked_mul(2)
+    } else {
+        count.checked_mul(size).and_then(|n| n.checked_add(1))
+    };
+    if needed.is_none_or(|n| n > available) {
+        return Err(ImageError::Truncated);
+    }
+    let mut image = new_image(w, h)?;
+
+    let mut samples = Vec::new();
+    if ascii {
+        for _ in 0..count {
+            let v = tokens.number()?;
+            if v > max {
+                return Err(ImageError::Malformed(format!(
+                    "sample {} above maximum {}",
+                    v, max
+                )));
+            }
+            samples.push(v);
+        }
+    } else {
+        // A single whitespace byte separates the header from the samples.
+        let start = tokens.pos + 1;
+        let end = start + count * size;
+        for s in data[start..end].chunks(size) {
+            let v = if size == 1 {
+                s[0] as usize
+            } else {
+                u16::from_be_bytes([s[0], s[1]]) as usize
+            };
+            samples.push(v.min(max));
+        }
+    }
+
+    let scale = |v: usize| ((v * 255 + max / 2) / max) as u8;
+    for (i, s) in samples.chunks(channels).enumerate() {
+        let color = if channels == 3 {
+            [scale(s[0]), scale(s[1]), scale(s[2]), 255]
+        } else {
+            let v = scale(s[0]);
+            [v, v, v, 255]
+        };
+        image.set(i % w, i / w, color);
+    }
+
+    Ok(image)
+}
+
+// Whitespace separated header fields and ASCII samples. Comments run from
+// '#' to the end of the line.
+struct Tokens<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn number(&mut self) -> Result<usize, ImageError> {
+        loop {
+            match self.data.get(self.pos) {
+                None => return Err(ImageError::Truncated),
+                Some(b'#') => {
+                    while self.pos < self.data.len() && self.data[self.pos] != b'\n' {
+                        self.pos += 1;
+                    }
+                }
+                Some(c) if c.is_ascii_whitespace() => self.pos += 1,
+                Some(_) => break,
+            }
+        }
+
+        let start = self.pos;
+        while self.pos < self.data.len() && self.data[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(ImageError::Malformed(format!(
+                "expected a number at offset {}",
+                start
+            )));
+        }
+
+        // At most 20 ASCII digits, so the parse only fails on overflow.
+        let digits = String::from_utf8_lossy(&self.data[start..self.pos]);
+        digits
+            .parse()
+            .map_err(|_| ImageError::Malformed(format!("number {} too large", digits)))
+    }
+}
+
+/// Encodes a binary (P6) PPM. Alpha is dropped.
+pub fn encode_ppm(image: &RGBA) -> Vec<u8> {
+    let w = image.bounds().w();
+    let h = image.bounds().h();
+
+    let mut out = format!("P6\n{} {}\n255\n", w, h).into_bytes();
+    out.reserve(w * h * 3);
+    for y in 0..h {
+        for x in 0..w {
+            let p = image.at(image.bounds().min.x + x, image.bounds().min.y + y);
+            out.extend_from_slice(&p[..3]);
+        }
+    }
+
+    out
+}
+
+/// Encodes a binary (P5) PGM from the image's luminance. Alpha is dropped.
+pub fn encode_pgm(image: &RGBA) -> Vec<u8> {
+    let w = image.bounds().w();
+    let h = image.bounds().h();
+
+    let mut out = format!("P5\n{} {}\n255\n", w, h).into_bytes();
+    out.reserve(w * h);
+    for y in 0..h {
+        for x in 0..w {
+            let p = image.at(image.bounds().min.x + x, image.bounds().min.y + y);
+            out.push(luminance(p[0], p[1], p[2]).round().min(255.0) as u8);
+        }
+    }
+
+    out
+}
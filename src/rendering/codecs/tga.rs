@@ -0,0 +1,288 @@
+use rendering::codecs::{new_image, put_u16, put_u32, ImageError, Reader};
+use rendering::image::RGBA;
+
+// Truevision TGA. Reads color mapped, true color and grayscale images,
+// raw or RLE compressed, in any of the four orientations. True color
+// pixels can be 15/16 bit (5-5-5 plus an attribute bit), 24 or 32 bit.
+//
+// 16 bit pixels only use the attribute bit as alpha when the descriptor
+// says there is one, as many writers leave it clear on opaque images.
+
+const HEADER_SIZE: usize = 18;
+const FOOTER: &[u8] = b"TRUEVISION-XFILE.\0";
+
+// Image types, the RLE variants are these plus 8.
+const COLOR_MAPPED: u8 = 1;
+const TRUE_COLOR: u8 = 2;
+const GRAYSCALE: u8 = 3;
+const RLE: u8 = 8;
+
+// Descriptor bits
+const ALPHA_BITS: u8 = 0x0f;
+const RIGHT_TO_LEFT: u8 = 0x10;
+const TOP_TO_BOTTOM: u8 = 0x20;
+
+struct Header {
+    image_type: u8,
+    depth: u8,
+    has_alpha: bool,
+    // Index of the first color map entry.
+    map_first: usize,
+}
+
+pub fn decode(data: &[u8]) -> Result<RGBA, ImageError> {
+    let mut r = Reader::new(data);
+
+    let id_length = r.u8()? as usize;
+    let map_type = r.u8()?;
+    let image_type = r.u8()?;
+    let map_first = r.u16()? as usize;
+    let map_length = r.u16()? as usize;
+    let map_depth = r.u8()?;
+    r.skip(4)?; // origin
+    let w = r.u16()? as usize;
+    let h = r.u16()? as usize;
+    let depth = r.u8()?;
+    let descriptor = r.u8()?;
+
+    let kind = image_type & !RLE;
+    match image_type {
+        0 => return Err(ImageError::Unsupported(String::from("no image data"))),
+        1 | 2 | 3 | 9 | 10 | 11 => (),
+        _ => {
+            return Err(ImageError::Unsupported(format!(
+                "image type {}",
+                image_type
+            )))
+        }
+    }
+
+    let depth_ok = match kind {
+        COLOR_MAPPED => depth == 8 || depth == 16,
+        TRUE_COLOR => depth == 15 || depth == 16 || depth == 24 || depth == 32,
+        GRAYSCALE => depth == 8 || depth == 16,
+        _ => false,
+    };
+    if !depth_ok {
+        return Err(ImageError::Unsupported(format!(
+            "{} bit pixels for image type {}",
+            depth, image_type
+        )));
+    }
+
+    if map_type > 1 {
+        return Err(ImageError::Malformed(format!(
+            "color map type {}",
+            map_type
+        )));
+    }
+    if kind == COLOR_MAPPED && map_type != 1 {
+        return Err(ImageError::Malformed(String::from("missing color map")));
+    }
+
+    let header = Header {
+        image_type,
+        depth,
+        has_alpha: descriptor & ALPHA_BITS != 0,
+        map_first,
+    };
+
+    r.skip(id_length)?;
+
+    // The color map is stored even when the image doesn't use it.
+    let mut map = Vec::new();
+    if map_type == 1 {
+        match map_depth {
+            15 | 16 | 24 | 32 => (),
+            _ => {
+                return Err(ImageError::Unsupported(format!(
+                    "{} bit color map",
+                    map_depth
+                )))
+            }
+        }
+        let entry_size = bytes_per_pixel(map_depth);
+        let entries = r.bytes(map_length * entry_size)?;
+        for entry in entries.chunks(entry_size) {
+            map.push(true_color(entry, header.has_alpha));
+        }
+    }
+
+    let size = bytes_per_pixel(depth);
+    let count = w * h;
+
+    // Checked before allocating so a large header over a short file fails
+    // fast. An RLE packet covers at most 128 pixels.
+    let needed = if image_type & RLE != 0 {
+        count.div_ceil(128) * (1 + size)
+    } else {
+        count * size
+    };
+    if needed > data.len() - r.pos {
+        return Err(ImageError::Truncated);
+    }
+    let mut image = new_image(w, h)?;
+
+    let mut pixels: Vec<[u8; 4]> = Vec::new();
+    if image_type & RLE != 0 {
+        while pixels.len() < count {
+            let packet = r.u8()?;
+            let n = (packet & 0x7f) as usize + 1;
+            if packet & 0x80 != 0 {
+                let color = to_color(r.bytes(size)?, &header, &map)?;
+                for _ in 0..n {
+                    pixels.push(color);
+                }
+            } else {
+                for _ in 0..n {
+                    pixels.push(to_color(r.bytes(size)?, &header, &map)?);
+                }
+            }
+        }
+        // A run may spill past the last pixel.
+        pixels.truncate(count);
+    } else {
+        for p in r.bytes(count * size)?.chunks(size) {
+            pixels.push(to_color(p, &header, &map)?);
+        }
+    }
+
+    for (i, color) in pixels.iter().enumerate() {
+        let row = i / w;
+        let col = i % w;
+        let x = if descriptor & RIGHT_TO_LEFT != 0 {
+            w - 1 - col
+        } else {
+            col
+        };
+        let y = if descriptor & TOP_TO_BOTTOM != 0 {
+            row
+        } else {
+            h - 1 - row
+        };
+        image.set(x, y, *color);
+    }
+
+    Ok(image)
+}
+
+fn bytes_per_pixel(depth: u8) -> usize {
+    (depth as usize).div_ceil(8)
+}
+
+fn to_color(p: &[u8], header: &Header, map: &[[u8; 4]]) -> Result<[u8; 4], ImageError> {
+    match header.image_type & !RLE {
+        COLOR_MAPPED => {
+            let index = if header.depth == 8 {
+                p[0] as usize
+            } else {
+                u16::from_le_bytes([p[0], p[1]]) as usize
+            };
+            index
+                .checked_sub(header.map_first)
+                .and_then(|i| map.get(i))
+                .cloned()
+                .ok_or_else(|| ImageError::Malformed(format!("color index {} out of range", index)))
+        }
+        TRUE_COLOR => Ok(true_color(p, header.has_alpha)),
+        _ => {
+            let alpha = if header.depth == 16 { p[1] } else { 255 };
+            Ok([p[0], p[0], p[0], alpha])
+        }
+    }
+}
+
+fn true_color(p: &[u8], has_alpha: bool) -> [u8; 4] {
+    match p.len() {
+        2 => {
+            let v = u16::from_le_bytes([p[0], p[1]]);
+            let scale = |c: u16| ((c & 0x1f) * 255 / 31) as u8;
+            let alpha = if has_alpha && v & 0x8000 == 0 { 0 } else { 255 };
+            [scale(v >> 10), scale(v >> 5), scale(v), alpha]
+        }
+        3 => [p[2], p[1], p[0], 255],
+        _ => [p[2], p[1], p[0], p[3]],
+    }
+}
+
+/// Encodes a 32 bit, top-to-bottom, image, RLE compressed if `rle`.
+/// Packets never cross rows. TGA sizes are 16 bit so larger images are
+/// refused.
+pub fn encode(image: &RGBA, rle: bool) -> Result<Vec<u8>, ImageError> {
+    let w = image.bounds().w();
+    let h = image.bounds().h();
+    if w > u16::MAX as usize || h > u16::MAX as usize {
+        return Err(ImageError::Unsupported(format!(
+            "image too large for TGA {}x{}",
+            w, h
+        )));
+    }
+
+    let mut out = Vec::with_capacity(HEADER_SIZE + w * h * 4);
+    out.push(0); // id length
+    out.push(0); // no color map
+    out.push(if rle { TRUE_COLOR | RLE } else { TRUE_COLOR });
+    out.extend_from_slice(&[0; 5]); // color map spec
+    put_u16(&mut out, 0);
+    put_u16(&mut out, 0);
+    put_u16(&mut out, w as u16);
+    put_u16(&mut out, h as u16);
+    out.push(32);
+    out.push(TOP_TO_BOTTOM | 8);
+
+    let mut row: Vec<[u8; 4]> = Vec::with_capacity(w);
+    for y in 0..h {
+        row.clear();
+        for x in 0..w {
+            let p = image.at(image.bounds().min.x + x, image.bounds().min.y + y);
+            row.push([p[2], p[1], p[0], p[3]]);
+        }
+
+        if rle {
+            encode_row(&row, &mut out);
+        } else {
+            for p in &row {
+                out.extend_from_slice(p);
+            }
+        }
+    }
+
+    // TGA 2.0 footer without extension or developer areas.
+    put_u32(&mut out, 0);
+    put_u32(&mut out, 0);
+    out.extend_from_slice(FOOTER);
+
+    Ok(out)
+}
+
+// Runs of two or more equal pixels become run packets, everything else
+// is gathered into raw packets. Both hold at most 128 pixels.
+fn encode_row(row: &[[u8; 4]], out: &mut Vec<u8>) {
+    let run_length = |start: usize| {
+        row[start..]
+            .iter()
+            .take(128)
+            .take_while(|&&p| p == row[start])
+            .count()
+    };
+
+    let mut i = 0;
+    while i < row.len() {
+        let run = run_length(i);
+        if run > 1 {
+            out.push(0x80 | (run - 1) as u8);
+            out.extend_from_slice(&row[i]);
+            i += run;
+            continue;
+        }
+
+        let start = i;
+        while i < row.len() && i - start < 128 && (i == start || run_length(i) < 2) {
+            i += 1;
+        }
+        out.push((i - start - 1) as u8);
+        for p in &row[start..i] {
+            out.extend_from_slice(p);
+        }
+    }
+}
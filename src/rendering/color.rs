@@ -1,4 +1,6 @@
-#[derive(Copy, Clone, Debug)]
+use math::interpolation::{Interpolation, Lerp};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Palette {
     pub r: u8,
     pub g: u8,
@@ -180,3 +182,21 @@ impl Palette {
         Palette::from_hex_rgb(0xaaaaff)
     }
 }
+
+impl Lerp for Palette {
+    /// Each component, including alpha, is interpolated independently.
+    fn lerp(from: &Palette, to: &Palette, t: f64) -> Palette {
+        Palette {
+            r: lerp_component(from.r, to.r, t),
+            g: lerp_component(from.g, to.g, t),
+            b: lerp_component(from.b, to.b, t),
+            a: lerp_component(from.a, to.a, t),
+        }
+    }
+}
+
+#[inline(always)]
+fn lerp_component(from: u8, to: u8, t: f64) -> u8 {
+    let v = Interpolation::lerp(from as f64, to as f64, t).round();
+    v.clamp(0.0, 255.0) as u8
+}
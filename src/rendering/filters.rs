@@ -0,0 +1,630 @@
+use math::interpolation::Interpolation;
+use rendering::color::Palette;
+use rendering::image::RGBA;
+
+// Full-frame software filters applied to an RGBA image, typically the
+// frame read back after a scene draws (see Context::apply_filters).
+//
+// Filters keep any scratch buffers they need between frames so a chain
+// doesn't allocate once warmed up. Unless noted otherwise alpha is left
+// alone.
+
+pub trait Filter {
+    fn apply(&mut self, image: &mut RGBA);
+}
+
+/// Filters applied in the order they were added.
+pub struct FilterChain {
+    filters: Vec<Box<dyn Filter>>,
+    enabled: bool,
+}
+
+impl FilterChain {
+    pub fn new() -> Self {
+        Self {
+            filters: Vec::new(),
+            enabled: true,
+        }
+    }
+
+    /// Returns the index of the new filter.
+    pub fn add(&mut self, filter: Box<dyn Filter>) -> usize {
+        self.filters.push(filter);
+        self.filters.len() - 1
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Box<dyn Filter>> {
+        if index < self.filters.len() {
+            Some(self.filters.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.filters.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// True if enabled and there is something to apply.
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.filters.is_empty()
+    }
+
+    pub fn apply(&mut self, image: &mut RGBA) {
+        if !self.enabled {
+            return;
+        }
+
+        for filter in self.filters.iter_mut() {
+            filter.apply(image);
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+// Blurs
+// ----------------------------------------------------------------
+/// Averages each pixel with its neighbors within `radius`. All four
+/// channels are blurred.
+pub struct BoxBlur {
+    kernel: Vec<f64>,
+    scratch: Vec<f64>,
+}
+
+impl BoxBlur {
+    pub fn new(radius: usize) -> Self {
+        let size = 2 * radius + 1;
+        Self {
+            kernel: vec![1.0 / size as f64; size],
+            scratch: Vec::new(),
+        }
+    }
+}
+
+impl Filter for BoxBlur {
+    fn apply(&mut self, image: &mut RGBA) {
+        convolve(image, &self.kernel, &mut self.scratch);
+    }
+}
+
+/// A Gaussian blur with standard deviation `sigma` pixels. All four
+/// channels are blurred.
+pub struct GaussianBlur {
+    kernel: Vec<f64>,
+    scratch: Vec<f64>,
+}
+
+impl GaussianBlur {
+    pub fn new(sigma: f64) -> Self {
+        Self {
+            kernel: gaussian_kernel(sigma),
+            scratch: Vec::new(),
+        }
+    }
+}
+
+impl Filter for GaussianBlur {
+    fn apply(&mut self, image: &mut RGBA) {
+        convolve(image, &self.kernel, &mut self.scratch);
+    }
+}
+
+/// A normalized kernel covering 3 sigma either side of the center.
+pub fn gaussian_kernel(sigma: f64) -> Vec<f64> {
+    if sigma <= 0.0 {
+        return vec![1.0];
+    }
+
+    let radius = (3.0 * sigma).ceil() as i32;
+    let mut kernel: Vec<f64> = (-radius..=radius)
+        .map(|i| (-((i * i) as f64) / (2.0 * sigma * sigma)).exp())
+        .collect();
+
+    let sum: f64 = kernel.iter().sum();
+    for k in kernel.iter_mut() {
+        *k /= sum;
+    }
+
+    kernel
+}
+
+// Applies a symmetric, odd sized, kernel horizontally then vertically.
+// Edge pixels are repeated beyond the image's bounds.
+fn convolve(image: &mut RGBA, kernel: &[f64], scratch: &mut Vec<f64>) {
+    let w = image.bounds().w();
+    let h = image.bounds().h();
+    if w == 0 || h == 0 || kernel.len() < 2 {
+        return;
+    }
+
+    let radius = (kernel.len() / 2) as isize;
+    let stride = image.stride;
+
+    scratch.clear();
+    scratch.resize(w * h * 4, 0.0);
+
+    // Horizontal pass: image -> scratch
+    for y in 0..h {
+        let row = y * stride;
+        for x in 0..w {
+            let mut acc = [0.0; 4];
+            for (k, weight) in kernel.iter().enumerate() {
+                let sx = (x as isize + k as isize - radius).clamp(0, w as isize - 1) as usize;
+                let i = row + sx * 4;
+                for (c, a) in acc.iter_mut().enumerate() {
+                    *a += image.pix[i + c] as f64 * weight;
+                }
+            }
+            let o = (y * w + x) * 4;
+            scratch[o..o + 4].copy_from_slice(&acc);
+        }
+    }
+
+    // Vertical pass: scratch -> image
+    for y in 0..h {
+        for x in 0..w {
+            let mut acc = [0.0; 4];
+            for (k, weight) in kernel.iter().enumerate() {
+                let sy = (y as isize + k as isize - radius).clamp(0, h as isize - 1) as usize;
+                let o = (sy * w + x) * 4;
+                for c in 0..4 {
+                    acc[c] += scratch[o + c] * weight;
+                }
+            }
+            let i = y * stride + x * 4;
+            for (c, a) in acc.iter().enumerate() {
+                image.pix[i + c] = to_u8(*a);
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+// Bloom
+// ----------------------------------------------------------------
+/// Makes bright areas glow: pixels whose luminance is at least
+/// `threshold` are blurred and added back onto the image.
+pub struct Bloom {
+    threshold: u8,
+    intensity: f64,
+    blur: GaussianBlur,
+    bright: RGBA,
+}
+
+impl Bloom {
+    pub fn new(threshold: u8, intensity: f64, sigma: f64) -> Self {
+        Self {
+            threshold,
+            intensity,
+            blur: GaussianBlur::new(sigma),
+            bright: RGBA::new(0, 0),
+        }
+    }
+}
+
+impl Filter for Bloom {
+    fn apply(&mut self, image: &mut RGBA) {
+        let w = image.bounds().w();
+        let h = image.bounds().h();
+        if self.bright.bounds().w() != w || self.bright.bounds().h() != h {
+            self.bright = RGBA::new(w, h);
+        }
+
+        // Bright pass
+        for y in 0..h {
+            for x in 0..w {
+                let i = y * image.stride + x * 4;
+                let o = y * self.bright.stride + x * 4;
+                let p = &image.pix[i..i + 4];
+                let keep = luminance(p[0], p[1], p[2]) >= self.threshold as f64;
+                for (c, v) in p.iter().take(3).enumerate() {
+                    self.bright.pix[o + c] = if keep { *v } else { 0 };
+                }
+                self.bright.pix[o + 3] = 255;
+            }
+        }
+
+        self.blur.apply(&mut self.bright);
+
+        // Additive composite
+        for y in 0..h {
+            for x in 0..w {
+                let i = y * image.stride + x * 4;
+                let o = y * self.bright.stride + x * 4;
+                for c in 0..3 {
+                    let glow = self.bright.pix[o + c] as f64 * self.intensity;
+                    image.pix[i + c] = to_u8(image.pix[i + c] as f64 + glow);
+                }
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+// Color grading
+// ----------------------------------------------------------------
+/// Maps each color channel through its own 256 entry lookup table.
+pub struct ColorGrade {
+    lut: [[u8; 256]; 3],
+}
+
+impl ColorGrade {
+    pub fn identity() -> Self {
+        ColorGrade::from_fn(|_, v| v)
+    }
+
+    /// Builds the tables from `f(channel, value)` where channel is 0, 1
+    /// or 2 for red, green and blue.
+    pub fn from_fn<F: Fn(usize, u8) -> u8>(f: F) -> Self {
+        let mut lut = [[0u8; 256]; 3];
+        for (channel, table) in lut.iter_mut().enumerate() {
+            for (v, entry) in table.iter_mut().enumerate() {
+                *entry = f(channel, v as u8);
+            }
+        }
+        Self { lut }
+    }
+
+    pub fn from_tables(red: [u8; 256], green: [u8; 256], blue: [u8; 256]) -> Self {
+        Self {
+            lut: [red, green, blue],
+        }
+    }
+
+    /// `brightness` is added, in the range [-1, 1], and `contrast` scales
+    /// about mid gray, 1.0 being unchanged.
+    pub fn brightness_contrast(brightness: f64, contrast: f64) -> Self {
+        ColorGrade::from_fn(|_, v| {
+            let n = v as f64 / 255.0;
+            to_u8(((n - 0.5) * contrast + 0.5 + brightness) * 255.0)
+        })
+    }
+
+    /// Multiplies each channel, e.g. (1.1, 1.0, 0.9) warms the image.
+    pub fn tint(red: f64, green: f64, blue: f64) -> Self {
+        let scales = [red, green, blue];
+        ColorGrade::from_fn(|channel, v| to_u8(v as f64 * scales[channel]))
+    }
+
+    pub fn invert() -> Self {
+        ColorGrade::from_fn(|_, v| 255 - v)
+    }
+
+    pub fn table(&self, channel: usize) -> &[u8; 256] {
+        &self.lut[channel]
+    }
+}
+
+impl Filter for ColorGrade {
+    fn apply(&mut self, image: &mut RGBA) {
+        let w = image.bounds().w();
+        for y in 0..image.bounds().h() {
+            for x in 0..w {
+                let i = y * image.stride + x * 4;
+                for c in 0..3 {
+                    image.pix[i + c] = self.lut[c][image.pix[i + c] as usize];
+                }
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+// Vignette
+// ----------------------------------------------------------------
+/// Darkens towards the corners. Distances are normalized so that the
+/// center is 0.0 and the corners are 1.0. Darkening starts at `radius`
+/// and reaches `strength` at `radius + softness`, a softness of 0.0 gives
+/// a hard edge.
+pub struct Vignette {
+    strength: f64,
+    radius: f64,
+    softness: f64,
+}
+
+impl Vignette {
+    pub fn new(strength: f64, radius: f64, softness: f64) -> Self {
+        Self {
+            strength: strength.clamp(0.0, 1.0),
+            radius,
+            softness: softness.max(0.0),
+        }
+    }
+}
+
+impl Filter for Vignette {
+    fn apply(&mut self, image: &mut RGBA) {
+        let w = image.bounds().w();
+        let h = image.bounds().h();
+        let cx = (w as f64 - 1.0) / 2.0;
+        let cy = (h as f64 - 1.0) / 2.0;
+        let max = (cx * cx + cy * cy).sqrt().max(f64::EPSILON);
+
+        for y in 0..h {
+            for x in 0..w {
+                let dx = x as f64 - cx;
+                let dy = y as f64 - cy;
+                let d = (dx * dx + dy * dy).sqrt() / max;
+                let edge = self.radius + self.softness;
+                // A softness lost to rounding against a large radius
+                // would leave smoothstep dividing by zero.
+                let falloff = if edge > self.radius {
+                    Interpolation::smoothstep(self.radius, edge, d)
+                } else if d >= self.radius {
+                    1.0
+                } else {
+                    0.0
+                };
+                let scale = 1.0 - self.strength * falloff;
+
+                let i = y * image.stride + x * 4;
+                for c in 0..3 {
+                    image.pix[i + c] = to_u8(image.pix[i + c] as f64 * scale);
+                }
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+// CRT scanlines
+// ----------------------------------------------------------------
+/// Darkens `thickness` rows out of every `spacing` rows by `darkness`,
+/// 0.0 being no change and 1.0 black.
+pub struct Scanlines {
+    spacing: usize,
+    thickness: usize,
+    darkness: f64,
+}
+
+impl Scanlines {
+    pub fn new(spacing: usize, thickness: usize, darkness: f64) -> Self {
+        Self {
+            spacing: spacing.max(1),
+            thickness,
+            darkness: darkness.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Filter for Scanlines {
+    fn apply(&mut self, image: &mut RGBA) {
+        let w = image.bounds().w();
+        let scale = 1.0 - self.darkness;
+
+        for y in 0..image.bounds().h() {
+            if y % self.spacing >= self.thickness {
+                continue;
+            }
+            for x in 0..w {
+                let i = y * image.stride + x * 4;
+                for c in 0..3 {
+                    image.pix[i + c] = to_u8(image.pix[i + c] as f64 * scale);
+                }
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------
+// Palette quantization
+// ----------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dither {
+    None,
+    /// 4x4 Bayer matrix. `spread` is the largest offset, in color levels,
+    /// added before picking the nearest color.
+    Ordered {
+        spread: f64,
+    },
+    /// Error diffusion.
+    FloydSteinberg,
+}
+
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Reduces the image to the colors of a palette.
+pub struct Quantize {
+    palette: Vec<Palette>,
+    dither: Dither,
+    // Floyd-Steinberg error for the current and next rows.
+    errors: [Vec<f64>; 2],
+}
+
+impl Quantize {
+    pub fn new(palette: Vec<Palette>, dither: Dither) -> Self {
+        Self {
+            palette,
+            dither,
+            errors: [Vec::new(), Vec::new()],
+        }
+    }
+
+    /// The palette color closest to (r, g, b).
+    pub fn nearest(&self, r: f64, g: f64, b: f64) -> Palette {
+        let mut best = Palette::BLACK();
+        let mut best_distance = f64::MAX;
+
+        for color in self.palette.iter() {
+            let dr = color.r as f64 - r;
+            let dg = color.g as f64 - g;
+            let db = color.b as f64 - b;
+            let distance = dr * dr + dg * dg + db * db;
+            if distance < best_distance {
+                best_distance = distance;
+                best = *color;
+            }
+        }
+
+        best
+    }
+
+    fn floyd_steinberg(&mut self, image: &mut RGBA) {
+        let w = image.bounds().w();
+        for row in self.errors.iter_mut() {
+            row.clear();
+            row.resize((w + 2) * 3, 0.0);
+        }
+
+        for y in 0..image.bounds().h() {
+            for x in 0..w {
+                let i = y * image.stride + x * 4;
+                // Errors are offset by one pixel so x - 1 is in bounds.
+                let e = (x + 1) * 3;
+
+                let want = [
+                    image.pix[i] as f64 + self.errors[0][e],
+                    image.pix[i + 1] as f64 + self.errors[0][e + 1],
+                    image.pix[i + 2] as f64 + self.errors[0][e + 2],
+                ];
+                let got = self.nearest(want[0], want[1], want[2]);
+                image.pix[i] = got.r;
+                image.pix[i + 1] = got.g;
+                image.pix[i + 2] = got.b;
+
+                let got = [got.r as f64, got.g as f64, got.b as f64];
+                for c in 0..3 {
+                    let err = want[c] - got[c];
+                    self.errors[0][e + 3 + c] += err * 7.0 / 16.0;
+                    self.errors[1][e - 3 + c] += err * 3.0 / 16.0;
+                    self.errors[1][e + c] += err * 5.0 / 16.0;
+                    self.errors[1][e + 3 + c] += err * 1.0 / 16.0;
+                }
+            }
+
+            self.errors.swap(0, 1);
+            for v in self.errors[1].iter_mut() {
+                *v = 0.0;
+            }
+        }
+    }
+}
+
+impl Filter for Quantize {
+    fn apply(&mut self, image: &mut RGBA) {
+        if self.palette.is_empty() {
+            return;
+        }
+
+        let w = image.bounds().w();
+        match self.dither {
+            Dither::FloydSteinberg => self.floyd_steinberg(image),
+            Dither::None | Dither::Ordered { .. } => {
+                for y in 0..image.bounds().h() {
+                    for x in 0..w {
+                        let offset = match self.dither {
+                            Dither::Ordered { spread } => {
+                                (BAYER_4X4[y % 4][x % 4] as f64 + 0.5) / 16.0 * spread
+                                    - spread / 2.0
+                            }
+                            _ => 0.0,
+                        };
+
+                        let i = y * image.stride + x * 4;
+                        let got = self.nearest(
+                            image.pix[i] as f64 + offset,
+                            image.pix[i + 1] as f64 + offset,
+                            image.pix[i + 2] as f64 + offset,
+                        );
+                        image.pix[i] = got.r;
+                        image.pix[i + 1] = got.g;
+                        image.pix[i + 2] = got.b;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Builds a palette of at most `colors` colors suited to the image using
+/// median cut. Pixels with alpha below 128 are ignored.
+pub fn median_cut(image: &RGBA, colors: usize) -> Vec<Palette> {
+    let mut pixels: Vec<[u8; 3]> = Vec::with_capacity(image.bounds().size());
+    for y in 0..image.bounds().h() {
+        let row = y * image.stride;
+        for p in image.pix[row..row + image.bounds().w() * 4].chunks(4) {
+            if p[3] >= 128 {
+                pixels.push([p[0], p[1], p[2]]);
+            }
+        }
+    }
+
+    if pixels.is_empty() || colors == 0 {
+        return vec![Palette::BLACK()];
+    }
+
+    // Boxes are ranges of `pixels`. The box with the widest channel is
+    // sorted on that channel and split at the median until there are
+    // enough boxes or none can be split.
+    let mut boxes = vec![(0, pixels.len())];
+    while boxes.len() < colors {
+        let mut widest = None;
+        let mut widest_extent = 0;
+        for (b, &(start, end)) in boxes.iter().enumerate() {
+            for c in 0..3 {
+                let (min, max) = pixels[start..end]
+                    .iter()
+                    .fold((255, 0), |(lo, hi), p| (p[c].min(lo), p[c].max(hi)));
+                if max - min > widest_extent {
+                    widest_extent = max - min;
+                    widest = Some((b, c));
+                }
+            }
+        }
+
+        let (b, c) = match widest {
+            Some(widest) => widest,
+            None => break,
+        };
+
+        let (start, end) = boxes[b];
+        pixels[start..end].sort_unstable_by_key(|p| p[c]);
+        let mid = start + (end - start) / 2;
+        boxes[b] = (start, mid);
+        boxes.push((mid, end));
+    }
+
+    boxes
+        .iter()
+        .map(|&(start, end)| {
+            let mut sum = [0usize; 3];
+            for p in pixels[start..end].iter() {
+                for (s, &v) in sum.iter_mut().zip(p.iter()) {
+                    *s += v as usize;
+                }
+            }
+            let n = end - start;
+            Palette::RGB(
+                ((sum[0] + n / 2) / n) as u8,
+                ((sum[1] + n / 2) / n) as u8,
+                ((sum[2] + n / 2) / n) as u8,
+            )
+        })
+        .collect()
+}
+
+// ----------------------------------------------------------------
+// Helpers
+// ----------------------------------------------------------------
+/// Rec. 601 luma in the range [0, 255].
+#[inline]
+pub fn luminance(r: u8, g: u8, b: u8) -> f64 {
+    0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64
+}
+
+#[inline]
+fn to_u8(v: f64) -> u8 {
+    v.round().clamp(0.0, 255.0) as u8
+}
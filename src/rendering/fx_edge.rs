@@ -1,4 +1,5 @@
 use geometry::point::Point;
+use rendering::fx_gradients::{Attributes, FXGradients, ATTRIBUTES};
 
 // -----------------------------------------------------
 // Edge
@@ -14,6 +15,12 @@ pub struct FXEdge {
     // current y and vertical count
     pub y: i64,
     pub height: i64,
+
+    // Interpolated vertex attributes at (x, y). Only valid after
+    // set_interpolated().
+    pub values: Attributes,
+    values_step: Attributes,
+    values_extra: Attributes,
 }
 
 impl FXEdge {
@@ -26,6 +33,9 @@ impl FXEdge {
             error_term: 0,
             y: 0,
             height: 0,
+            values: [0.0; ATTRIBUTES],
+            values_step: [0.0; ATTRIBUTES],
+            values_extra: [0.0; ATTRIBUTES],
         }
     }
 
@@ -71,16 +81,44 @@ impl FXEdge {
         }
     }
 
+    /// Sets the edge and the attribute values at its first pixel.
+    #[allow(clippy::needless_range_loop)]
+    pub fn set_interpolated(
+        &mut self,
+        top: &Point,
+        bot: &Point,
+        top_values: &Attributes,
+        grads: &FXGradients,
+    ) {
+        self.set(top, bot);
+
+        // Pre-step from the vertex to the first pixel the edge covers.
+        let y_prestep = self.y as f64 - top.y;
+        let x_prestep = self.x as f64 - top.x;
+
+        for i in 0..ATTRIBUTES {
+            self.values[i] = top_values[i] + y_prestep * grads.d_dy[i] + x_prestep * grads.d_dx[i];
+            self.values_step[i] = self.x_step as f64 * grads.d_dx[i] + grads.d_dy[i];
+            self.values_extra[i] = grads.d_dx[i];
+        }
+    }
+
     pub fn step(&mut self) {
         // println!("step: {}, {}", self.x_step, self.x);
         self.x += self.x_step;
         self.y += 1;
         self.height -= 1; // This is a counter
+        for (value, step) in self.values.iter_mut().zip(&self.values_step) {
+            *value += step;
+        }
 
         self.error_term += self.numerator;
         if self.error_term >= self.denominator {
             self.x += 1;
             self.error_term -= self.denominator;
+            for (value, extra) in self.values.iter_mut().zip(&self.values_extra) {
+                *value += extra;
+            }
         }
     }
 }
@@ -0,0 +1,51 @@
+use geometry::point::Point;
+
+/// The number of attributes that can be interpolated across a triangle,
+/// for example, (u, v) or (r, g, b, a).
+pub const ATTRIBUTES: usize = 4;
+
+pub type Attributes = [f64; ATTRIBUTES];
+
+// -----------------------------------------------------
+// Gradients
+// -----------------------------------------------------
+// The rates of change of each vertex attribute across the screen.
+// Because the interpolation is affine (no perspective) they are constant
+// over the whole triangle.
+pub struct FXGradients {
+    pub d_dx: Attributes,
+    pub d_dy: Attributes,
+}
+
+impl FXGradients {
+    pub fn new() -> Self {
+        Self {
+            d_dx: [0.0; ATTRIBUTES],
+            d_dy: [0.0; ATTRIBUTES],
+        }
+    }
+
+    /// `p` are the screen vertices and `values` the matching attributes.
+    /// Returns false if the triangle is degenerate.
+    #[allow(clippy::needless_range_loop)]
+    pub fn set(&mut self, p: &[Point; 3], values: &[Attributes; 3]) -> bool {
+        let dx = (p[1].x - p[2].x) * (p[0].y - p[2].y) - (p[0].x - p[2].x) * (p[1].y - p[2].y);
+
+        if dx == 0.0 {
+            return false;
+        }
+
+        let one_over_dx = 1.0 / dx;
+        let one_over_dy = -one_over_dx;
+
+        for i in 0..ATTRIBUTES {
+            let d12 = values[1][i] - values[2][i];
+            let d02 = values[0][i] - values[2][i];
+
+            self.d_dx[i] = (d12 * (p[0].y - p[2].y) - d02 * (p[1].y - p[2].y)) * one_over_dx;
+            self.d_dy[i] = (d12 * (p[0].x - p[2].x) - d02 * (p[1].x - p[2].x)) * one_over_dy;
+        }
+
+        true
+    }
+}
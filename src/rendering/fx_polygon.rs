@@ -0,0 +1,219 @@
+use std::cell::RefCell;
+
+use geometry::clipping::{Clipping, PolygonClipper};
+use geometry::point::Point;
+use geometry::rectangle::Rectangle;
+use rendering::fx_edge::FXEdge;
+use rendering::render_context::Context;
+
+// A scanline polygon rasterizer built on the same edge stepping as
+// FXTriangle, and therefore sharing its fill conventions (see
+// FXTriangle::scan):
+// vertices are truncated, scanlines run from ceil(top) up to but not
+// including ceil(bottom) and each span covers [ceil(left), ceil(right)).
+// Shapes that share an edge neither overlap nor leave gaps.
+//
+// A polygon is made of one or more closed contours. Holes are simply
+// additional contours; whether they are filled depends on the FillRule
+// (and for NonZero, the hole's orientation).
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillRule {
+    /// A pixel is inside if a ray from it crosses an odd number of edges.
+    EvenOdd,
+    /// A pixel is inside if the edges wind around it a non-zero number of
+    /// times.
+    NonZero,
+}
+
+struct PolyEdge {
+    edge: FXEdge,
+    // +1 if the edge runs downwards, -1 upwards.
+    winding: i32,
+}
+
+pub struct FXPolygon {
+    points: Vec<Point>,
+    // The end (exclusive) index into `points` of each contour.
+    contours: Vec<usize>,
+
+    rule: FillRule,
+
+    // Scratch buffers. By defining them as RefCells the scan methods can
+    // be immutable.
+    edges: RefCell<Vec<PolyEdge>>,
+    active: RefCell<Vec<usize>>,
+    crossings: RefCell<Vec<(i64, i32)>>,
+}
+
+impl FXPolygon {
+    pub fn new() -> Self {
+        Self {
+            points: Vec::new(),
+            contours: Vec::new(),
+            rule: FillRule::NonZero,
+            edges: RefCell::new(Vec::new()),
+            active: RefCell::new(Vec::new()),
+            crossings: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn set_fill_rule(&mut self, rule: FillRule) {
+        self.rule = rule;
+    }
+
+    pub fn fill_rule(&self) -> FillRule {
+        self.rule
+    }
+
+    /// Removes all contours.
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.contours.clear();
+    }
+
+    /// Replaces the polygon with a single contour.
+    pub fn set(&mut self, vertices: &[Point]) {
+        self.clear();
+        self.add_contour(vertices);
+    }
+
+    /// Adds a contour. The contour is implicitly closed, the last vertex
+    /// connects back to the first.
+    pub fn add_contour(&mut self, vertices: &[Point]) {
+        for v in vertices {
+            self.points
+                .push(Point::from_xy(f64::trunc(v.x), f64::trunc(v.y)));
+        }
+        self.contours.push(self.points.len());
+    }
+
+    /// Clips every contour to `rect`. Contours entirely outside are
+    /// removed.
+    pub fn clip(&mut self, rect: &Rectangle, clipper: &mut PolygonClipper) {
+        if Clipping::contains_all(rect, &self.points) {
+            return;
+        }
+
+        let points = std::mem::take(&mut self.points);
+        let contours = std::mem::take(&mut self.contours);
+        let mut clipped = Vec::new();
+
+        let mut first = 0;
+        for &end in contours.iter() {
+            clipper.clip(rect, &points[first..end], &mut clipped);
+            first = end;
+
+            if clipped.len() > 2 {
+                self.add_contour(&clipped);
+            }
+        }
+    }
+
+    pub fn draw(&self, context: &Context) {
+        // draw_horz_line is inclusive of both ends.
+        self.scan(|x1, x2, y| context.draw_horz_line(x1, x2 - 1, y));
+    }
+
+    /// Scan converts the polygon passing each span to `span` as
+    /// (x_start, x_end, y) where x_end is exclusive. Spans are produced top
+    /// to bottom then left to right.
+    pub fn scan<F>(&self, mut span: F)
+    where
+        F: FnMut(i32, i32, i32),
+    {
+        let mut edges = self.edges.borrow_mut();
+        let mut active = self.active.borrow_mut();
+        let mut crossings = self.crossings.borrow_mut();
+
+        self.build_edges(&mut edges);
+        if edges.is_empty() {
+            return;
+        }
+
+        active.clear();
+
+        let mut next = 0; // Next edge (in y order) to activate
+        let mut y = edges[0].edge.y;
+
+        while next < edges.len() || !active.is_empty() {
+            // Jump over any empty gap between contours
+            if active.is_empty() && edges[next].edge.y > y {
+                y = edges[next].edge.y;
+            }
+
+            while next < edges.len() && edges[next].edge.y == y {
+                active.push(next);
+                next += 1;
+            }
+
+            crossings.clear();
+            for &i in active.iter() {
+                crossings.push((edges[i].edge.x, edges[i].winding));
+            }
+            crossings.sort_by_key(|c| c.0);
+
+            let mut winding = 0;
+            let mut start = 0;
+            for &(x, w) in crossings.iter() {
+                let was_inside = self.inside(winding);
+                winding += w;
+                let is_inside = self.inside(winding);
+
+                if !was_inside && is_inside {
+                    start = x;
+                } else if was_inside && !is_inside && x > start {
+                    span(start as i32, x as i32, y as i32);
+                }
+            }
+
+            for &i in active.iter() {
+                edges[i].edge.step();
+            }
+            active.retain(|&i| edges[i].edge.height > 0);
+
+            y += 1;
+        }
+    }
+
+    #[inline]
+    fn inside(&self, winding: i32) -> bool {
+        match self.rule {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+        }
+    }
+
+    // Builds the edge list sorted by starting scanline. Edges that don't
+    // cross a scanline center (e.g. horizontal edges) are dropped.
+    fn build_edges(&self, edges: &mut Vec<PolyEdge>) {
+        edges.clear();
+
+        let mut first = 0;
+        for &end in self.contours.iter() {
+            let contour = &self.points[first..end];
+            first = end;
+
+            for (i, p0) in contour.iter().enumerate() {
+                let p1 = &contour[(i + 1) % contour.len()];
+
+                if f64::ceil(p0.y) == f64::ceil(p1.y) {
+                    continue;
+                }
+
+                let mut edge = FXEdge::new();
+                let winding = if p0.y < p1.y {
+                    edge.set(p0, p1);
+                    1
+                } else {
+                    edge.set(p1, p0);
+                    -1
+                };
+
+                edges.push(PolyEdge { edge, winding });
+            }
+        }
+
+        edges.sort_by_key(|e| e.edge.y);
+    }
+}
@@ -3,14 +3,19 @@ extern crate sdl2;
 use std::cell::RefCell;
 
 use geometry::point::Point;
+use rendering::color::Palette;
 use rendering::fx_edge::FXEdge;
+use rendering::fx_gradients::{Attributes, FXGradients};
+use rendering::image::RGBA;
 use rendering::render_context::Context;
+use rendering::texture_sampler::TextureSampler;
 
 // This is the fastest rasterizer of the bunch.
 
-// A port of Chris Hecker's texture rasterizers with the
-// the texture part removed, thus making this a simple
-// flat triangle rasterizer.
+// A port of Chris Hecker's texture rasterizers. draw() is a simple
+// flat triangle rasterizer (see scan() for its spans), draw_textured() is the affine
+// (perspective-free) texture mapper and draw_shaded() interpolates
+// per-vertex colors.
 // http://chrishecker.com/Miscellaneous_Technical_Articles
 
 type RfFXEdge = RefCell<FXEdge>;
@@ -29,8 +34,17 @@ pub struct FXTriangle {
     top_to_middle: RfFXEdge,
     middle_to_bottom: RfFXEdge,
 
-    /// true = clockwise, false = counter-clockwise
-    clockwise: bool,
+    // Normalized texture coordinates for p0, p1 and p2
+    uv0: Point,
+    uv1: Point,
+    uv2: Point,
+
+    // Colors for p0, p1 and p2
+    c0: Palette,
+    c1: Palette,
+    c2: Palette,
+
+    gradients: RefCell<FXGradients>,
 }
 
 // Coord system
@@ -51,11 +65,17 @@ impl FXTriangle {
             top_to_bottom: RefCell::new(FXEdge::new()),
             top_to_middle: RefCell::new(FXEdge::new()),
             middle_to_bottom: RefCell::new(FXEdge::new()),
-            clockwise: false,
+            uv0: p,
+            uv1: p,
+            uv2: p,
+            c0: Palette::WHITE(255),
+            c1: Palette::WHITE(255),
+            c2: Palette::WHITE(255),
+            gradients: RefCell::new(FXGradients::new()),
         }
     }
 
-    /// Points are specified in Clockwise order
+    /// Points can be in either winding order.
     pub fn with_points(p0: Point, p1: Point, p2: Point) -> Self {
         Self {
             p0: p0,
@@ -64,11 +84,13 @@ impl FXTriangle {
             top_to_bottom: RefCell::new(FXEdge::new()),
             top_to_middle: RefCell::new(FXEdge::new()),
             middle_to_bottom: RefCell::new(FXEdge::new()),
-            clockwise: if orientation(&p0, &p1, &p2) == 1 {
-                true
-            } else {
-                false
-            },
+            uv0: Point::new(),
+            uv1: Point::new(),
+            uv2: Point::new(),
+            c0: Palette::WHITE(255),
+            c1: Palette::WHITE(255),
+            c2: Palette::WHITE(255),
+            gradients: RefCell::new(FXGradients::new()),
         }
     }
 
@@ -76,207 +98,221 @@ impl FXTriangle {
         self.p0.set_xy(f64::trunc(x0), f64::trunc(y0));
         self.p1.set_xy(f64::trunc(x1), f64::trunc(y1));
         self.p2.set_xy(f64::trunc(x2), f64::trunc(y2));
-        self.clockwise = if orientation(&self.p0, &self.p1, &self.p2) == 1 {
-            true
-        } else {
-            false
-        };
     }
 
-    pub fn set_clockwise(&mut self) {
-        self.clockwise = true;
+    /// Sets the texture coordinates matching the points given to set().
+    /// (0,0) is the texture's top-left corner and (1,1) its bottom-right.
+    /// Values outside [0,1] are resolved by the sampler's wrap mode.
+    pub fn set_uvs(&mut self, u0: f64, v0: f64, u1: f64, v1: f64, u2: f64, v2: f64) {
+        self.uv0.set_xy(u0, v0);
+        self.uv1.set_xy(u1, v1);
+        self.uv2.set_xy(u2, v2);
     }
 
-    pub fn draw(&self, context: &Context) {
-        let top: &Point;
-        let middle: &Point;
-        let bottom: &Point;
-        let middle_for_compare: u8;
-        let bottom_for_compare: u8;
-        let y0 = self.p0.y;
-        let y1 = self.p1.y;
-        let y2 = self.p2.y;
+    /// Sets the colors matching the points given to set().
+    pub fn set_colors(&mut self, c0: &Palette, c1: &Palette, c2: &Palette) {
+        self.c0 = *c0;
+        self.c1 = *c1;
+        self.c2 = *c2;
+    }
+
+    /// Rasterizes the triangle with `texture` mapped onto it using the
+    /// UVs from set_uvs(). Each covered pixel and its sampled color is
+    /// handed to `plot`, which makes any pixel target possible.
+    /// Winding doesn't matter.
+    pub fn draw_textured<F>(&self, texture: &RGBA, sampler: &TextureSampler, mut plot: F)
+    where
+        F: FnMut(i32, i32, [u8; 4]),
+    {
+        let bounds = texture.bounds();
+        let tw = bounds.w() as f64;
+        let th = bounds.h() as f64;
+
+        // Texel-space coordinates
+        let values = [
+            [self.uv0.x * tw, self.uv0.y * th, 0.0, 0.0],
+            [self.uv1.x * tw, self.uv1.y * th, 0.0, 0.0],
+            [self.uv2.x * tw, self.uv2.y * th, 0.0, 0.0],
+        ];
+
+        self.rasterize(&values, |x, y, uv| {
+            plot(x, y, sampler.sample(texture, uv[0], uv[1]));
+        });
+    }
+
+    /// Rasterizes the triangle blending the colors from set_colors()
+    /// (Gouraud shading), alpha included. Each covered pixel and its color
+    /// is handed to `plot`. Winding doesn't matter.
+    pub fn draw_shaded<F>(&self, mut plot: F)
+    where
+        F: FnMut(i32, i32, [u8; 4]),
+    {
+        let values = [
+            color_attributes(&self.c0),
+            color_attributes(&self.c1),
+            color_attributes(&self.c2),
+        ];
+
+        self.rasterize(&values, |x, y, c| {
+            plot(
+                x,
+                y,
+                [
+                    to_component(c[0]),
+                    to_component(c[1]),
+                    to_component(c[2]),
+                    to_component(c[3]),
+                ],
+            );
+        });
+    }
+
+    // Scan converts the triangle interpolating `values` (one set per
+    // point) across it. `plot` receives each pixel and the values at the
+    // pixel's center.
+    fn rasterize<F>(&self, values: &[Attributes; 3], mut plot: F)
+    where
+        F: FnMut(i32, i32, &Attributes),
+    {
+        let p = [self.p0, self.p1, self.p2];
+        if p.iter().any(|v| !v.x.is_finite() || !v.y.is_finite()) {
+            return; // Degenerate
+        }
+
+        let mut grads = self.gradients.borrow_mut();
+        if !grads.set(&p, values) {
+            return; // Degenerate
+        }
 
         // sort vertices in y
-        if y0 < y1 {
-            if y2 < y0 {
-                top = &self.p2;
-                middle = &self.p0;
-                bottom = &self.p1;
-                middle_for_compare = 0;
-                bottom_for_compare = 1;
+        let mut order = [0usize, 1, 2];
+        order.sort_by(|a, b| p[*a].y.total_cmp(&p[*b].y));
+        let (top, middle, bottom) = (order[0], order[1], order[2]);
+
+        let mut ttb = self.top_to_bottom.borrow_mut();
+        let mut ttm = self.top_to_middle.borrow_mut();
+        let mut mtb = self.middle_to_bottom.borrow_mut();
+
+        ttb.set_interpolated(&p[top], &p[bottom], &values[top], &grads);
+        ttm.set_interpolated(&p[top], &p[middle], &values[top], &grads);
+        mtb.set_interpolated(&p[middle], &p[bottom], &values[middle], &grads);
+
+        // The middle vertex is on the left if it lies to the left of the
+        // long edge.
+        let cross = (p[bottom].x - p[top].x) * (p[middle].y - p[top].y)
+            - (p[bottom].y - p[top].y) * (p[middle].x - p[top].x);
+        let middle_is_left = cross > 0.0;
+
+        for _ in 0..ttm.height {
+            if middle_is_left {
+                scan_interpolated(&ttm, &ttb, &grads, &mut plot);
             } else {
-                top = &self.p0;
-                if y1 < y2 {
-                    middle = &self.p1;
-                    bottom = &self.p2;
-                    middle_for_compare = 1;
-                    bottom_for_compare = 2;
-                } else {
-                    middle = &self.p2;
-                    bottom = &self.p1;
-                    middle_for_compare = 2;
-                    bottom_for_compare = 1;
-                }
+                scan_interpolated(&ttb, &ttm, &grads, &mut plot);
             }
-        } else {
-            if y2 < y1 {
-                top = &self.p2;
-                middle = &self.p1;
-                bottom = &self.p0;
-                middle_for_compare = 1;
-                bottom_for_compare = 0;
+            ttb.step();
+            ttm.step();
+        }
+
+        for _ in 0..mtb.height {
+            if middle_is_left {
+                scan_interpolated(&mtb, &ttb, &grads, &mut plot);
             } else {
-                top = &self.p1;
-                if y0 < y2 {
-                    middle = &self.p0;
-                    bottom = &self.p2;
-                    middle_for_compare = 3;
-                    bottom_for_compare = 2;
-                } else {
-                    middle = &self.p2;
-                    bottom = &self.p0;
-                    middle_for_compare = 2;
-                    bottom_for_compare = 3;
-                }
+                scan_interpolated(&ttb, &mtb, &grads, &mut plot);
             }
+            ttb.step();
+            mtb.step();
         }
+    }
+
+    /// Fills the triangle with the context's draw color.
+    pub fn draw(&self, context: &Context) {
+        // draw_horz_line is inclusive of both ends.
+        self.scan(|x1, x2, y| context.draw_horz_line(x1, x2 - 1, y));
+    }
+
+    /// Scan converts the triangle passing each non-empty span to `span` as
+    /// (x_start, x_end, y) where x_end is exclusive. Winding doesn't
+    /// matter. The spans are those FXPolygon produces for the same points.
+    pub fn scan<F>(&self, mut span: F)
+    where
+        F: FnMut(i32, i32, i32),
+    {
+        let p = [self.p0, self.p1, self.p2];
+        if p.iter().any(|v| !v.x.is_finite() || !v.y.is_finite()) {
+            return; // Degenerate
+        }
+
+        // sort vertices in y
+        let mut order = [0usize, 1, 2];
+        order.sort_by(|a, b| p[*a].y.total_cmp(&p[*b].y));
+        let (top, middle, bottom) = (order[0], order[1], order[2]);
+
+        let mut ttb = self.top_to_bottom.borrow_mut();
+        let mut ttm = self.top_to_middle.borrow_mut();
+        let mut mtb = self.middle_to_bottom.borrow_mut();
+
+        ttb.set(&p[top], &p[bottom]);
+        ttm.set(&p[top], &p[middle]);
+        mtb.set(&p[middle], &p[bottom]);
+
+        let cross = (p[bottom].x - p[top].x) * (p[middle].y - p[top].y)
+            - (p[bottom].y - p[top].y) * (p[middle].x - p[top].x);
+        let middle_is_left = cross > 0.0;
 
-        self.top_to_bottom.borrow_mut().set(top, bottom);
-        self.top_to_middle.borrow_mut().set(top, middle);
-        self.middle_to_bottom.borrow_mut().set(middle, bottom);
-
-        let middle_is_left: bool;
-        let mut height = self.top_to_middle.borrow().height;
-
-        // the triangle is clockwise, so if bottom > middle then middle is right
-        // the triangle is counter-clockwise, so if bottom > middle then middle is left
-        if bottom_for_compare > middle_for_compare {
-            middle_is_left = false;
-            for _ in 0..height {
-                if self.clockwise {
-                    let ttb = self.top_to_bottom.borrow();
-                    context.draw_horz_line(
-                        ttb.x as i32,
-                        self.top_to_middle.borrow().x as i32,
-                        ttb.y as i32,
-                    );
-                } else {
-                    let ttm = self.top_to_middle.borrow();
-                    context.draw_horz_line(
-                        ttm.x as i32,
-                        self.top_to_bottom.borrow().x as i32,
-                        ttm.y as i32,
-                    );
-                }
-
-                self.top_to_bottom.borrow_mut().step();
-                self.top_to_middle.borrow_mut().step();
+        let mut emit = |left: &FXEdge, right: &FXEdge| {
+            if left.x < right.x {
+                span(left.x as i32, right.x as i32, left.y as i32);
             }
-        } else {
-            middle_is_left = true;
-            for _ in 0..height {
-                if self.clockwise {
-                    let ttm = self.top_to_middle.borrow();
-                    context.draw_horz_line(
-                        ttm.x as i32,
-                        self.top_to_bottom.borrow().x as i32,
-                        ttm.y as i32,
-                    );
-                } else {
-                    let ttb = self.top_to_bottom.borrow();
-                    context.draw_horz_line(
-                        ttb.x as i32,
-                        self.top_to_middle.borrow().x as i32,
-                        ttb.y as i32,
-                    );
-                }
-
-                self.top_to_bottom.borrow_mut().step();
-                self.top_to_middle.borrow_mut().step();
+        };
+
+        for _ in 0..ttm.height {
+            if middle_is_left {
+                emit(&ttm, &ttb);
+            } else {
+                emit(&ttb, &ttm);
             }
+            ttb.step();
+            ttm.step();
         }
 
-        height = self.middle_to_bottom.borrow().height;
-
-        if middle_is_left {
-            for _ in 0..height {
-                if self.clockwise {
-                    let mtb = self.middle_to_bottom.borrow();
-                    context.draw_horz_line(
-                        mtb.x as i32,
-                        self.top_to_bottom.borrow().x as i32,
-                        mtb.y as i32,
-                    );
-                } else {
-                    let ttb = self.top_to_bottom.borrow();
-                    context.draw_horz_line(
-                        ttb.x as i32,
-                        self.middle_to_bottom.borrow().x as i32,
-                        ttb.y as i32,
-                    );
-                }
-
-                self.middle_to_bottom.borrow_mut().step();
-                self.top_to_bottom.borrow_mut().step();
-            }
-        } else {
-            for _ in 0..height {
-                if self.clockwise {
-                    let ttb = self.top_to_bottom.borrow();
-                    context.draw_horz_line(
-                        ttb.x as i32,
-                        self.middle_to_bottom.borrow().x as i32,
-                        ttb.y as i32,
-                    );
-                } else {
-                    let mtb = self.middle_to_bottom.borrow();
-                    context.draw_horz_line(
-                        mtb.x as i32,
-                        self.top_to_bottom.borrow().x as i32,
-                        mtb.y as i32,
-                    );
-                }
-
-                self.top_to_bottom.borrow_mut().step();
-                self.middle_to_bottom.borrow_mut().step();
+        for _ in 0..mtb.height {
+            if middle_is_left {
+                emit(&mtb, &ttb);
+            } else {
+                emit(&ttb, &mtb);
             }
+            ttb.step();
+            mtb.step();
         }
     }
-
-    // #[inline]
-    // fn draw_scanline(&self, l_x: i64, r_x: i64, l_y: i64, context: &mut Context) {
-    //     context.draw_horz_line(l_x, r_x, l_y);
-    //     // let mut x_start = l_x;
-    //     // let width = r_x - x_start;
-    //     // for _ in 0..width {
-    //     //     context.set_pixel(x_start, l_y);
-    //     //     x_start += 1; // move to next pixel
-    //     // }
-    // }
 }
 
-/// Find orientation of ordered triplet (p1, p2, p3).
-/// The function returns the following values
-///
-/// # returns
-///
-/// * 0 --> Colinear
-/// * 1 --> Counterclockwise
-/// * 2 --> Clockwise
-fn orientation(p0: &Point, p1: &Point, p2: &Point) -> u32 {
-    // Cross product
-    let val = (p1.y - p0.y) * (p2.x - p1.x) - (p1.x - p0.x) * (p2.y - p1.y);
-
-    if val == 0.0 {
-        // TODO should use epsilon comparison.
-        return 0; // colinear
+// Draws the span [left.x, right.x) on the current scanline.
+#[inline]
+fn scan_interpolated<F>(left: &FXEdge, right: &FXEdge, grads: &FXGradients, plot: &mut F)
+where
+    F: FnMut(i32, i32, &Attributes),
+{
+    // Sample at pixel centers rather than pixel corners.
+    let mut values = left.values;
+    for (value, (dx, dy)) in values.iter_mut().zip(grads.d_dx.iter().zip(&grads.d_dy)) {
+        *value += 0.5 * (dx + dy);
     }
 
-    // clock or counterclock wise
-    if val > 0.0 {
-        2
-    } else {
-        1
+    for x in left.x..right.x {
+        plot(x as i32, left.y as i32, &values);
+        for (value, dx) in values.iter_mut().zip(&grads.d_dx) {
+            *value += dx;
+        }
     }
 }
+
+#[inline]
+fn color_attributes(c: &Palette) -> Attributes {
+    [c.r as f64, c.g as f64, c.b as f64, c.a as f64]
+}
+
+#[inline]
+fn to_component(value: f64) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
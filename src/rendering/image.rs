@@ -1,5 +1,8 @@
 use std::ops::{Index, IndexMut};
 
+use rendering::blend::BlendMode;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct ImgPoint {
     // components are usize because there shouldn't be negative
     // indexs into an Image
@@ -34,6 +37,7 @@ impl ImgPoint {
 // ######################################################
 // Rectangle
 // ######################################################
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct ImgRectangle {
     pub min: ImgPoint,
     pub max: ImgPoint,
@@ -54,6 +58,13 @@ impl ImgRectangle {
         }
     }
 
+    pub fn from_xywh(x: usize, y: usize, w: usize, h: usize) -> Self {
+        Self {
+            min: ImgPoint { x, y },
+            max: ImgPoint { x: x + w, y: y + h },
+        }
+    }
+
     pub fn size(&self) -> usize {
         self.w() + self.h()
     }
@@ -78,12 +89,30 @@ impl ImgRectangle {
     pub fn xy_inside(&self, x: usize, y: usize) -> bool {
         self.min.x <= x && x < self.max.x && self.min.y <= y && y < self.max.y
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x >= self.max.x || self.min.y >= self.max.y
+    }
+
+    /// The overlap of both rectangles. If they don't overlap the result is
+    /// empty, with min == max.
+    pub fn intersect(&self, other: &ImgRectangle) -> ImgRectangle {
+        let min = ImgPoint::from_xy(self.min.x.max(other.min.x), self.min.y.max(other.min.y));
+        let max = ImgPoint::from_xy(self.max.x.min(other.max.x), self.max.y.min(other.max.y));
+
+        if min.x >= max.x || min.y >= max.y {
+            ImgRectangle { min, max: min }
+        } else {
+            ImgRectangle { min, max }
+        }
+    }
 }
 
 // R,G,B,A = 4 bytes
 const BYTES_PER_PIXEL: usize = 4;
 
 // RGBA is an in-memory image whose `at` method returns RGBA values.
+#[derive(Clone)]
 pub struct RGBA {
     /// Pix holds the image's pixels, in R, G, B, A order.
     /// The pixel at (x, y) starts at
@@ -155,6 +184,14 @@ impl RGBA {
         }
     }
 
+    /// Blends `color` onto the pixel at (x, y) using `mode`.
+    pub fn blend(&mut self, x: usize, y: usize, color: [u8; 4], mode: BlendMode) {
+        if self.rect.xy_inside(x, y) {
+            let dst = self.at(x, y);
+            self.set(x, y, mode.blend(color, dst));
+        }
+    }
+
     pub fn set_components(&mut self, x: usize, y: usize, r: u8, g: u8, b: u8, a: u8) {
         if self.rect.xy_inside(x, y) {
             let i = self.pix_offset(x, y) as usize;
@@ -166,6 +203,379 @@ impl RGBA {
     }
 }
 
+// #######################################################
+// Image sources
+// #######################################################
+/// Anything pixels can be read from: an RGBA or a view into one.
+pub trait ImageSource {
+    fn bounds(&self) -> &ImgRectangle;
+    /// Returns transparent black outside of the bounds.
+    fn at(&self, x: usize, y: usize) -> [u8; 4];
+}
+
+impl ImageSource for RGBA {
+    fn bounds(&self) -> &ImgRectangle {
+        &self.rect
+    }
+
+    fn at(&self, x: usize, y: usize) -> [u8; 4] {
+        RGBA::at(self, x, y)
+    }
+}
+
+// #######################################################
+// Sub-images
+// A sub-image shares its parent's pixels. Like the parent its coordinates
+// start at rect.min, so a pixel has the same coordinates in both.
+// #######################################################
+pub struct SubImage<'a> {
+    pix: &'a [u8],
+    stride: usize,
+    rect: ImgRectangle,
+}
+
+impl SubImage<'_> {
+    #[inline(always)]
+    fn pix_offset(&self, x: usize, y: usize) -> usize {
+        (y - self.rect.min.y) * self.stride + (x - self.rect.min.x) * BYTES_PER_PIXEL
+    }
+
+    /// Copies the pixels into a new image whose bounds start at (0, 0).
+    pub fn to_image(&self) -> RGBA {
+        let w = self.rect.w();
+        let mut img = RGBA::new(w, self.rect.h());
+        for row in 0..self.rect.h() {
+            let i = self.pix_offset(self.rect.min.x, self.rect.min.y + row);
+            let o = row * img.stride;
+            img.pix[o..o + w * BYTES_PER_PIXEL]
+                .copy_from_slice(&self.pix[i..i + w * BYTES_PER_PIXEL]);
+        }
+        img
+    }
+}
+
+impl ImageSource for SubImage<'_> {
+    fn bounds(&self) -> &ImgRectangle {
+        &self.rect
+    }
+
+    fn at(&self, x: usize, y: usize) -> [u8; 4] {
+        if !self.rect.xy_inside(x, y) {
+            return [0; 4];
+        }
+        let i = self.pix_offset(x, y);
+        [
+            self.pix[i],
+            self.pix[i + 1],
+            self.pix[i + 2],
+            self.pix[i + 3],
+        ]
+    }
+}
+
+pub struct SubImageMut<'a> {
+    pix: &'a mut [u8],
+    stride: usize,
+    rect: ImgRectangle,
+}
+
+impl SubImageMut<'_> {
+    #[inline(always)]
+    fn pix_offset(&self, x: usize, y: usize) -> usize {
+        (y - self.rect.min.y) * self.stride + (x - self.rect.min.x) * BYTES_PER_PIXEL
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: [u8; 4]) {
+        if self.rect.xy_inside(x, y) {
+            let i = self.pix_offset(x, y);
+            self.pix[i..i + BYTES_PER_PIXEL].copy_from_slice(&color);
+        }
+    }
+
+    pub fn fill(&mut self, color: [u8; 4]) {
+        for y in self.rect.min.y..self.rect.max.y {
+            for x in self.rect.min.x..self.rect.max.x {
+                let i = self.pix_offset(x, y);
+                self.pix[i..i + BYTES_PER_PIXEL].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+impl ImageSource for SubImageMut<'_> {
+    fn bounds(&self) -> &ImgRectangle {
+        &self.rect
+    }
+
+    fn at(&self, x: usize, y: usize) -> [u8; 4] {
+        if !self.rect.xy_inside(x, y) {
+            return [0; 4];
+        }
+        let i = self.pix_offset(x, y);
+        [
+            self.pix[i],
+            self.pix[i + 1],
+            self.pix[i + 2],
+            self.pix[i + 3],
+        ]
+    }
+}
+
+// #######################################################
+// Manipulation
+// #######################################################
+impl RGBA {
+    /// A view of the part of the image within `r`, clipped to the image.
+    pub fn sub_image(&self, r: &ImgRectangle) -> SubImage<'_> {
+        let rect = r.intersect(&self.rect);
+        let start = if rect.is_empty() {
+            self.pix.len()
+        } else {
+            self.pix_offset(rect.min.x, rect.min.y)
+        };
+
+        SubImage {
+            pix: &self.pix[start..],
+            stride: self.stride,
+            rect,
+        }
+    }
+
+    /// A mutable view of the part of the image within `r`, clipped to the
+    /// image.
+    pub fn sub_image_mut(&mut self, r: &ImgRectangle) -> SubImageMut<'_> {
+        let rect = r.intersect(&self.rect);
+        let start = if rect.is_empty() {
+            self.pix.len()
+        } else {
+            self.pix_offset(rect.min.x, rect.min.y)
+        };
+
+        SubImageMut {
+            pix: &mut self.pix[start..],
+            stride: self.stride,
+            rect,
+        }
+    }
+
+    /// Copies the part of the image within `r` into a new image whose
+    /// bounds start at (0, 0).
+    pub fn crop(&self, r: &ImgRectangle) -> RGBA {
+        self.sub_image(r).to_image()
+    }
+
+    /// Draws `src` with its top-left corner at (x, y) using `mode`.
+    /// BlendMode::None copies the pixels as is. Anything outside of this
+    /// image is clipped.
+    pub fn blit<S: ImageSource>(&mut self, src: &S, x: usize, y: usize, mode: BlendMode) {
+        let sr = *src.bounds();
+        let dst = ImgRectangle::from_xywh(x, y, sr.w(), sr.h()).intersect(&self.rect);
+
+        for dy in dst.min.y..dst.max.y {
+            for dx in dst.min.x..dst.max.x {
+                let color = src.at(sr.min.x + dx - x, sr.min.y + dy - y);
+                let i = self.pix_offset(dx, dy);
+                let out = match mode {
+                    BlendMode::None => color,
+                    _ => {
+                        let under = [
+                            self.pix[i],
+                            self.pix[i + 1],
+                            self.pix[i + 2],
+                            self.pix[i + 3],
+                        ];
+                        mode.blend(color, under)
+                    }
+                };
+                self.pix[i..i + BYTES_PER_PIXEL].copy_from_slice(&out);
+            }
+        }
+    }
+
+    pub fn fill(&mut self, color: [u8; 4]) {
+        let rect = self.rect;
+        self.fill_rect(&rect, color);
+    }
+
+    pub fn fill_rect(&mut self, r: &ImgRectangle, color: [u8; 4]) {
+        self.sub_image_mut(r).fill(color);
+    }
+
+    /// Replaces every pixel that is exactly `from` with `to`. Returns how
+    /// many were replaced.
+    pub fn replace_color(&mut self, from: [u8; 4], to: [u8; 4]) -> usize {
+        let mut count = 0;
+        for y in self.rect.min.y..self.rect.max.y {
+            for x in self.rect.min.x..self.rect.max.x {
+                let i = self.pix_offset(x, y);
+                if self.pix[i..i + BYTES_PER_PIXEL] == from {
+                    self.pix[i..i + BYTES_PER_PIXEL].copy_from_slice(&to);
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Mirrors left to right.
+    pub fn flip_horizontal(&mut self) {
+        let (min, max) = (self.rect.min, self.rect.max);
+        for y in min.y..max.y {
+            let (mut left, mut right) = (min.x, max.x);
+            while left + 1 < right {
+                right -= 1;
+                let a = self.pix_offset(left, y);
+                let b = self.pix_offset(right, y);
+                for c in 0..BYTES_PER_PIXEL {
+                    self.pix.swap(a + c, b + c);
+                }
+                left += 1;
+            }
+        }
+    }
+
+    /// Mirrors top to bottom.
+    pub fn flip_vertical(&mut self) {
+        let w = self.rect.w() * BYTES_PER_PIXEL;
+        let (mut top, mut bottom) = (self.rect.min.y, self.rect.max.y);
+        while top + 1 < bottom {
+            bottom -= 1;
+            let a = self.pix_offset(self.rect.min.x, top);
+            let b = self.pix_offset(self.rect.min.x, bottom);
+            let (upper, lower) = self.pix.split_at_mut(b);
+            upper[a..a + w].swap_with_slice(&mut lower[..w]);
+            top += 1;
+        }
+    }
+
+    pub fn rotate_180(&mut self) {
+        self.flip_horizontal();
+        self.flip_vertical();
+    }
+
+    /// Returns the image rotated a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> RGBA {
+        let (w, h) = (self.rect.w(), self.rect.h());
+        let mut out = RGBA::new(h, w);
+        for y in 0..h {
+            for x in 0..w {
+                let color = self.at(self.rect.min.x + x, self.rect.min.y + y);
+                out.set(h - 1 - y, x, color);
+            }
+        }
+        out
+    }
+
+    /// Returns the image rotated a quarter turn counter-clockwise.
+    pub fn rotate_ccw(&self) -> RGBA {
+        let (w, h) = (self.rect.w(), self.rect.h());
+        let mut out = RGBA::new(h, w);
+        for y in 0..h {
+            for x in 0..w {
+                let color = self.at(self.rect.min.x + x, self.rect.min.y + y);
+                out.set(y, w - 1 - x, color);
+            }
+        }
+        out
+    }
+
+    /// Resizes by picking the nearest pixel. Keeps hard edges, e.g. for
+    /// pixel art.
+    pub fn resize_nearest(&self, width: usize, height: usize) -> RGBA {
+        let mut out = RGBA::new(width, height);
+        let (w, h) = (self.rect.w(), self.rect.h());
+        if w == 0 || h == 0 {
+            return out;
+        }
+
+        for y in 0..height {
+            let sy = ((y * 2 + 1) * h / (height * 2)).min(h - 1);
+            for x in 0..width {
+                let sx = ((x * 2 + 1) * w / (width * 2)).min(w - 1);
+                out.set(x, y, self.at(self.rect.min.x + sx, self.rect.min.y + sy));
+            }
+        }
+        out
+    }
+
+    /// Resizes by blending the four nearest pixels. Colors are weighted by
+    /// alpha so transparent pixels don't darken edges.
+    pub fn resize_bilinear(&self, width: usize, height: usize) -> RGBA {
+        let mut out = RGBA::new(width, height);
+        let (w, h) = (self.rect.w(), self.rect.h());
+        if w == 0 || h == 0 {
+            return out;
+        }
+
+        let sample = |x: usize, y: usize| self.at(self.rect.min.x + x, self.rect.min.y + y);
+
+        for y in 0..height {
+            // Align pixel centers
+            let fy = ((y as f64 + 0.5) * h as f64 / height as f64 - 0.5).max(0.0);
+            let y0 = (fy as usize).min(h - 1);
+            let y1 = (y0 + 1).min(h - 1);
+            let ty = fy - y0 as f64;
+
+            for x in 0..width {
+                let fx = ((x as f64 + 0.5) * w as f64 / width as f64 - 0.5).max(0.0);
+                let x0 = (fx as usize).min(w - 1);
+                let x1 = (x0 + 1).min(w - 1);
+                let tx = fx - x0 as f64;
+
+                let taps = [
+                    (sample(x0, y0), (1.0 - tx) * (1.0 - ty)),
+                    (sample(x1, y0), tx * (1.0 - ty)),
+                    (sample(x0, y1), (1.0 - tx) * ty),
+                    (sample(x1, y1), tx * ty),
+                ];
+
+                let mut color = [0.0; 3];
+                let mut alpha = 0.0;
+                for &(p, weight) in taps.iter() {
+                    let a = p[3] as f64 * weight;
+                    alpha += a;
+                    for (c, v) in color.iter_mut().enumerate() {
+                        *v += p[c] as f64 * a;
+                    }
+                }
+
+                let mut px = [0u8; 4];
+                if alpha > 0.0 {
+                    for (c, v) in color.iter().enumerate() {
+                        px[c] = (v / alpha).round().clamp(0.0, 255.0) as u8;
+                    }
+                }
+                px[3] = alpha.round().clamp(0.0, 255.0) as u8;
+                out.set(x, y, px);
+            }
+        }
+        out
+    }
+
+    /// Multiplies the color components by alpha.
+    pub fn premultiply(&mut self) {
+        for px in self.pix.chunks_mut(BYTES_PER_PIXEL) {
+            let a = px[3] as u32;
+            for c in px.iter_mut().take(3) {
+                *c = ((*c as u32 * a + 127) / 255) as u8;
+            }
+        }
+    }
+
+    /// Divides the color components by alpha, the inverse of premultiply.
+    /// Fully transparent pixels become transparent black.
+    pub fn unpremultiply(&mut self) {
+        for px in self.pix.chunks_mut(BYTES_PER_PIXEL) {
+            let a = px[3] as u32;
+            for c in px.iter_mut().take(3) {
+                *c = (*c as u32 * 255 + a / 2)
+                    .checked_div(a)
+                    .map_or(0, |v| v.min(255) as u8);
+            }
+        }
+    }
+}
+
 // #######################################################
 // Raw color component access
 // #######################################################
@@ -1,11 +1,24 @@
+pub mod atlas;
+pub mod blend;
+pub mod clear_mode;
+pub mod codecs;
 // pub mod bx_triangle;
 pub mod color;
+pub mod filters;
 // pub mod ex_triangle;
 mod fx_edge;
+mod fx_gradients;
+pub mod fx_polygon;
 pub mod fx_rectangle;
 pub mod fx_triangle;
 pub mod image;
 // pub mod nx_triangle;
+pub mod render_batch;
 pub mod render_context;
+pub mod render_targets;
+pub mod sprite_sheet;
+pub mod stroke;
 // pub mod sx_triangle;
+pub mod texture_sampler;
 pub mod vector_font;
+pub mod wu_line;
@@ -0,0 +1,382 @@
+extern crate sdl2;
+
+use std::collections::HashSet;
+
+use self::sdl2::{
+    pixels::Color,
+    rect::{Point, Rect},
+    render::{BlendMode as SdlBlendMode, WindowCanvas},
+};
+
+use rendering::blend::{fade_to_white, BlendMode};
+use rendering::color::Palette;
+
+// Context doesn't draw immediately, it records primitives here and the
+// batch is flushed to the backend at the end of the frame (or earlier if
+// something that bypasses the batch, like a texture copy, needs to draw).
+//
+// Consecutive primitives of the same kind and state are merged into one
+// batch which is submitted with a single bulk call. Submission order is
+// preserved so overlapping primitives composite as if drawn immediately.
+//
+// One pixel wide horizontal and vertical lines, which is what the
+// rasterizers produce, are recorded as rectangles so they batch with
+// other spans.
+//
+// Anti-aliasing and shading produce points that each have their own
+// color. Runs of them are kept in one batch and grouped by color when
+// flushed, rather than starting a batch per point.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Primitive {
+    Points,
+    /// Points that each have their own color.
+    ColoredPoints,
+    Lines,
+    FilledRects,
+    OutlinedRects,
+}
+
+/// The render state a primitive is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchState {
+    pub color: Palette,
+    pub blend: BlendMode,
+}
+
+/// Per-frame counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DrawStats {
+    /// Calls that draw, bulk or not.
+    pub draw_calls: usize,
+    /// Color and/or blend mode changes sent to the backend.
+    pub state_changes: usize,
+    pub batches: usize,
+    /// Points, lines and rectangles recorded.
+    pub primitives: usize,
+}
+
+/// Where batches are flushed to, normally the SDL canvas.
+pub trait BatchTarget {
+    fn apply_state(&mut self, state: &BatchState);
+    fn points(&mut self, points: &[Point]);
+    /// Draws a connected polyline.
+    fn polyline(&mut self, points: &[Point]);
+    fn filled_rects(&mut self, rects: &[Rect]);
+    fn outlined_rects(&mut self, rects: &[Rect]);
+}
+
+struct Batch {
+    primitive: Primitive,
+    state: BatchState,
+    // Range into the batch's point or rect buffer.
+    start: usize,
+    end: usize,
+}
+
+pub struct RenderBatch {
+    state: BatchState,
+    batches: Vec<Batch>,
+
+    // Points, and lines as pairs of points
+    points: Vec<Point>,
+    rects: Vec<Rect>,
+    colored: Vec<(Palette, Point)>,
+
+    // The pixels of the last batch of colored points. Grouping reorders
+    // them, so a pixel that is already in the run starts a new batch.
+    run: HashSet<(i32, i32)>,
+
+    // The state last applied to the target. It persists across flushes so
+    // the state isn't resent needlessly.
+    applied: Option<BatchState>,
+
+    // Scratch points used while flushing lines and colored points.
+    polyline: Vec<Point>,
+
+    stats: DrawStats,
+}
+
+impl RenderBatch {
+    pub fn new() -> Self {
+        Self {
+            state: BatchState {
+                color: Palette::BLACK(),
+                blend: BlendMode::Alpha,
+            },
+            batches: Vec::new(),
+            points: Vec::new(),
+            rects: Vec::new(),
+            colored: Vec::new(),
+            run: HashSet::new(),
+            applied: None,
+            polyline: Vec::new(),
+            stats: DrawStats::default(),
+        }
+    }
+
+    /// Subsequent primitives are drawn with this state.
+    pub fn set_state(&mut self, color: Palette, blend: BlendMode) {
+        self.state = BatchState { color, blend };
+    }
+
+    pub fn state(&self) -> &BatchState {
+        &self.state
+    }
+
+    pub fn set_color(&mut self, color: Palette) {
+        self.state.color = color;
+    }
+
+    pub fn set_blend(&mut self, blend: BlendMode) {
+        self.state.blend = blend;
+    }
+
+    /// Forgets the state last applied to the target, for example, because
+    /// something else changed it.
+    pub fn invalidate(&mut self) {
+        self.applied = None;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+
+    /// The number of pending batches.
+    pub fn len(&self) -> usize {
+        self.batches.len()
+    }
+
+    pub fn point(&mut self, x: i32, y: i32) {
+        let start = self.points.len();
+        self.points.push(Point::new(x, y));
+        self.record(Primitive::Points, start, self.points.len());
+    }
+
+    /// A point drawn with `color` and the current blend mode.
+    pub fn colored_point(&mut self, x: i32, y: i32, color: Palette) {
+        self.stats.primitives += 1;
+        let start = self.colored.len();
+        self.colored.push((color, Point::new(x, y)));
+
+        if let Some(last) = self.batches.last_mut() {
+            if last.primitive == Primitive::ColoredPoints
+                && last.state.blend == self.state.blend
+                && last.end == start
+                && self.run.insert((x, y))
+            {
+                last.end = start + 1;
+                return;
+            }
+        }
+
+        self.run.clear();
+        self.run.insert((x, y));
+        self.batches.push(Batch {
+            primitive: Primitive::ColoredPoints,
+            state: BatchState {
+                color,
+                blend: self.state.blend,
+            },
+            start,
+            end: start + 1,
+        });
+    }
+
+    /// A line that includes both end points.
+    pub fn line(&mut self, x1: i32, y1: i32, x2: i32, y2: i32) {
+        if y1 == y2 {
+            self.fill_rect(x1.min(x2), y1, (x1 - x2).unsigned_abs() + 1, 1);
+        } else if x1 == x2 {
+            self.fill_rect(x1, y1.min(y2), 1, (y1 - y2).unsigned_abs() + 1);
+        } else {
+            let start = self.points.len();
+            self.points.push(Point::new(x1, y1));
+            self.points.push(Point::new(x2, y2));
+            self.record(Primitive::Lines, start, self.points.len());
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: u32, h: u32) {
+        let start = self.rects.len();
+        self.rects.push(Rect::new(x, y, w, h));
+        self.record(Primitive::FilledRects, start, self.rects.len());
+    }
+
+    pub fn draw_rect(&mut self, x: i32, y: i32, w: u32, h: u32) {
+        let start = self.rects.len();
+        self.rects.push(Rect::new(x, y, w, h));
+        self.record(Primitive::OutlinedRects, start, self.rects.len());
+    }
+
+    // Extends the last batch if it is compatible, otherwise starts a new
+    // one. The new item is at [start, end) in its buffer.
+    fn record(&mut self, primitive: Primitive, start: usize, end: usize) {
+        self.stats.primitives += 1;
+
+        if let Some(last) = self.batches.last_mut() {
+            if last.primitive == primitive && last.state == self.state && last.end == start {
+                last.end = end;
+                return;
+            }
+        }
+
+        self.batches.push(Batch {
+            primitive,
+            state: self.state,
+            start,
+            end,
+        });
+    }
+
+    /// Submits all pending batches to `target`.
+    pub fn flush(&mut self, target: &mut dyn BatchTarget) {
+        for batch in self.batches.iter() {
+            if batch.primitive != Primitive::ColoredPoints {
+                apply_state(target, &mut self.applied, &mut self.stats, batch.state);
+            }
+
+            self.stats.batches += 1;
+
+            match batch.primitive {
+                Primitive::Points => {
+                    target.points(&self.points[batch.start..batch.end]);
+                    self.stats.draw_calls += 1;
+                }
+                Primitive::ColoredPoints => {
+                    // None of the points overlap so they can be drawn in
+                    // any order, one call per color.
+                    let run = &mut self.colored[batch.start..batch.end];
+                    run.sort_by_key(|&(c, _)| (c.r, c.g, c.b, c.a));
+                    for group in run.chunk_by(|a, b| a.0 == b.0) {
+                        let state = BatchState {
+                            color: group[0].0,
+                            blend: batch.state.blend,
+                        };
+                        apply_state(target, &mut self.applied, &mut self.stats, state);
+
+                        self.polyline.clear();
+                        self.polyline.extend(group.iter().map(|&(_, p)| p));
+                        target.points(&self.polyline);
+                        self.stats.draw_calls += 1;
+                    }
+                }
+                Primitive::FilledRects => {
+                    target.filled_rects(&self.rects[batch.start..batch.end]);
+                    self.stats.draw_calls += 1;
+                }
+                Primitive::OutlinedRects => {
+                    target.outlined_rects(&self.rects[batch.start..batch.end]);
+                    self.stats.draw_calls += 1;
+                }
+                Primitive::Lines => {
+                    // SDL can only draw connected lines in bulk so chain
+                    // segments that share end points.
+                    self.polyline.clear();
+                    for pair in self.points[batch.start..batch.end].chunks(2) {
+                        let connected = match self.polyline.last() {
+                            Some(last) => *last == pair[0],
+                            None => false,
+                        };
+                        if !connected && !self.polyline.is_empty() {
+                            target.polyline(&self.polyline);
+                            self.stats.draw_calls += 1;
+                            self.polyline.clear();
+                        }
+                        if !connected {
+                            self.polyline.push(pair[0]);
+                        }
+                        self.polyline.push(pair[1]);
+                    }
+                    if !self.polyline.is_empty() {
+                        target.polyline(&self.polyline);
+                        self.stats.draw_calls += 1;
+                    }
+                }
+            }
+        }
+
+        self.batches.clear();
+        self.points.clear();
+        self.rects.clear();
+        self.colored.clear();
+        self.run.clear();
+    }
+
+    /// Returns the counters accumulated since the last call and resets
+    /// them. Called once per frame.
+    pub fn take_stats(&mut self) -> DrawStats {
+        std::mem::take(&mut self.stats)
+    }
+
+    pub fn stats(&self) -> &DrawStats {
+        &self.stats
+    }
+}
+
+// Sends `state` to the target unless it is already applied.
+fn apply_state(
+    target: &mut dyn BatchTarget,
+    applied: &mut Option<BatchState>,
+    stats: &mut DrawStats,
+    state: BatchState,
+) {
+    if *applied != Some(state) {
+        target.apply_state(&state);
+        *applied = Some(state);
+        stats.state_changes += 1;
+    }
+}
+
+impl BatchTarget for WindowCanvas {
+    fn apply_state(&mut self, state: &BatchState) {
+        let [r, g, b, a] = to_sdl_color(state);
+        self.set_draw_color(Color::RGBA(r, g, b, a));
+        self.set_blend_mode(to_sdl_blend_mode(state.blend));
+    }
+
+    fn points(&mut self, points: &[Point]) {
+        if let Err(err) = self.draw_points(points) {
+            dbg!(err);
+        }
+    }
+
+    fn polyline(&mut self, points: &[Point]) {
+        if let Err(err) = self.draw_lines(points) {
+            dbg!(err);
+        }
+    }
+
+    fn filled_rects(&mut self, rects: &[Rect]) {
+        if let Err(err) = self.fill_rects(rects) {
+            dbg!(err);
+        }
+    }
+
+    fn outlined_rects(&mut self, rects: &[Rect]) {
+        if let Err(err) = self.draw_rects(rects) {
+            dbg!(err);
+        }
+    }
+}
+
+/// The draw color to give SDL for `state`. SDL's multiply (Mod) ignores the
+/// source alpha, so for Multiply the color is faded towards white by its
+/// alpha first, as the software path does.
+pub fn to_sdl_color(state: &BatchState) -> [u8; 4] {
+    let c = state.color;
+    let rgba = [c.r, c.g, c.b, c.a];
+    match state.blend {
+        BlendMode::Multiply => fade_to_white(rgba),
+        _ => rgba,
+    }
+}
+
+pub fn to_sdl_blend_mode(mode: BlendMode) -> SdlBlendMode {
+    match mode {
+        BlendMode::None => SdlBlendMode::None,
+        BlendMode::Alpha => SdlBlendMode::Blend,
+        BlendMode::Additive | BlendMode::Screen => SdlBlendMode::Add,
+        BlendMode::Multiply => SdlBlendMode::Mod,
+    }
+}
@@ -1,30 +1,74 @@
 extern crate font8x8;
 extern crate sdl2;
 
-use std::cell::RefCell;
-// use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use std::f64::consts::PI;
+use std::rc::Rc;
 
 use self::font8x8::{UnicodeFonts, BASIC_FONTS};
 
 use self::sdl2::{
-    pixels::Color,
+    pixels::{Color, PixelFormatEnum},
     rect::{Point, Rect},
-    render::BlendMode,
+    render::{BlendMode as SdlBlendMode, Texture},
 };
 
-use geometry::{aabb::AABBox, point::Point as RPoint};
+use geometry::{
+    aabb::AABBox,
+    clipping::{Clipping, PolygonClipper, INSIDE},
+    point::Point as RPoint,
+    rectangle::Rectangle,
+    shapes::Shapes,
+};
 use math::affine_transform::AffineTransform;
+use math::curve::Curve;
 // use rendering::color::Color as RColor;
 // use rendering::bx_triangle::BXTriangle;
+use rendering::blend::BlendMode;
+use rendering::clear_mode::ClearMode;
 use rendering::color::Palette;
+use rendering::filters::FilterChain;
 // use rendering::ex_triangle::EXTriangle;
+use rendering::fx_polygon::{FXPolygon, FillRule};
 use rendering::fx_triangle::FXTriangle;
+use rendering::image::{ImgRectangle, RGBA};
+use rendering::render_batch::{to_sdl_blend_mode, DrawStats, RenderBatch};
+use rendering::render_targets::RenderTargets;
+use rendering::stroke::{LineStyle, StrokeStyle, Stroker};
+use rendering::texture_sampler::TextureSampler;
+use rendering::wu_line::wu_line;
 // use rendering::nx_triangle::NXTriangle;
 // use rendering::sx_triangle::SXTriangle;
 use world::{RCCanvas, WorldProperties};
 
 const STATE_STACK_DEPTH: usize = 100;
 
+// The maximum distance, in pixels, between a flattened curve and the
+// true curve.
+const CURVE_TOLERANCE: f64 = 0.25;
+
+// Anti-aliasing coverage is rounded to this many levels so the points of
+// a line share a few colors.
+const COVERAGE_LEVELS: f64 = 16.0;
+
+/// A handle to a texture uploaded via Context::upload_texture. Valid
+/// until the texture is freed, after which the id may be reused.
+pub type TextureId = usize;
+
+/// Frees textures from where there is no Context, e.g. Drop impls. The
+/// textures are freed once the current frame has been presented.
+#[derive(Clone)]
+pub struct TextureReleaser {
+    released: Rc<RefCell<Vec<TextureId>>>,
+}
+
+impl TextureReleaser {
+    pub fn release(&self, texture: TextureId) {
+        self.released.borrow_mut().push(texture);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RenderStyle {
     FILLED,
     OUTLINE,
@@ -33,8 +77,9 @@ pub enum RenderStyle {
 
 #[derive(Copy, Clone, Debug)]
 struct State {
-    clear_color: Color,
     draw_color: Color,
+    blend_mode: BlendMode,
+    opacity: f64,
     current: AffineTransform,
     filtered: AffineTransform,
 }
@@ -46,9 +91,13 @@ pub struct Context {
     state: Vec<State>,
     stack_top: usize,
 
-    clear_color: Color,
     draw_color: Color,
 
+    blend_mode: BlendMode,
+    // Multiplies the alpha of everything drawn. Nodes multiply their own
+    // opacity into it as the tree is visited.
+    opacity: f64,
+
     // SDL canvas target.
     canvas: RCCanvas,
 
@@ -64,6 +113,39 @@ pub struct Context {
     // nx_rasterizer: RefCell<NXTriangle>,
     // ex_rasterizer: RefCell<EXTriangle>,
     // sx_rasterizer: RefCell<SXTriangle>,
+
+    // Polygon rasterizer
+    poly_rasterizer: RefCell<FXPolygon>,
+    stroker: RefCell<Stroker>,
+
+    // Device-space clipping
+    clipper: RefCell<PolygonClipper>,
+    clip_points: RefCell<Vec<RPoint>>,
+
+    // Shape and curve tessellation buffers: local-space and device-space.
+    shape_local: RefCell<Vec<RPoint>>,
+    shape_device: RefCell<Vec<RPoint>>,
+
+    // Primitives waiting to be submitted to the canvas.
+    batch: RefCell<RenderBatch>,
+    // The draw stats of the last presented frame.
+    frame_stats: Cell<DrawStats>,
+
+    // Uploaded textures. A TextureId is an index into this list, freed
+    // slots are None until reused. Textures aren't freed on drop (the
+    // sdl2 "unsafe_textures" feature) so they must be destroyed here.
+    textures: RefCell<Vec<Option<Texture>>>,
+    releaser: TextureReleaser,
+    // The texture of the current ClearMode::Image, uploaded on first use.
+    background: RefCell<Option<(Rc<RGBA>, TextureId)>>,
+
+    // Nested render targets. Empty when drawing to the window.
+    targets: RenderTargets,
+
+    // Post-processing: the frame read back and the texture it is written
+    // back through, recreated if the frame size changes.
+    frame: RefCell<RGBA>,
+    frame_texture: RefCell<Option<Texture>>,
 }
 
 // SceneManager creates the Context.
@@ -73,8 +155,9 @@ impl Context {
             world_properties: WorldProperties::new(),
             state: Vec::with_capacity(STATE_STACK_DEPTH),
             stack_top: 0,
-            clear_color: Color::RGB(32, 32, 32),
             draw_color: Color::RGB(0, 0, 0),
+            blend_mode: BlendMode::Alpha,
+            opacity: 1.0,
             canvas: canvas,
             current_aft: AffineTransform::new(),
             post: AffineTransform::new(),
@@ -84,16 +167,33 @@ impl Context {
             // nx_rasterizer: RefCell::new(NXTriangle::new()),
             // ex_rasterizer: RefCell::new(EXTriangle::new()),
             // sx_rasterizer: RefCell::new(SXTriangle::new()),
+            poly_rasterizer: RefCell::new(FXPolygon::new()),
+            stroker: RefCell::new(Stroker::new()),
+            clipper: RefCell::new(PolygonClipper::new()),
+            clip_points: RefCell::new(Vec::new()),
+            shape_local: RefCell::new(Vec::new()),
+            shape_device: RefCell::new(Vec::new()),
+            batch: RefCell::new(RenderBatch::new()),
+            frame_stats: Cell::new(DrawStats::default()),
+            textures: RefCell::new(Vec::new()),
+            releaser: TextureReleaser {
+                released: Rc::new(RefCell::new(Vec::new())),
+            },
+            background: RefCell::new(None),
+            targets: RenderTargets::new(),
+            frame: RefCell::new(RGBA::new(0, 0)),
+            frame_texture: RefCell::new(None),
         }
     }
 
     pub fn initialize(&mut self, wp: &WorldProperties) {
         self.world_properties.set(wp);
-        self.canvas.borrow_mut().set_blend_mode(BlendMode::Blend);
+        self.batch.borrow_mut().set_blend(self.blend_mode);
 
         let copy = State {
-            clear_color: Color::RGB(0, 0, 0),
             draw_color: Color::RGB(0, 0, 0),
+            blend_mode: BlendMode::Alpha,
+            opacity: 1.0,
             current: AffineTransform::new(),
             filtered: AffineTransform::new(),
         };
@@ -135,51 +235,169 @@ impl Context {
     // ----------------------------------------------------------
     // Color
     // ----------------------------------------------------------
+    /// Switches the clear mode to a solid color.
     pub fn set_clear_color(&mut self, color: Palette) {
-        self.clear_color = Color::RGB(color.r, color.g, color.b);
+        self.set_clear_mode(ClearMode::Solid(color));
     }
 
+    pub fn set_clear_mode(&mut self, mode: ClearMode) {
+        self.world_properties.clear_mode = mode;
+    }
+
+    pub fn clear_mode(&self) -> &ClearMode {
+        &self.world_properties.clear_mode
+    }
+
+    pub fn set_perform_clear(&mut self, perform: bool) {
+        self.world_properties.perform_clear = perform;
+    }
+
+    /// The color's alpha is multiplied by the current opacity.
     pub fn set_draw_color(&mut self, color: &Palette) {
-        self.draw_color = Color::RGBA(color.r, color.g, color.b, color.a);
-        self.canvas.borrow_mut().set_draw_color(self.draw_color);
+        self.draw_color = Color::RGBA(color.r, color.g, color.b, self.modulate(color.a));
+        self.batch
+            .borrow_mut()
+            .set_color(to_palette(self.draw_color));
+    }
+
+    // ----------------------------------------------------------
+    // Blending
+    // Both are part of the saved state.
+    // ----------------------------------------------------------
+    /// SDL has no native Screen mode, it falls back to Additive on the SDL
+    /// path. The software (RGBA) path supports all modes.
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+        self.batch.borrow_mut().set_blend(mode);
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    pub fn set_opacity(&mut self, opacity: f64) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    /// Multiplies `opacity` into the current opacity.
+    pub fn apply_opacity(&mut self, opacity: f64) {
+        self.set_opacity(self.opacity * opacity);
+    }
+
+    pub fn opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    // Scales an alpha value by the current opacity.
+    #[inline]
+    fn modulate(&self, alpha: u8) -> u8 {
+        (alpha as f64 * self.opacity).round() as u8
     }
 
-    /// Clears the background canvas
+    /// Paints the background according to the clear mode. Does nothing
+    /// if clearing is disabled. The current draw state is unaffected.
     pub fn clear(&self) {
-        // {
-        //     let mut can = self.canvas.borrow_mut();
-        //     can.set_draw_color(self.clear_color);
-        //     can.clear();
-        // }
+        if !self.world_properties.perform_clear {
+            return;
+        }
 
-        // Draw checkerboard as an clear indicator for debugging
         let w = self.world_properties.window_width as i32;
         let h = self.world_properties.window_height as i32;
-        let mut flip = false;
-        let size = 200i32;
-        let mut col = 0i32;
-        let mut row = 0i32;
-
-        while row < h {
-            while col < w {
-                if flip {
-                    self.canvas
-                        .borrow_mut()
-                        .set_draw_color(Color::RGB(100, 100, 100));
-                } else {
-                    self.canvas
-                        .borrow_mut()
-                        .set_draw_color(Color::RGB(80, 80, 80));
-                }
-                self.fill_rectangle(col, row, col + size, row + size);
-                flip = !flip;
 
-                col += size;
+        // The background replaces whatever was there, alpha included.
+        self.batch
+            .borrow_mut()
+            .set_state(Palette::BLACK(), BlendMode::None);
+
+        let mode = &self.world_properties.clear_mode;
+        match *mode {
+            ClearMode::Solid(color) => {
+                let mut batch = self.batch.borrow_mut();
+                batch.set_color(color);
+                batch.fill_rect(0, 0, w as u32, h as u32);
+            }
+            ClearMode::Checkerboard { size, .. } => {
+                let size = size.max(1) as i32;
+
+                // One pass per color so that each color is a single batch.
+                for parity in 0..2 {
+                    if let Some(color) = mode.color_at(parity * size, 0, h) {
+                        self.batch.borrow_mut().set_color(color);
+                    }
+
+                    let mut row = 0i32;
+                    while row < h {
+                        let mut col = 0i32;
+                        while col < w {
+                            if (row / size + col / size) % 2 == parity {
+                                self.fill_rectangle(col, row, col + size, row + size);
+                            }
+                            col += size;
+                        }
+                        row += size;
+                    }
+                }
             }
-            flip = !flip;
-            col = 0;
-            row += size;
+            ClearMode::VerticalGradient { .. } => {
+                // Rows of the same color merge into one batch.
+                let mut batch = self.batch.borrow_mut();
+                for row in 0..h {
+                    if let Some(color) = mode.color_at(0, row, h) {
+                        batch.set_color(color);
+                        batch.fill_rect(0, row, w as u32, 1);
+                    }
+                }
+            }
+            ClearMode::Image(ref image) => self.clear_with_image(image),
         }
+
+        self.batch
+            .borrow_mut()
+            .set_state(to_palette(self.draw_color), self.blend_mode);
+    }
+
+    // Stretches the image over the window.
+    fn clear_with_image(&self, image: &Rc<RGBA>) {
+        let cached = match *self.background.borrow() {
+            Some((ref img, id)) if Rc::ptr_eq(img, image) => Some(id),
+            _ => None,
+        };
+
+        let id = match cached {
+            Some(id) => id,
+            None => match self.replace_background(image) {
+                Ok(id) => id,
+                Err(err) => {
+                    dbg!(err);
+                    return;
+                }
+            },
+        };
+
+        // The copy bypasses the batch.
+        self.flush();
+
+        let mut textures = self.textures.borrow_mut();
+        let tex = match textures[id].as_mut() {
+            Some(tex) => tex,
+            None => return,
+        };
+        tex.set_blend_mode(SdlBlendMode::None);
+        tex.set_alpha_mod(255);
+        if let Err(err) = self.canvas.borrow_mut().copy(tex, None, None) {
+            dbg!(err);
+        }
+    }
+
+    // Uploads a new clear image, freeing the previous image's texture.
+    fn replace_background(&self, image: &Rc<RGBA>) -> Result<TextureId, String> {
+        if let Some((_, id)) = self.background.borrow_mut().take() {
+            self.free_texture(id);
+        }
+
+        let id = self.upload_texture(image)?;
+        *self.background.borrow_mut() = Some((image.clone(), id));
+        Ok(id)
     }
 
     // ----------------------------------------------------------
@@ -194,8 +412,9 @@ impl Context {
         {
             let top = &mut self.state[self.stack_top];
 
-            top.clear_color = self.clear_color;
             top.draw_color = self.draw_color;
+            top.blend_mode = self.blend_mode;
+            top.opacity = self.opacity;
             top.current = self.current_aft;
         }
         // println!("Context save:");
@@ -220,12 +439,14 @@ impl Context {
 
         let top = self.state[self.stack_top];
 
-        self.clear_color = top.clear_color;
         self.draw_color = top.draw_color;
         self.current_aft = top.current;
+        self.opacity = top.opacity;
+        self.blend_mode = top.blend_mode;
 
-        let mut can = self.canvas.borrow_mut();
-        can.set_draw_color(self.draw_color);
+        self.batch
+            .borrow_mut()
+            .set_state(to_palette(self.draw_color), self.blend_mode);
     }
 
     // ----------------------------------------------------------
@@ -259,63 +480,402 @@ impl Context {
     // Render primitives
     // All render methods are affected by the current matrix context.
     // -------------------------------------------------------------
+    /// Submits everything drawn this frame and presents it.
     pub fn post(&self) {
+        self.flush();
         self.canvas.borrow_mut().present();
+
+        self.frame_stats.set(self.batch.borrow_mut().take_stats());
+
+        let released = std::mem::take(&mut *self.releaser.released.borrow_mut());
+        for id in released {
+            self.free_texture(id);
+        }
+    }
+
+    /// Submits pending primitives to the canvas. Anything that draws to
+    /// the canvas directly must flush first to preserve drawing order.
+    pub fn flush(&self) {
+        let mut can = self.canvas.borrow_mut();
+        self.batch.borrow_mut().flush(&mut *can);
+    }
+
+    /// Draw calls, batches, etc. of the last presented frame.
+    pub fn stats(&self) -> DrawStats {
+        self.frame_stats.get()
     }
 
     pub fn render_points(&self, vertices: &RefCell<Vec<RPoint>>) {
         let v = vertices.borrow();
-        let mut can = self.canvas.borrow_mut();
+        let mut batch = self.batch.borrow_mut();
+        let rect = self.clip_rect();
 
         for p in v.iter() {
-            match can.draw_point(Point::new(p.x as i32, p.y as i32)) {
-                Err(err) => {
-                    dbg!(err);
-                }
-                _ => (),
+            if Clipping::outcode(&rect, p.x, p.y) != INSIDE {
+                continue;
             }
+            batch.point(p.x as i32, p.y as i32);
         }
     }
 
     pub fn render_line(&self, x1: f64, y1: f64, x2: f64, y2: f64) {
-        let mut can = self.canvas.borrow_mut();
-        match can.draw_line(
-            Point::new(x1 as i32, y1 as i32),
-            Point::new(x2 as i32, y2 as i32),
-        ) {
-            Err(err) => {
-                dbg!(err);
+        let rect = self.clip_rect();
+        let (p0, p1) =
+            match Clipping::liang_barsky(&rect, &RPoint::from_xy(x1, y1), &RPoint::from_xy(x2, y2))
+            {
+                Some(line) => line,
+                None => return,
+            };
+
+        self.batch
+            .borrow_mut()
+            .line(p0.x as i32, p0.y as i32, p1.x as i32, p1.y as i32);
+    }
+
+    pub fn render_lines(&self, vertices: &RefCell<Vec<RPoint>>) {
+        let v = vertices.borrow();
+        let mut batch = self.batch.borrow_mut();
+        let rect = self.clip_rect();
+
+        for pair in v.chunks(2) {
+            if pair.len() < 2 {
+                continue;
+            }
+            if let Some((p0, p1)) = Clipping::liang_barsky(&rect, &pair[0], &pair[1]) {
+                batch.line(p0.x as i32, p0.y as i32, p1.x as i32, p1.y as i32);
             }
-            _ => (),
         }
     }
 
-    pub fn render_lines(&self, vertices: &RefCell<Vec<RPoint>>) {
+    /// Draws an anti-aliased, one pixel wide, line with the current draw
+    /// color.
+    pub fn render_line_aa(&self, x1: f64, y1: f64, x2: f64, y2: f64) {
+        let rect = self.clip_rect();
+        let (p0, p1) =
+            match Clipping::liang_barsky(&rect, &RPoint::from_xy(x1, y1), &RPoint::from_xy(x2, y2))
+            {
+                Some(line) => line,
+                None => return,
+            };
+
+        let mut batch = self.batch.borrow_mut();
+        let color = self.draw_color;
+        wu_line(p0.x, p0.y, p1.x, p1.y, |x, y, coverage| {
+            blend_point(&mut batch, color, x, y, coverage);
+        });
+    }
+
+    /// The anti-aliased version of render_lines().
+    pub fn render_lines_aa(&self, vertices: &RefCell<Vec<RPoint>>) {
         let v = vertices.borrow();
-        let mut can = self.canvas.borrow_mut();
-        let mut capture = true;
-        let mut v1 = Point::new(0, 0);
+        let mut batch = self.batch.borrow_mut();
+        let color = self.draw_color;
+        let rect = self.clip_rect();
 
-        for p in v.iter() {
-            if capture {
-                v1 = Point::new(p.x as i32, p.y as i32);
-                capture = false;
-            } else {
-                match can.draw_line(v1, Point::new(p.x as i32, p.y as i32)) {
-                    Err(err) => {
-                        dbg!(err);
+        for pair in v.chunks(2) {
+            if pair.len() < 2 {
+                continue;
+            }
+            if let Some((p0, p1)) = Clipping::liang_barsky(&rect, &pair[0], &pair[1]) {
+                wu_line(p0.x, p0.y, p1.x, p1.y, |x, y, coverage| {
+                    blend_point(&mut batch, color, x, y, coverage);
+                });
+            }
+        }
+    }
+
+    /// Strokes a polyline with the current draw color. The width is in
+    /// device-space (pixels) because the vertices are already transformed.
+    pub fn render_polyline(
+        &self,
+        vertices: &RefCell<Vec<RPoint>>,
+        closed: bool,
+        style: &StrokeStyle,
+    ) {
+        let mut stroker = self.stroker.borrow_mut();
+        stroker.stroke(&vertices.borrow(), closed, style);
+        self.fill_polygon(stroker.polygon_mut());
+    }
+
+    /// The stroked version of render_lines(), each pair of vertices is a
+    /// separate line.
+    pub fn render_stroked_lines(&self, vertices: &RefCell<Vec<RPoint>>, style: &StrokeStyle) {
+        let mut stroker = self.stroker.borrow_mut();
+        stroker.stroke_segments(&vertices.borrow(), style);
+        self.fill_polygon(stroker.polygon_mut());
+    }
+
+    /// Renders pairs of vertices as lines using `style`.
+    pub fn render_styled_lines(&self, vertices: &RefCell<Vec<RPoint>>, style: &LineStyle) {
+        match *style {
+            LineStyle::Hairline => self.render_lines(vertices),
+            LineStyle::AntiAliased => self.render_lines_aa(vertices),
+            LineStyle::Stroked(ref stroke) => self.render_stroked_lines(vertices, stroke),
+        }
+    }
+
+    /// Fills a closed polygon, concave or self-intersecting, with the
+    /// current draw color.
+    pub fn render_polygon(&self, vertices: &RefCell<Vec<RPoint>>, rule: FillRule) {
+        let mut raster = self.poly_rasterizer.borrow_mut();
+        raster.set(&vertices.borrow());
+        raster.set_fill_rule(rule);
+        self.fill_polygon(&mut raster);
+    }
+
+    /// Fills a polygon made of several contours, for example, an outline
+    /// and its holes.
+    pub fn render_contours(&self, contours: &[RefCell<Vec<RPoint>>], rule: FillRule) {
+        let mut raster = self.poly_rasterizer.borrow_mut();
+        raster.clear();
+        for contour in contours {
+            raster.add_contour(&contour.borrow());
+        }
+        raster.set_fill_rule(rule);
+        self.fill_polygon(&mut raster);
+    }
+
+    // ----------------------------------------------------------
+    // Shapes
+    // Unlike the methods above, shapes are given in local-space and
+    // transformed by the current matrix. A non-uniformly scaled circle
+    // therefore renders as an ellipse. Angles are in radians.
+    // ----------------------------------------------------------
+    pub fn render_circle(&self, cx: f64, cy: f64, radius: f64, style: RenderStyle) {
+        self.render_ellipse(cx, cy, radius, radius, style);
+    }
+
+    pub fn render_ellipse(&self, cx: f64, cy: f64, rx: f64, ry: f64, style: RenderStyle) {
+        let segments = Shapes::segments_for(self.device_radius(rx, ry), 2.0 * PI);
+        Shapes::ellipse(cx, cy, rx, ry, segments, &mut self.shape_local.borrow_mut());
+        self.render_shape(style, true);
+    }
+
+    /// Draws an open arc outline.
+    pub fn render_arc(&self, cx: f64, cy: f64, rx: f64, ry: f64, start: f64, sweep: f64) {
+        let segments = Shapes::segments_for(self.device_radius(rx, ry), sweep);
+        Shapes::arc(
+            cx,
+            cy,
+            rx,
+            ry,
+            start,
+            sweep,
+            segments,
+            &mut self.shape_local.borrow_mut(),
+        );
+        self.render_shape(RenderStyle::OUTLINE, false);
+    }
+
+    /// Draws a pie slice: the arc closed through the center.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_pie(
+        &self,
+        cx: f64,
+        cy: f64,
+        rx: f64,
+        ry: f64,
+        start: f64,
+        sweep: f64,
+        style: RenderStyle,
+    ) {
+        let segments = Shapes::segments_for(self.device_radius(rx, ry), sweep);
+        Shapes::pie(
+            cx,
+            cy,
+            rx,
+            ry,
+            start,
+            sweep,
+            segments,
+            &mut self.shape_local.borrow_mut(),
+        );
+        self.render_shape(style, true);
+    }
+
+    /// (x, y) is the upper-left corner.
+    pub fn render_rounded_rectangle(
+        &self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radius: f64,
+        style: RenderStyle,
+    ) {
+        let segments = Shapes::segments_for(self.device_radius(radius, radius), PI / 2.0);
+        Shapes::rounded_rectangle(
+            x,
+            y,
+            width,
+            height,
+            radius,
+            segments,
+            &mut self.shape_local.borrow_mut(),
+        );
+        self.render_shape(style, true);
+    }
+
+    // The largest radius, in pixels, after the current transform.
+    fn device_radius(&self, rx: f64, ry: f64) -> f64 {
+        f64::max(
+            rx.abs() * self.current_aft.extract_scale_x(),
+            ry.abs() * self.current_aft.extract_scale_y(),
+        )
+    }
+
+    /// Draws a curve, given in local-space, flattened finely enough to
+    /// look smooth after the current transform.
+    pub fn render_curve(&self, curve: &dyn Curve, style: &LineStyle) {
+        let scale = self.device_radius(1.0, 1.0);
+        if scale == 0.0 {
+            return;
+        }
+        curve.flatten(CURVE_TOLERANCE / scale, &mut self.shape_local.borrow_mut());
+        self.transform_shape();
+
+        let device = self.shape_device.borrow();
+        let closed = curve.is_closed();
+
+        match *style {
+            LineStyle::Hairline => self.draw_polyline(&device, closed),
+            LineStyle::AntiAliased => {
+                let mut batch = self.batch.borrow_mut();
+                let color = self.draw_color;
+                let rect = self.clip_rect();
+                for pair in device.windows(2) {
+                    if let Some((p0, p1)) = Clipping::liang_barsky(&rect, &pair[0], &pair[1]) {
+                        wu_line(p0.x, p0.y, p1.x, p1.y, |x, y, coverage| {
+                            blend_point(&mut batch, color, x, y, coverage);
+                        });
                     }
-                    _ => (),
                 }
-                capture = true;
+            }
+            LineStyle::Stroked(ref stroke) => {
+                let mut stroker = self.stroker.borrow_mut();
+                stroker.stroke(&device, closed, stroke);
+                self.fill_polygon(stroker.polygon_mut());
+            }
+        }
+    }
+
+    // Transforms the tessellated shape into device-space.
+    fn transform_shape(&self) {
+        let local = self.shape_local.borrow();
+        let mut device = self.shape_device.borrow_mut();
+
+        device.clear();
+        for p in local.iter() {
+            let mut d = RPoint::new();
+            AffineTransform::transform_to_point(p, &mut d, &self.current_aft);
+            device.push(d);
+        }
+    }
+
+    // Transforms the tessellated shape into device-space and renders it.
+    fn render_shape(&self, style: RenderStyle, closed: bool) {
+        self.transform_shape();
+        let device = self.shape_device.borrow();
+
+        if style != RenderStyle::OUTLINE {
+            let mut raster = self.poly_rasterizer.borrow_mut();
+            raster.set(&device);
+            raster.set_fill_rule(FillRule::NonZero);
+            self.fill_polygon(&mut raster);
+        }
+
+        if style != RenderStyle::FILLED {
+            self.draw_polyline(&device, closed);
+        }
+    }
+
+    // Connects device-space vertices with hairlines.
+    fn draw_polyline(&self, vertices: &[RPoint], closed: bool) {
+        if vertices.len() < 2 {
+            return;
+        }
+
+        let mut batch = self.batch.borrow_mut();
+        let rect = self.clip_rect();
+        let count = if closed {
+            vertices.len()
+        } else {
+            vertices.len() - 1
+        };
+        for i in 0..count {
+            let a = &vertices[i];
+            let b = &vertices[(i + 1) % vertices.len()];
+            if let Some((p0, p1)) = Clipping::liang_barsky(&rect, a, b) {
+                batch.line(p0.x as i32, p0.y as i32, p1.x as i32, p1.y as i32);
             }
         }
     }
 
-    // pub fn render_polygon(&self, vertices: &Vec<RPoint>) {
-    //     let mut raster = self.tri_rasterizer.borrow_mut();
+    // ----------------------------------------------------------
+    // Clipping
+    // ----------------------------------------------------------
+    // The device-space clip rectangle, the window plus the guard band.
+    fn clip_rect(&self) -> Rectangle {
+        self.targets.clip_rect(self.window_size())
+    }
 
-    // }
+    // The size of what is being drawn to: a render target or the window.
+    fn device_size(&self) -> (u32, u32) {
+        self.targets.device_size(self.window_size())
+    }
+
+    // The canvas size, which can differ from the requested window size
+    // (high DPI, resized or fullscreen windows).
+    fn window_size(&self) -> (u32, u32) {
+        match self.canvas.borrow().output_size() {
+            Ok(size) => size,
+            Err(_) => (
+                self.world_properties.window_width as u32,
+                self.world_properties.window_height as u32,
+            ),
+        }
+    }
+
+    // Clips a polygon's contours to the clip rectangle then fills it.
+    fn fill_polygon(&self, raster: &mut FXPolygon) {
+        raster.clip(&self.clip_rect(), &mut self.clipper.borrow_mut());
+        raster.draw(self);
+    }
+
+    // Fills a convex polygon that crosses the clip rectangle. FXPolygon
+    // produces the same spans as FXTriangle, so a shape's coverage doesn't
+    // change as it crosses the clip rectangle.
+    fn fill_clipped(&self, vertices: &[RPoint]) {
+        let mut raster = self.poly_rasterizer.borrow_mut();
+        raster.set(vertices);
+        raster.set_fill_rule(FillRule::NonZero);
+        self.fill_polygon(&mut raster);
+    }
+
+    // Clips a triangle to the clip rectangle and passes each triangle of
+    // the resulting fan to `draw`, along with the barycentric weights of
+    // its vertices relative to the original triangle. Attributes are
+    // linear in device-space so the weights reproduce them exactly.
+    fn clip_triangle<F>(&self, tri: &[RPoint; 3], mut draw: F)
+    where
+        F: FnMut(&[RPoint; 3], &[[f64; 3]; 3]),
+    {
+        let mut clipped = self.clip_points.borrow_mut();
+        self.clipper
+            .borrow_mut()
+            .clip(&self.clip_rect(), tri, &mut clipped);
+
+        if clipped.len() < 3 {
+            return;
+        }
+
+        let weights = |p: &RPoint| barycentric(tri, p);
+        let w0 = weights(&clipped[0]);
+        for i in 1..clipped.len() - 1 {
+            let fan = [clipped[0], clipped[i], clipped[i + 1]];
+            draw(&fan, &[w0, weights(&fan[1]), weights(&fan[2])]);
+        }
+    }
 
     pub fn render_triangle(&self, vertices: &RefCell<Vec<RPoint>>) {
         // Update visual with transformed vertices.
@@ -324,14 +884,130 @@ impl Context {
         // raster.set(v[0].x, v[0].y, v[1].x, v[1].y, v[2].x, v[2].y);
         // raster.draw(&self);
 
+        let tri = [v[0], v[1], v[2]];
+        let rect = self.clip_rect();
+        if Clipping::outside_all(&rect, &tri) {
+            return;
+        }
+        if !Clipping::contains_all(&rect, &tri) {
+            self.fill_clipped(&tri);
+            return;
+        }
+
         let mut raster = self.fx_rasterizer.borrow_mut();
         raster.set(v[0].x, v[0].y, v[1].x, v[1].y, v[2].x, v[2].y);
         raster.draw(&self);
     }
 
+    /// Rasterizes a triangle in software with `texture` mapped onto it.
+    /// `uvs` are the normalized texture coordinates of the vertices.
+    /// Unlike render_texture() shear is honored.
+    pub fn render_textured_triangle(
+        &self,
+        vertices: &RefCell<Vec<RPoint>>,
+        uvs: &[RPoint],
+        texture: &RGBA,
+        sampler: &TextureSampler,
+    ) {
+        let v = vertices.borrow();
+        let tri = [v[0], v[1], v[2]];
+        let uvs = [uvs[0], uvs[1], uvs[2]];
+
+        let rect = self.clip_rect();
+        if Clipping::outside_all(&rect, &tri) {
+            return;
+        }
+        if Clipping::contains_all(&rect, &tri) {
+            self.draw_textured_triangle(&tri, &uvs, texture, sampler);
+            return;
+        }
+
+        self.clip_triangle(&tri, |fan, weights| {
+            let fan_uvs = [
+                weigh_points(&uvs, &weights[0]),
+                weigh_points(&uvs, &weights[1]),
+                weigh_points(&uvs, &weights[2]),
+            ];
+            self.draw_textured_triangle(fan, &fan_uvs, texture, sampler);
+        });
+    }
+
+    fn draw_textured_triangle(
+        &self,
+        v: &[RPoint; 3],
+        uvs: &[RPoint; 3],
+        texture: &RGBA,
+        sampler: &TextureSampler,
+    ) {
+        let mut raster = self.fx_rasterizer.borrow_mut();
+        raster.set(v[0].x, v[0].y, v[1].x, v[1].y, v[2].x, v[2].y);
+        raster.set_uvs(uvs[0].x, uvs[0].y, uvs[1].x, uvs[1].y, uvs[2].x, uvs[2].y);
+
+        let mut batch = self.batch.borrow_mut();
+        raster.draw_textured(texture, sampler, |x, y, c| {
+            let alpha = self.modulate(c[3]);
+            if alpha == 0 {
+                return;
+            }
+            batch.colored_point(x, y, Palette::RGBA(c[0], c[1], c[2], alpha));
+        });
+    }
+
+    /// Rasterizes a triangle in software blending `colors`, one per
+    /// vertex, across it. Alpha is blended with what is already drawn.
+    pub fn render_shaded_triangle(&self, vertices: &RefCell<Vec<RPoint>>, colors: &[Palette]) {
+        let v = vertices.borrow();
+        let tri = [v[0], v[1], v[2]];
+        let colors = [colors[0], colors[1], colors[2]];
+
+        let rect = self.clip_rect();
+        if Clipping::outside_all(&rect, &tri) {
+            return;
+        }
+        if Clipping::contains_all(&rect, &tri) {
+            self.draw_shaded_triangle(&tri, &colors);
+            return;
+        }
+
+        self.clip_triangle(&tri, |fan, weights| {
+            let fan_colors = [
+                weigh_colors(&colors, &weights[0]),
+                weigh_colors(&colors, &weights[1]),
+                weigh_colors(&colors, &weights[2]),
+            ];
+            self.draw_shaded_triangle(fan, &fan_colors);
+        });
+    }
+
+    fn draw_shaded_triangle(&self, v: &[RPoint; 3], colors: &[Palette; 3]) {
+        let mut raster = self.fx_rasterizer.borrow_mut();
+        raster.set(v[0].x, v[0].y, v[1].x, v[1].y, v[2].x, v[2].y);
+        raster.set_colors(&colors[0], &colors[1], &colors[2]);
+
+        let mut batch = self.batch.borrow_mut();
+        raster.draw_shaded(|x, y, c| {
+            let alpha = self.modulate(c[3]);
+            if alpha == 0 {
+                return;
+            }
+            batch.colored_point(x, y, Palette::RGBA(c[0], c[1], c[2], alpha));
+        });
+    }
+
     pub fn render_rectangle(&self, vertices: &RefCell<Vec<RPoint>>) {
         // Update visual with transformed vertices.
         let v = vertices.borrow();
+
+        let quad = [v[0], v[1], v[2], v[3]];
+        let rect = self.clip_rect();
+        if Clipping::outside_all(&rect, &quad) {
+            return;
+        }
+        if !Clipping::contains_all(&rect, &quad) {
+            self.fill_clipped(&quad);
+            return;
+        }
+
         // let mut raster = self.tri_rasterizer.borrow_mut();
         let mut raster = self.fx_rasterizer.borrow_mut();
 
@@ -352,6 +1028,78 @@ impl Context {
         raster.draw(&self);
     }
 
+    /// Renders the `src` region of a texture onto a quad. The vertices are
+    /// the transformed corners of the quad in the order: top-left,
+    /// top-right, bottom-right, bottom-left.
+    /// Any rotation, scale and mirroring are honored, shear is not.
+    pub fn render_texture(
+        &self,
+        texture: TextureId,
+        src: &ImgRectangle,
+        vertices: &RefCell<Vec<RPoint>>,
+    ) {
+        let v = vertices.borrow();
+        let mut textures = self.textures.borrow_mut();
+
+        let tex = match textures.get_mut(texture).and_then(Option::as_mut) {
+            Some(tex) => tex,
+            None => return,
+        };
+
+        tex.set_blend_mode(to_sdl_blend_mode(self.blend_mode));
+        tex.set_alpha_mod(self.modulate(255));
+
+        // The quad's edge vectors
+        let (ux, uy) = (v[1].x - v[0].x, v[1].y - v[0].y);
+        let (vx, vy) = (v[3].x - v[0].x, v[3].y - v[0].y);
+
+        let width = (ux * ux + uy * uy).sqrt().round();
+        let height = (vx * vx + vy * vy).sqrt().round();
+        if width < 1.0 || height < 1.0 {
+            return;
+        }
+
+        // SDL rotates clockwise (in device-space) about the destination's
+        // top-left. If the quad is mirrored the bottom-left becomes the
+        // origin and the texture is flipped vertically.
+        let mirrored = ux * vy - uy * vx < 0.0;
+        let origin = if mirrored { v[3] } else { v[0] };
+        let angle = uy.atan2(ux).to_degrees();
+
+        let src_rect = Rect::new(
+            src.min.x as i32,
+            src.min.y as i32,
+            src.w() as u32,
+            src.h() as u32,
+        );
+        let dst_rect = Rect::new(
+            origin.x.round() as i32,
+            origin.y.round() as i32,
+            width as u32,
+            height as u32,
+        );
+
+        // The copy bypasses the batch so anything drawn before it must be
+        // submitted first.
+        self.flush();
+
+        let mut can = self.canvas.borrow_mut();
+        match can.copy_ex(
+            tex,
+            src_rect,
+            dst_rect,
+            angle,
+            Point::new(0, 0),
+            false,
+            mirrored,
+        ) {
+            Err(err) => {
+                dbg!(err);
+            }
+            _ => (),
+        }
+    }
+
     // The vertices are expected to be the corners of an axis aligned bounding box.
     pub fn render_aabb_rectangle(&self, corners: &AABBox, filled: RenderStyle) {
         // Update visual with transformed vertices.
@@ -373,6 +1121,275 @@ impl Context {
         }
     }
 
+    // ----------------------------------------------------------
+    // Textures
+    // ----------------------------------------------------------
+    /// Uploads an image to the backend. The returned id is valid until
+    /// passed to free_texture.
+    pub fn upload_texture(&self, image: &RGBA) -> Result<TextureId, String> {
+        let texture = self.create_texture(image)?;
+        Ok(self.store_texture(texture))
+    }
+
+    /// Destroys the texture, its id may be handed out again. Unknown or
+    /// already freed ids are ignored. A render target mustn't be freed
+    /// while it is being drawn into, see TextureReleaser.
+    pub fn free_texture(&self, texture: TextureId) {
+        let freed = match self.textures.borrow_mut().get_mut(texture) {
+            Some(slot) => slot.take(),
+            None => None,
+        };
+        if let Some(tex) = freed {
+            unsafe { tex.destroy() };
+        }
+    }
+
+    /// A handle for freeing textures without a Context.
+    pub fn texture_releaser(&self) -> TextureReleaser {
+        self.releaser.clone()
+    }
+
+    // Puts the texture in the first free slot.
+    fn store_texture(&self, texture: Texture) -> TextureId {
+        let mut textures = self.textures.borrow_mut();
+        match textures.iter().position(|slot| slot.is_none()) {
+            Some(id) => {
+                textures[id] = Some(texture);
+                id
+            }
+            None => {
+                textures.push(Some(texture));
+                textures.len() - 1
+            }
+        }
+    }
+
+    fn create_texture(&self, image: &RGBA) -> Result<Texture, String> {
+        let bounds = image.bounds();
+        let can = self.canvas.borrow();
+
+        // RGBA32 matches RGBA's byte order regardless of endianness.
+        let mut texture = match can.create_texture_static(
+            PixelFormatEnum::RGBA32,
+            bounds.w() as u32,
+            bounds.h() as u32,
+        ) {
+            Ok(tex) => tex,
+            Err(err) => return Err(err.to_string()),
+        };
+
+        if let Err(err) = texture.update(None, &image.pix, image.stride) {
+            return Err(err.to_string());
+        }
+
+        texture.set_blend_mode(SdlBlendMode::Blend);
+
+        Ok(texture)
+    }
+
+    // ----------------------------------------------------------
+    // Render targets
+    // ----------------------------------------------------------
+    /// Creates a texture that can be drawn into with begin_render_target.
+    /// Its contents are undefined until rendered. Free it with
+    /// free_texture like any other texture.
+    pub fn create_render_target(&self, width: u32, height: u32) -> Result<TextureId, String> {
+        let can = self.canvas.borrow();
+
+        if !can.render_target_supported() {
+            return Err(String::from("Render targets are not supported"));
+        }
+
+        let mut texture =
+            match can.create_texture_target(PixelFormatEnum::RGBA32, width.max(1), height.max(1)) {
+                Ok(tex) => tex,
+                Err(err) => return Err(err.to_string()),
+            };
+
+        texture.set_blend_mode(SdlBlendMode::Blend);
+
+        Ok(self.store_texture(texture))
+    }
+
+    /// Redirects drawing into `target`, which must have been created with
+    /// create_render_target, until the matching end_render_target. Targets
+    /// nest.
+    ///
+    /// The state is saved and the transform is reset so that the target's
+    /// center is the origin and one unit is one texel. The target is
+    /// cleared to `clear`, use a transparent color to keep it see-through.
+    pub fn begin_render_target(
+        &mut self,
+        target: TextureId,
+        width: u32,
+        height: u32,
+        clear: Palette,
+    ) -> Result<(), String> {
+        // Everything so far belongs to the current target.
+        self.flush();
+
+        self.bind_target(Some(target))?;
+
+        self.save();
+        self.targets.push(target, width, height);
+
+        self.current_aft
+            .make_translate(width as f64 / 2.0, height as f64 / 2.0);
+        self.opacity = 1.0;
+        self.blend_mode = BlendMode::Alpha;
+
+        {
+            let mut batch = self.batch.borrow_mut();
+            batch.set_state(clear, BlendMode::None);
+            batch.fill_rect(0, 0, width, height);
+            batch.set_state(to_palette(self.draw_color), self.blend_mode);
+        }
+
+        Ok(())
+    }
+
+    /// Finishes the current render target and returns to the previous one,
+    /// or the window.
+    pub fn end_render_target(&mut self) {
+        if !self.targets.pop() {
+            return;
+        }
+
+        self.flush();
+
+        if let Err(err) = self.bind_target(self.targets.current()) {
+            dbg!(err);
+        }
+
+        self.restore();
+    }
+
+    /// True while drawing into a render target.
+    pub fn in_render_target(&self) -> bool {
+        !self.targets.is_empty()
+    }
+
+    // ----------------------------------------------------------
+    // Post-processing
+    // ----------------------------------------------------------
+    /// Reads back what has been drawn so far, runs it through `chain` and
+    /// writes the result back. Applies to the current render target, or
+    /// the window. This is a full-frame software pass and costs a read
+    /// back plus an upload, the filters' own cost aside.
+    pub fn apply_filters(&self, chain: &mut FilterChain) {
+        if !chain.is_active() {
+            return;
+        }
+
+        self.flush();
+
+        let (width, height) = self.device_size();
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let mut frame = self.frame.borrow_mut();
+        if let Err(err) = self.read_frame(&mut frame) {
+            dbg!(err);
+            return;
+        }
+
+        chain.apply(&mut frame);
+
+        let mut frame_texture = self.frame_texture.borrow_mut();
+        let stale = match *frame_texture {
+            Some(ref tex) => {
+                let query = tex.query();
+                query.width != width || query.height != height
+            }
+            None => true,
+        };
+
+        if stale {
+            let can = self.canvas.borrow();
+            match can.create_texture_streaming(PixelFormatEnum::RGBA32, width, height) {
+                Ok(mut tex) => {
+                    // The frame replaces what is there.
+                    tex.set_blend_mode(SdlBlendMode::None);
+                    if let Some(old) = frame_texture.replace(tex) {
+                        // Textures aren't freed automatically.
+                        unsafe { old.destroy() };
+                    }
+                }
+                Err(err) => {
+                    dbg!(err);
+                    return;
+                }
+            }
+        }
+
+        if let Some(ref mut tex) = *frame_texture {
+            if let Err(err) = tex.update(None, &frame.pix, frame.stride) {
+                dbg!(err);
+                return;
+            }
+            if let Err(err) = self.canvas.borrow_mut().copy(tex, None, None) {
+                dbg!(err);
+            }
+        }
+    }
+
+    /// Copies what has been drawn so far, from the current render target
+    /// or the window. For the window this must happen before `post()` as
+    /// the back buffer is undefined once presented.
+    pub fn capture_frame(&self) -> Result<RGBA, String> {
+        self.flush();
+
+        let (width, height) = self.device_size();
+        let mut frame = RGBA::new(width as usize, height as usize);
+        self.read_frame(&mut frame)?;
+        Ok(frame)
+    }
+
+    // Reads the current target into `frame`, resizing it if needed.
+    fn read_frame(&self, frame: &mut RGBA) -> Result<(), String> {
+        let (width, height) = self.device_size();
+        if frame.bounds().w() != width as usize || frame.bounds().h() != height as usize {
+            *frame = RGBA::new(width as usize, height as usize);
+        }
+
+        let pixels = self
+            .canvas
+            .borrow()
+            .read_pixels(None, PixelFormatEnum::RGBA32)?;
+        let len = frame.pix.len().min(pixels.len());
+        frame.pix[..len].copy_from_slice(&pixels[..len]);
+
+        Ok(())
+    }
+
+    // Points SDL at a texture, or the window if None.
+    fn bind_target(&self, target: Option<TextureId>) -> Result<(), String> {
+        let textures = self.textures.borrow();
+        let raw_texture = match target {
+            Some(id) => match textures.get(id).and_then(Option::as_ref) {
+                Some(tex) => tex.raw(),
+                None => return Err(format!("Unknown render target: {}", id)),
+            },
+            None => std::ptr::null_mut(),
+        };
+
+        let can = self.canvas.borrow();
+        // The sdl2 crate only exposes render targets through a closure
+        // (with_texture_canvas) which can't span a node tree visit, so the
+        // target is set directly. The texture outlives its use as a target
+        // as targets being drawn into mustn't be freed.
+        let result = unsafe { sdl2::sys::SDL_SetRenderTarget(can.raw(), raw_texture) };
+        if result != 0 {
+            return Err(sdl2::get_error());
+        }
+
+        // Some drivers reset the draw state when the target changes.
+        self.batch.borrow_mut().invalidate();
+
+        Ok(())
+    }
+
     // ############################################################################
     // ############################################################################
     // ############################################################################
@@ -384,43 +1401,19 @@ impl Context {
     /// x,y should already be transformed.
     #[inline(always)]
     pub fn set_pixel(&self, x: i32, y: i32) {
-        let mut can = self.canvas.borrow_mut();
-        match can.draw_point(Point::new(x, y)) {
-            Err(err) => {
-                dbg!(err);
-            }
-            _ => (),
-        }
+        self.batch.borrow_mut().point(x, y);
     }
 
     pub fn draw_rectangle(&self, xmin: i32, ymin: i32, xmax: i32, ymax: i32) {
-        let mut can = self.canvas.borrow_mut();
-        match can.draw_rect(Rect::new(
-            xmin,
-            ymin,
-            (xmax - xmin) as u32,
-            (ymax - ymin) as u32,
-        )) {
-            Err(err) => {
-                dbg!(err);
-            }
-            _ => (),
-        }
+        self.batch
+            .borrow_mut()
+            .draw_rect(xmin, ymin, (xmax - xmin) as u32, (ymax - ymin) as u32);
     }
 
     pub fn fill_rectangle(&self, xmin: i32, ymin: i32, xmax: i32, ymax: i32) {
-        let mut can = self.canvas.borrow_mut();
-        match can.fill_rect(Rect::new(
-            xmin,
-            ymin,
-            (xmax - xmin) as u32,
-            (ymax - ymin) as u32,
-        )) {
-            Err(err) => {
-                dbg!(err);
-            }
-            _ => (),
-        }
+        self.batch
+            .borrow_mut()
+            .fill_rect(xmin, ymin, (xmax - xmin) as u32, (ymax - ymin) as u32);
     }
 
     pub fn draw_point(&mut self, _x: f64, _y: f64) {
@@ -429,37 +1422,16 @@ impl Context {
 
     #[inline]
     pub fn draw_horz_line(&self, x1: i32, x2: i32, y: i32) {
-        match self
-            .canvas
-            .borrow_mut()
-            .draw_line(Point::new(x1, y), Point::new(x2, y))
-        {
-            Err(err) => {
-                dbg!(err);
-            }
-            _ => (),
-        }
+        self.batch.borrow_mut().line(x1, y, x2, y);
     }
 
     // x,y are in view-space coordinates
     pub fn draw_horz_line_color(&mut self, x1: i32, x2: i32, y: i32) {
-        let mut can = self.canvas.borrow_mut();
-        match can.draw_line(Point::new(x1, y), Point::new(x2, y)) {
-            Err(err) => {
-                dbg!(err);
-            }
-            _ => (),
-        }
+        self.batch.borrow_mut().line(x1, y, x2, y);
     }
 
     pub fn draw_vert_line(&mut self, y1: i32, y2: i32, x: i32) {
-        let mut can = self.canvas.borrow_mut();
-        match can.draw_line(Point::new(x, y1), Point::new(x, y2)) {
-            Err(err) => {
-                dbg!(err);
-            }
-            _ => (),
-        }
+        self.batch.borrow_mut().line(x, y1, x, y2);
     }
 
     pub fn text(&mut self, x: i32, y: i32, text: &str, scale: usize, fill: usize) {
@@ -506,6 +1478,56 @@ impl Context {
     }
 }
 
+// The barycentric weights of `p` relative to `tri`.
+fn barycentric(tri: &[RPoint; 3], p: &RPoint) -> [f64; 3] {
+    let (a, b, c) = (&tri[0], &tri[1], &tri[2]);
+    let area = (b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y);
+    if area == 0.0 {
+        return [1.0, 0.0, 0.0];
+    }
+
+    let w1 = ((p.x - a.x) * (c.y - a.y) - (c.x - a.x) * (p.y - a.y)) / area;
+    let w2 = ((b.x - a.x) * (p.y - a.y) - (p.x - a.x) * (b.y - a.y)) / area;
+    [1.0 - w1 - w2, w1, w2]
+}
+
+fn weigh_points(points: &[RPoint; 3], w: &[f64; 3]) -> RPoint {
+    RPoint::from_xy(
+        points[0].x * w[0] + points[1].x * w[1] + points[2].x * w[2],
+        points[0].y * w[0] + points[1].y * w[1] + points[2].y * w[2],
+    )
+}
+
+fn weigh_colors(colors: &[Palette; 3], w: &[f64; 3]) -> Palette {
+    let mix = |c0: u8, c1: u8, c2: u8| {
+        (c0 as f64 * w[0] + c1 as f64 * w[1] + c2 as f64 * w[2])
+            .round()
+            .clamp(0.0, 255.0) as u8
+    };
+    Palette::RGBA(
+        mix(colors[0].r, colors[1].r, colors[2].r),
+        mix(colors[0].g, colors[1].g, colors[2].g),
+        mix(colors[0].b, colors[1].b, colors[2].b),
+        mix(colors[0].a, colors[1].a, colors[2].a),
+    )
+}
+
+fn to_palette(color: Color) -> Palette {
+    Palette::RGBA(color.r, color.g, color.b, color.a)
+}
+
+// Draws a point with `color`'s alpha scaled by `coverage`, rounded to
+// one of COVERAGE_LEVELS.
+#[inline]
+fn blend_point(batch: &mut RenderBatch, color: Color, x: i32, y: i32, coverage: f64) {
+    let coverage = (coverage.min(1.0) * COVERAGE_LEVELS).round() / COVERAGE_LEVELS;
+    let alpha = (color.a as f64 * coverage).round() as u8;
+    if alpha == 0 {
+        return;
+    }
+    batch.colored_point(x, y, Palette::RGBA(color.r, color.g, color.b, alpha));
+}
+
 fn print_stack(state: &Vec<State>, stack_top: usize, to_depth: usize) {
     println!("Stack --------------------------");
     for i in 0..to_depth {
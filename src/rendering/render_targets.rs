@@ -0,0 +1,79 @@
+use geometry::rectangle::Rectangle;
+use rendering::render_context::TextureId;
+
+// The render targets the Context is drawing into. begin_render_target
+// pushes, end_render_target pops and binds whatever is now on top, or the
+// window once the stack is empty.
+//
+// Kept apart from the Context, which does the SDL side of binding, so the
+// nesting can be checked without a window.
+
+// Geometry is clipped to the device grown by this many pixels on each
+// side. Geometry within the band is left alone, so clipping rarely
+// changes which pixels are drawn.
+pub const GUARD_BAND: f64 = 64.0;
+
+// An offscreen texture being rendered into.
+#[derive(Debug, Clone, Copy)]
+struct Offscreen {
+    texture: TextureId,
+    width: u32,
+    height: u32,
+}
+
+pub struct RenderTargets {
+    // The last is being drawn to.
+    stack: Vec<Offscreen>,
+}
+
+impl RenderTargets {
+    pub fn new() -> Self {
+        Self { stack: Vec::new() }
+    }
+
+    pub fn push(&mut self, texture: TextureId, width: u32, height: u32) {
+        self.stack.push(Offscreen {
+            texture,
+            width,
+            height,
+        });
+    }
+
+    /// Returns false if there was no target to pop.
+    pub fn pop(&mut self) -> bool {
+        self.stack.pop().is_some()
+    }
+
+    /// The target being drawn to, None for the window.
+    pub fn current(&self) -> Option<TextureId> {
+        self.stack.last().map(|target| target.texture)
+    }
+
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// The size of what is being drawn to: the current target, or the
+    /// window whose size is given.
+    pub fn device_size(&self, window: (u32, u32)) -> (u32, u32) {
+        match self.stack.last() {
+            Some(target) => (target.width, target.height),
+            None => window,
+        }
+    }
+
+    /// The device-space clip rectangle, the device plus the guard band.
+    pub fn clip_rect(&self, window: (u32, u32)) -> Rectangle {
+        let (width, height) = self.device_size(window);
+        Rectangle::from_min_max(
+            -GUARD_BAND,
+            -GUARD_BAND,
+            width as f64 + GUARD_BAND,
+            height as f64 + GUARD_BAND,
+        )
+    }
+}
@@ -0,0 +1,148 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use rendering::image::{ImgRectangle, RGBA};
+use rendering::render_context::{Context, TextureId, TextureReleaser};
+
+// A SpriteSheet is an atlas image plus the regions (frames) within it.
+// The image is uploaded to the backend the first time it is needed and
+// the texture is shared by every node drawing from the sheet. It is freed
+// when the sheet is dropped.
+//
+// Frames may be named, e.g. when loaded from an atlas, and may be stored
+// rotated 90 degrees clockwise as atlas packers do to save space.
+
+pub struct SpriteSheet {
+    image: RGBA,
+    frames: Vec<ImgRectangle>,
+    rotated: Vec<bool>,
+    names: HashMap<String, usize>,
+
+    texture: Cell<Option<TextureId>>,
+    upload_failed: Cell<bool>,
+    // Set with the texture.
+    releaser: RefCell<Option<TextureReleaser>>,
+}
+
+impl Drop for SpriteSheet {
+    fn drop(&mut self) {
+        if let (Some(texture), Some(releaser)) =
+            (self.texture.get(), self.releaser.borrow().as_ref())
+        {
+            releaser.release(texture);
+        }
+    }
+}
+
+impl SpriteSheet {
+    pub fn new(image: RGBA) -> Self {
+        Self {
+            image,
+            frames: Vec::new(),
+            rotated: Vec::new(),
+            names: HashMap::new(),
+            texture: Cell::new(None),
+            upload_failed: Cell::new(false),
+            releaser: RefCell::new(None),
+        }
+    }
+
+    /// Slices the image into frames of equal size, left to right then
+    /// top to bottom. Partial frames at the right and bottom edges are
+    /// ignored.
+    pub fn from_grid(image: RGBA, frame_width: usize, frame_height: usize) -> Self {
+        let mut sheet = SpriteSheet::new(image);
+
+        if frame_width == 0 || frame_height == 0 {
+            return sheet;
+        }
+
+        let columns = sheet.image.bounds().w() / frame_width;
+        let rows = sheet.image.bounds().h() / frame_height;
+
+        for row in 0..rows {
+            for col in 0..columns {
+                sheet.add_frame(ImgRectangle::from_xywh(
+                    col * frame_width,
+                    row * frame_height,
+                    frame_width,
+                    frame_height,
+                ));
+            }
+        }
+
+        sheet
+    }
+
+    /// Returns the index of the new frame.
+    pub fn add_frame(&mut self, region: ImgRectangle) -> usize {
+        self.frames.push(region);
+        self.rotated.push(false);
+        self.frames.len() - 1
+    }
+
+    /// Adds a frame that can be looked up by name. If `rotated` the region
+    /// holds the frame rotated 90 degrees clockwise. A name that is
+    /// already used refers to the new frame afterwards.
+    pub fn add_named_frame(&mut self, name: &str, region: ImgRectangle, rotated: bool) -> usize {
+        let index = self.add_frame(region);
+        self.rotated[index] = rotated;
+        self.names.insert(name.to_string(), index);
+        index
+    }
+
+    /// The frame's region within the image, as stored.
+    pub fn frame(&self, index: usize) -> Option<&ImgRectangle> {
+        self.frames.get(index)
+    }
+
+    pub fn frame_index(&self, name: &str) -> Option<usize> {
+        self.names.get(name).cloned()
+    }
+
+    pub fn frame_names(&self) -> impl Iterator<Item = (&String, &usize)> {
+        self.names.iter()
+    }
+
+    pub fn is_rotated(&self, index: usize) -> bool {
+        self.rotated.get(index).cloned().unwrap_or(false)
+    }
+
+    /// The frame's size as displayed, i.e. with any rotation undone.
+    pub fn frame_size(&self, index: usize) -> Option<(usize, usize)> {
+        self.frames.get(index).map(|r| {
+            if self.is_rotated(index) {
+                (r.h(), r.w())
+            } else {
+                (r.w(), r.h())
+            }
+        })
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn image(&self) -> &RGBA {
+        &self.image
+    }
+
+    /// Returns the sheet's texture, uploading the image if it hasn't been
+    /// already. A failed upload is reported once and not retried.
+    pub fn texture(&self, context: &Context) -> Option<TextureId> {
+        if self.texture.get().is_none() && !self.upload_failed.get() {
+            match context.upload_texture(&self.image) {
+                Ok(id) => {
+                    self.texture.set(Some(id));
+                    *self.releaser.borrow_mut() = Some(context.texture_releaser());
+                }
+                Err(err) => {
+                    println!("SpriteSheet: texture upload failed: {}", err);
+                    self.upload_failed.set(true);
+                }
+            }
+        }
+
+        self.texture.get()
+    }
+}
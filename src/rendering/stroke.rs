@@ -0,0 +1,368 @@
+use std::f64::consts::PI;
+
+use geometry::point::Point;
+use rendering::fx_polygon::{FXPolygon, FillRule};
+
+// A Stroker converts a polyline into a polygon that covers the line
+// drawn with a given width. Each segment, join and cap becomes its own
+// contour, all wound the same way, so filling with the NonZero rule gives
+// their union and no pixel is drawn twice.
+
+// Segments shorter than this are ignored.
+const EPSILON: f64 = 1.0e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineJoin {
+    /// Extend the outer edges until they meet. Falls back to Bevel when
+    /// the miter would exceed the miter limit.
+    Miter,
+    Round,
+    /// Connect the outer corners with a straight edge.
+    Bevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineCap {
+    /// The stroke ends exactly at the end point.
+    Butt,
+    Round,
+    /// The stroke extends half the width beyond the end point.
+    Square,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f64,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// The maximum ratio of miter length to stroke width.
+    pub miter_limit: f64,
+}
+
+impl StrokeStyle {
+    pub fn new(width: f64) -> Self {
+        Self {
+            width,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+        }
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_miter_limit(mut self, limit: f64) -> Self {
+        self.miter_limit = limit;
+        self
+    }
+}
+
+/// How line based nodes render their lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineStyle {
+    /// One pixel wide and aliased
+    Hairline,
+    /// One pixel wide and anti-aliased
+    AntiAliased,
+    /// Filled with a width, joins and caps
+    Stroked(StrokeStyle),
+}
+
+pub struct Stroker {
+    polygon: FXPolygon,
+
+    // Polyline with duplicate points removed
+    points: Vec<Point>,
+    contour: Vec<Point>,
+}
+
+impl Stroker {
+    pub fn new() -> Self {
+        let mut polygon = FXPolygon::new();
+        polygon.set_fill_rule(FillRule::NonZero);
+
+        Self {
+            polygon,
+            points: Vec::new(),
+            contour: Vec::new(),
+        }
+    }
+
+    /// The polygon built by the last call to stroke() or stroke_segments().
+    pub fn polygon(&self) -> &FXPolygon {
+        &self.polygon
+    }
+
+    pub fn polygon_mut(&mut self) -> &mut FXPolygon {
+        &mut self.polygon
+    }
+
+    /// Strokes a polyline. If `closed` the last point connects back to the
+    /// first with a join instead of caps.
+    pub fn stroke(&mut self, vertices: &[Point], closed: bool, style: &StrokeStyle) -> &FXPolygon {
+        self.polygon.clear();
+        self.add_polyline(vertices, closed, style);
+        &self.polygon
+    }
+
+    /// Strokes independent line segments given as pairs of points, the
+    /// same layout Context::render_lines() uses.
+    pub fn stroke_segments(&mut self, vertices: &[Point], style: &StrokeStyle) -> &FXPolygon {
+        self.polygon.clear();
+        for pair in vertices.chunks(2) {
+            if pair.len() == 2 {
+                self.add_polyline(pair, false, style);
+            }
+        }
+        &self.polygon
+    }
+
+    fn add_polyline(&mut self, vertices: &[Point], closed: bool, style: &StrokeStyle) {
+        let hw = style.width / 2.0;
+        if hw <= 0.0 {
+            return;
+        }
+
+        self.points.clear();
+        for v in vertices {
+            let duplicate = match self.points.last() {
+                Some(last) => distance(last, v) < EPSILON,
+                None => false,
+            };
+            if !duplicate {
+                self.points.push(*v);
+            }
+        }
+
+        let mut closed = closed;
+        if closed
+            && self.points.len() > 1
+            && distance(&self.points[0], &self.points[self.points.len() - 1]) < EPSILON
+        {
+            self.points.pop();
+        }
+        if self.points.len() < 3 {
+            closed = false;
+        }
+
+        let count = self.points.len();
+        if count == 0 {
+            return;
+        }
+
+        if count == 1 {
+            // A dot
+            let p = self.points[0];
+            match style.cap {
+                LineCap::Butt => (),
+                LineCap::Round => {
+                    self.add_arc(&p, hw, 0.0, 2.0 * PI, false);
+                }
+                LineCap::Square => {
+                    let quad = [
+                        Point::from_xy(p.x - hw, p.y - hw),
+                        Point::from_xy(p.x + hw, p.y - hw),
+                        Point::from_xy(p.x + hw, p.y + hw),
+                        Point::from_xy(p.x - hw, p.y + hw),
+                    ];
+                    self.add_contour(&quad);
+                }
+            }
+            return;
+        }
+
+        let segments = if closed { count } else { count - 1 };
+
+        for i in 0..segments {
+            let a = self.points[i];
+            let b = self.points[(i + 1) % count];
+            let n = normal(&a, &b);
+
+            let quad = [
+                Point::from_xy(a.x + n.x * hw, a.y + n.y * hw),
+                Point::from_xy(b.x + n.x * hw, b.y + n.y * hw),
+                Point::from_xy(b.x - n.x * hw, b.y - n.y * hw),
+                Point::from_xy(a.x - n.x * hw, a.y - n.y * hw),
+            ];
+            self.add_contour(&quad);
+        }
+
+        // Joins
+        let (first, last) = if closed { (0, count) } else { (1, count - 1) };
+        for i in first..last {
+            let prev = self.points[(i + count - 1) % count];
+            let p = self.points[i];
+            let next = self.points[(i + 1) % count];
+            self.add_join(&prev, &p, &next, hw, style);
+        }
+
+        if !closed {
+            let start = self.points[0];
+            let second = self.points[1];
+            self.add_cap(&start, &second, hw, style.cap);
+
+            let end = self.points[count - 1];
+            let before = self.points[count - 2];
+            self.add_cap(&end, &before, hw, style.cap);
+        }
+    }
+
+    fn add_join(&mut self, prev: &Point, p: &Point, next: &Point, hw: f64, style: &StrokeStyle) {
+        let d0 = direction(prev, p);
+        let d1 = direction(p, next);
+        let cross = d0.x * d1.y - d0.y * d1.x;
+        let dot = d0.x * d1.x + d0.y * d1.y;
+
+        if cross.abs() < EPSILON && dot > 0.0 {
+            return; // Straight, the segments already meet.
+        }
+
+        // The outer side of the turn
+        let side = if cross > 0.0 { -1.0 } else { 1.0 };
+        let n0 = Point::from_xy(-d0.y * side, d0.x * side);
+        let n1 = Point::from_xy(-d1.y * side, d1.x * side);
+
+        let o0 = Point::from_xy(p.x + n0.x * hw, p.y + n0.y * hw);
+        let o1 = Point::from_xy(p.x + n1.x * hw, p.y + n1.y * hw);
+
+        match style.join {
+            LineJoin::Round => {
+                let start = n0.y.atan2(n0.x);
+                let mut sweep = n1.y.atan2(n1.x) - start;
+                // Always take the short way round the outside.
+                while sweep > PI {
+                    sweep -= 2.0 * PI;
+                }
+                while sweep < -PI {
+                    sweep += 2.0 * PI;
+                }
+                if dot < 0.0 && cross.abs() < EPSILON {
+                    // A full reversal, go round the end from n0 through d0.
+                    sweep = -PI;
+                }
+                self.add_arc(p, hw, start, sweep, true);
+            }
+            LineJoin::Miter => {
+                let bisector = Point::from_xy(n0.x + n1.x, n0.y + n1.y);
+                let len = (bisector.x * bisector.x + bisector.y * bisector.y).sqrt();
+                // cos of half the angle between the normals
+                let cos_half = if len > EPSILON {
+                    (bisector.x * n0.x + bisector.y * n0.y) / len
+                } else {
+                    0.0
+                };
+
+                if cos_half > EPSILON && 1.0 / cos_half <= style.miter_limit {
+                    let miter = hw / cos_half;
+                    let m = Point::from_xy(
+                        p.x + bisector.x / len * miter,
+                        p.y + bisector.y / len * miter,
+                    );
+                    self.add_contour(&[*p, o0, m, o1]);
+                } else {
+                    self.add_contour(&[*p, o0, o1]);
+                }
+            }
+            LineJoin::Bevel => {
+                self.add_contour(&[*p, o0, o1]);
+            }
+        }
+    }
+
+    // `p` is the end point and `inner` the neighbouring point on the line.
+    fn add_cap(&mut self, p: &Point, inner: &Point, hw: f64, cap: LineCap) {
+        // Outward direction
+        let d = direction(inner, p);
+        let n = Point::from_xy(-d.y, d.x);
+
+        match cap {
+            LineCap::Butt => (),
+            LineCap::Square => {
+                let quad = [
+                    Point::from_xy(p.x + n.x * hw, p.y + n.y * hw),
+                    Point::from_xy(p.x + (n.x + d.x) * hw, p.y + (n.y + d.y) * hw),
+                    Point::from_xy(p.x + (d.x - n.x) * hw, p.y + (d.y - n.y) * hw),
+                    Point::from_xy(p.x - n.x * hw, p.y - n.y * hw),
+                ];
+                self.add_contour(&quad);
+            }
+            LineCap::Round => {
+                // Half circle from +n through d to -n. n is d rotated by
+                // +90 degrees so the sweep is negative.
+                let start = n.y.atan2(n.x);
+                self.add_arc(p, hw, start, -PI, true);
+            }
+        }
+    }
+
+    // Adds a circular arc (as a pie slice if `pie`) or a full circle.
+    fn add_arc(&mut self, center: &Point, radius: f64, start: f64, sweep: f64, pie: bool) {
+        let steps = arc_steps(radius, sweep);
+
+        self.contour.clear();
+        if pie {
+            self.contour.push(*center);
+        }
+        for i in 0..=steps {
+            let a = start + sweep * i as f64 / steps as f64;
+            self.contour.push(Point::from_xy(
+                center.x + radius * a.cos(),
+                center.y + radius * a.sin(),
+            ));
+        }
+
+        let contour = std::mem::take(&mut self.contour);
+        self.add_contour(&contour);
+        self.contour = contour;
+    }
+
+    // Adds a contour making sure it is wound consistently with the others.
+    fn add_contour(&mut self, contour: &[Point]) {
+        if signed_area(contour) >= 0.0 {
+            self.polygon.add_contour(contour);
+        } else {
+            let reversed: Vec<Point> = contour.iter().rev().cloned().collect();
+            self.polygon.add_contour(&reversed);
+        }
+    }
+}
+
+#[inline]
+fn distance(a: &Point, b: &Point) -> f64 {
+    ((b.x - a.x) * (b.x - a.x) + (b.y - a.y) * (b.y - a.y)).sqrt()
+}
+
+#[inline]
+fn direction(a: &Point, b: &Point) -> Point {
+    let len = distance(a, b);
+    Point::from_xy((b.x - a.x) / len, (b.y - a.y) / len)
+}
+
+#[inline]
+fn normal(a: &Point, b: &Point) -> Point {
+    let d = direction(a, b);
+    Point::from_xy(-d.y, d.x)
+}
+
+// Enough steps that each chord is a couple of pixels long.
+fn arc_steps(radius: f64, sweep: f64) -> usize {
+    let steps = (sweep.abs() * radius / 2.0).ceil() as usize;
+    steps.max(4)
+}
+
+fn signed_area(contour: &[Point]) -> f64 {
+    let mut area = 0.0;
+    for (i, a) in contour.iter().enumerate() {
+        let b = &contour[(i + 1) % contour.len()];
+        area += a.x * b.y - b.x * a.y;
+    }
+    area / 2.0
+}
@@ -0,0 +1,82 @@
+use rendering::image::RGBA;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureFilter {
+    /// The closest texel. Blocky but exact for 1:1 mappings.
+    Nearest,
+    /// A weighted blend of the four closest texels.
+    Bilinear,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureWrap {
+    /// Coordinates outside the image tile it.
+    Repeat,
+    /// Coordinates outside the image use the nearest edge texel.
+    Clamp,
+}
+
+/// Reads colors from an RGBA image given coordinates in texel-space, where
+/// texel (i, j) covers [i, i+1) x [j, j+1) and its center is (i+0.5, j+0.5).
+#[derive(Debug, Clone, Copy)]
+pub struct TextureSampler {
+    pub filter: TextureFilter,
+    pub wrap: TextureWrap,
+}
+
+impl TextureSampler {
+    pub fn new(filter: TextureFilter, wrap: TextureWrap) -> Self {
+        Self { filter, wrap }
+    }
+
+    pub fn sample(&self, image: &RGBA, u: f64, v: f64) -> [u8; 4] {
+        let bounds = image.bounds();
+        let w = bounds.w() as i64;
+        let h = bounds.h() as i64;
+        if w == 0 || h == 0 {
+            return [0; 4];
+        }
+
+        match self.filter {
+            TextureFilter::Nearest => {
+                let x = self.wrap_coord(u.floor() as i64, w);
+                let y = self.wrap_coord(v.floor() as i64, h);
+                image.at(bounds.min.x + x, bounds.min.y + y)
+            }
+            TextureFilter::Bilinear => {
+                // Shift so that texel centers land on integers.
+                let su = u - 0.5;
+                let sv = v - 0.5;
+                let fu = su.floor();
+                let fv = sv.floor();
+                let tx = su - fu;
+                let ty = sv - fv;
+
+                let x0 = self.wrap_coord(fu as i64, w) + bounds.min.x;
+                let x1 = self.wrap_coord(fu as i64 + 1, w) + bounds.min.x;
+                let y0 = self.wrap_coord(fv as i64, h) + bounds.min.y;
+                let y1 = self.wrap_coord(fv as i64 + 1, h) + bounds.min.y;
+
+                let c00 = image.at(x0, y0);
+                let c10 = image.at(x1, y0);
+                let c01 = image.at(x0, y1);
+                let c11 = image.at(x1, y1);
+
+                let mut color = [0u8; 4];
+                for i in 0..4 {
+                    let top = c00[i] as f64 + (c10[i] as f64 - c00[i] as f64) * tx;
+                    let bot = c01[i] as f64 + (c11[i] as f64 - c01[i] as f64) * tx;
+                    color[i] = (top + (bot - top) * ty).round() as u8;
+                }
+                color
+            }
+        }
+    }
+
+    fn wrap_coord(&self, c: i64, size: i64) -> usize {
+        match self.wrap {
+            TextureWrap::Repeat => c.rem_euclid(size) as usize,
+            TextureWrap::Clamp => c.max(0).min(size - 1) as usize,
+        }
+    }
+}
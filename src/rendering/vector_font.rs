@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use geometry::point::Point;
 
 pub struct VectorLine(f64, f64, f64, f64);
@@ -23,8 +25,19 @@ impl VectorGlyph {
 
 // VectorFont is a collection of glyphs where each glyph is a collection
 // of lines.
+//
+// Fonts can be loaded from text files that add or replace glyphs of the
+// built-in font and adjust its spacing and scale:
+//
+//     # comment
+//     spacing 1.2 1.2
+//     scale 3.0
+//     glyph a        (or U+0061)
+//     line -0.5 0.0 0.5 0.0
 pub struct VectorFont {
     glyphs: Vec<VectorGlyph>,
+    // Loaded glyphs, they take precedence over the built-in ones.
+    custom: HashMap<char, VectorGlyph>,
 
     horizontal_offset: f64,
     vertical_offset: f64,
@@ -35,6 +48,7 @@ impl VectorFont {
     pub fn new() -> Self {
         let mut vf = Self {
             glyphs: Vec::new(),
+            custom: HashMap::new(),
             horizontal_offset: 1.2,
             vertical_offset: 1.2,
             scale: 3.0,
@@ -57,7 +71,52 @@ impl VectorFont {
         self.scale
     }
 
+    /// Parses a font file, see above. Errors name the offending line.
+    pub fn parse(text: &str) -> Result<VectorFont, String> {
+        let mut vf = VectorFont::new();
+        let mut current: Option<char> = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let keyword = fields.next().unwrap_or("");
+            let args: Vec<&str> = fields.collect();
+            let numbers: Vec<f64> = args.iter().filter_map(|a| a.parse().ok()).collect();
+            let error = |message: &str| format!("line {}: {}", i + 1, message);
+
+            match keyword {
+                "spacing" if numbers.len() == 2 && args.len() == 2 => {
+                    vf.horizontal_offset = numbers[0];
+                    vf.vertical_offset = numbers[1];
+                }
+                "scale" if numbers.len() == 1 && args.len() == 1 => vf.scale = numbers[0],
+                "glyph" if args.len() == 1 => {
+                    let c = parse_char(args[0]).ok_or_else(|| error("bad glyph character"))?;
+                    vf.custom.insert(c, VectorGlyph::new());
+                    current = Some(c);
+                }
+                "line" if numbers.len() == 4 && args.len() == 4 => {
+                    let glyph = current
+                        .and_then(|c| vf.custom.get_mut(&c))
+                        .ok_or_else(|| error("line before any glyph"))?;
+                    glyph.add_line(numbers[0], numbers[1], numbers[2], numbers[3]);
+                }
+                _ => return Err(error(&format!("can't parse '{}'", line))),
+            }
+        }
+
+        Ok(vf)
+    }
+
     pub fn get_glyph(&self, c: char) -> &VectorGlyph {
+        if let Some(glyph) = self.custom.get(&c) {
+            return glyph;
+        }
+
         match c {
             'A' => &self.glyphs[0],
             'B' => &self.glyphs[1],
@@ -491,3 +550,18 @@ impl VectorFont {
         vf.glyphs.push(glyph);
     }
 }
+
+// A single character or a code point written as U+XXXX.
+fn parse_char(arg: &str) -> Option<char> {
+    if arg.len() > 2 && (arg.starts_with("U+") || arg.starts_with("u+")) {
+        return u32::from_str_radix(&arg[2..], 16)
+            .ok()
+            .and_then(std::char::from_u32);
+    }
+
+    let mut chars = arg.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
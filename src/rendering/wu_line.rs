@@ -0,0 +1,79 @@
+// Xiaolin Wu's anti-aliased line algorithm.
+// https://en.wikipedia.org/wiki/Xiaolin_Wu%27s_line_algorithm
+//
+// Integer coordinates are pixel centers. Each pixel touched by the line is
+// handed to `plot` along with its coverage in (0, 1].
+pub fn wu_line<F>(x0: f64, y0: f64, x1: f64, y1: f64, mut plot: F)
+where
+    F: FnMut(i32, i32, f64),
+{
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+
+    // Work in the octant where x is the major axis.
+    let (mut x0, mut y0, mut x1, mut y1) = if steep {
+        (y0, x0, y1, x1)
+    } else {
+        (x0, y0, x1, y1)
+    };
+
+    if x0 > x1 {
+        std::mem::swap(&mut x0, &mut x1);
+        std::mem::swap(&mut y0, &mut y1);
+    }
+
+    let dx = x1 - x0;
+    let dy = y1 - y0;
+    let gradient = if dx == 0.0 { 1.0 } else { dy / dx };
+
+    let mut put = |x: i32, y: i32, coverage: f64| {
+        if coverage <= 0.0 {
+            return;
+        }
+        if steep {
+            plot(y, x, coverage);
+        } else {
+            plot(x, y, coverage);
+        }
+    };
+
+    // First endpoint
+    let x_end = x0.round();
+    let y_end = y0 + gradient * (x_end - x0);
+    let x_gap = rfpart(x0 + 0.5);
+    let x_pxl1 = x_end as i32;
+    let y_pxl1 = y_end.floor() as i32;
+    put(x_pxl1, y_pxl1, rfpart(y_end) * x_gap);
+    put(x_pxl1, y_pxl1 + 1, fpart(y_end) * x_gap);
+
+    let mut inter_y = y_end + gradient;
+
+    // Second endpoint
+    let x_end = x1.round();
+    let y_end = y1 + gradient * (x_end - x1);
+    let x_gap = fpart(x1 + 0.5);
+    let x_pxl2 = x_end as i32;
+    let y_pxl2 = y_end.floor() as i32;
+
+    if x_pxl2 != x_pxl1 {
+        put(x_pxl2, y_pxl2, rfpart(y_end) * x_gap);
+        put(x_pxl2, y_pxl2 + 1, fpart(y_end) * x_gap);
+    }
+
+    // Main loop
+    for x in (x_pxl1 + 1)..x_pxl2 {
+        let y = inter_y.floor() as i32;
+        put(x, y, rfpart(inter_y));
+        put(x, y + 1, fpart(inter_y));
+        inter_y += gradient;
+    }
+}
+
+#[inline]
+fn fpart(x: f64) -> f64 {
+    x - x.floor()
+}
+
+#[inline]
+fn rfpart(x: f64) -> f64 {
+    1.0 - fpart(x)
+}
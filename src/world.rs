@@ -3,14 +3,21 @@ extern crate sdl2;
 // use std::error::Error;
 // use std::fmt;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 
 use self::sdl2::render::WindowCanvas;
 use self::sdl2::Sdl;
 
+use assets::asset_manager::AssetManager;
+use config;
 use engine::core::Core;
+use engine::timing::rates::{self, Rates};
 use engine::timing::scheduler::Scheduler;
+use engine::timing::time_control::TimeControl;
+use engine::timing::timers::Timers;
 use nodes::{node::RNode, scenes::scene_manager::SceneManager};
+use rendering::clear_mode::ClearMode;
 
 // Game developer uses this callback to build their game.
 type BuildCallback = fn(&mut World) -> bool;
@@ -26,7 +33,20 @@ pub struct WorldProperties {
     pub title: String,
     pub config: String,
     pub vysnc_enabled: bool,
+    /// If false the background is never cleared, which is fine when a
+    /// Scene or Layer always covers the whole window.
     pub perform_clear: bool,
+    pub clear_mode: ClearMode,
+    pub updates_per_second: usize,
+    pub frames_per_second: usize,
+    /// Sleep away the rest of each frame period, see timing/rates.rs.
+    pub limit_frame_rate: bool,
+    pub max_catch_up: usize,
+    /// Action name to the SDL names of the keys bound to it, e.g.
+    /// "quit" -> ["Escape"].
+    pub bindings: BTreeMap<String, Vec<String>>,
+    pub show_stats: bool,
+    pub show_coordinates: bool,
 }
 
 impl WorldProperties {
@@ -41,9 +61,23 @@ impl WorldProperties {
             config: String::from("config.json"),
             vysnc_enabled: true,
             perform_clear: true,
+            clear_mode: ClearMode::checkerboard(),
+            updates_per_second: rates::UPDATES_PER_SECOND,
+            frames_per_second: rates::FRAMES_PER_SECOND,
+            limit_frame_rate: false,
+            max_catch_up: rates::MAX_CATCH_UP,
+            bindings: Self::default_bindings(),
+            show_stats: true,
+            show_coordinates: true,
         }
     }
 
+    pub fn default_bindings() -> BTreeMap<String, Vec<String>> {
+        let mut bindings = BTreeMap::new();
+        bindings.insert(String::from("quit"), vec![String::from("Escape")]);
+        bindings
+    }
+
     pub fn from(wp: &WorldProperties) -> Self {
         Self {
             window_width: wp.window_width,
@@ -55,6 +89,14 @@ impl WorldProperties {
             config: String::from(wp.config.to_owned()),
             vysnc_enabled: wp.vysnc_enabled,
             perform_clear: wp.perform_clear,
+            clear_mode: wp.clear_mode.clone(),
+            updates_per_second: wp.updates_per_second,
+            frames_per_second: wp.frames_per_second,
+            limit_frame_rate: wp.limit_frame_rate,
+            max_catch_up: wp.max_catch_up,
+            bindings: wp.bindings.clone(),
+            show_stats: wp.show_stats,
+            show_coordinates: wp.show_coordinates,
         }
     }
 
@@ -68,6 +110,14 @@ impl WorldProperties {
         self.config = String::from(wp.config.to_owned());
         self.vysnc_enabled = wp.vysnc_enabled;
         self.perform_clear = wp.perform_clear;
+        self.clear_mode = wp.clear_mode.clone();
+        self.updates_per_second = wp.updates_per_second;
+        self.frames_per_second = wp.frames_per_second;
+        self.limit_frame_rate = wp.limit_frame_rate;
+        self.max_catch_up = wp.max_catch_up;
+        self.bindings = wp.bindings.clone();
+        self.show_stats = wp.show_stats;
+        self.show_coordinates = wp.show_coordinates;
     }
 }
 
@@ -81,9 +131,9 @@ pub struct World {
 
     scene_manager: SceneManager,
     scheduler: Scheduler,
+    assets: AssetManager,
 
     context: Sdl,
-    config: String,
 
     id: usize,
 }
@@ -96,7 +146,8 @@ impl World {
     /// * `width` - Width of gui window
     /// * `height` - Height of gui window
     /// * `title` - Window's title bar text
-    /// * `config` - JSON configuration file. For example, contains the window background color
+    /// * `config` - JSON configuration file, see config/mod.rs. Its settings override the
+    ///   arguments. If it doesn't exist it is created from them. Empty for none.
     pub fn new(
         window_width: u32,
         window_height: u32,
@@ -114,6 +165,19 @@ impl World {
         wp.view_height = view_height;
         wp.view_centered = view_centered;
         wp.title = String::from(title);
+        wp.vysnc_enabled = vysnc_enabled;
+        wp.config = String::from(config);
+
+        if !config.is_empty() {
+            match config::load(config, &mut wp) {
+                Ok(true) => println!("Using config: {}", config),
+                Ok(false) => match config::save(config, &wp) {
+                    Ok(()) => println!("Created config: {}", config),
+                    Err(err) => return Err(format!("{}: {}", config, err)),
+                },
+                Err(err) => return Err(format!("{}: {}", config, err)),
+            }
+        }
 
         let sdl_context = match sdl2::init() {
             Ok(context) => context,
@@ -126,7 +190,7 @@ impl World {
         };
 
         let window = match video_subsystem
-            .window(title, window_width, window_height)
+            .window(&wp.title, wp.window_width as u32, wp.window_height as u32)
             .position_centered()
             .build()
         {
@@ -134,7 +198,7 @@ impl World {
             Err(build_error) => return Err(build_error.to_string()),
         };
 
-        let canvas = if vysnc_enabled {
+        let canvas = if wp.vysnc_enabled {
             match window.into_canvas().present_vsync().build() {
                 Ok(can) => Rc::new(RefCell::new(can)),
                 Err(err) => return Err(err.to_string()),
@@ -146,19 +210,22 @@ impl World {
             }
         };
 
+        let scheduler = Scheduler::new();
+
         let mut scene_manager = SceneManager::new(canvas);
         scene_manager.initialize(&wp);
+        scene_manager.set_timers(scheduler.timers());
 
         let mut core = Core::new();
-        core.initialize();
+        core.initialize(&wp)?;
 
         let e = Self {
             properties: wp,
             core: core,
             scene_manager: scene_manager,
-            scheduler: Scheduler::new(),
+            scheduler: scheduler,
+            assets: AssetManager::new(""),
             context: sdl_context,
-            config: config.to_string(),
             id: 0,
         };
 
@@ -170,11 +237,41 @@ impl World {
         self.id
     }
 
-    /// Configure using config json
+    /// Writes the current properties to the config file, creating it.
+    pub fn save_config(&self) -> Result<String, String> {
+        let path = &self.properties.config;
+        if path.is_empty() {
+            return Err(String::from("No config file"));
+        }
+
+        match config::save(path, &self.properties) {
+            Ok(()) => Ok(format!("Saved config: {}", path)),
+            Err(err) => Err(format!("{}: {}", path, err)),
+        }
+    }
+
+    /// Re-reads the config file and applies it. The window's size, title
+    /// and vsync were fixed when it was created so they are kept, changes
+    /// to them take effect on the next run.
     pub fn configure(&mut self) -> Result<String, String> {
-        println!("Using config: {}", self.config);
+        if self.properties.config.is_empty() {
+            return Ok(String::from("Configured"));
+        }
+
+        let path = self.properties.config.clone();
+        let mut wp = WorldProperties::from(&self.properties);
+        if let Err(err) = config::load(&path, &mut wp) {
+            return Err(format!("{}: {}", path, err));
+        }
+        wp.window_width = self.properties.window_width;
+        wp.window_height = self.properties.window_height;
+        wp.title = self.properties.title.clone();
+        wp.vysnc_enabled = self.properties.vysnc_enabled;
+
+        self.core.initialize(&wp)?;
+        self.scene_manager.initialize(&wp);
+        self.properties.set(&wp);
 
-        // Err(String::from("kaboom"))
         Ok(String::from("Configured"))
     }
 
@@ -193,8 +290,12 @@ impl World {
         }
 
         println!("Launching game...");
-        self.core
-            .core_loop(&self.context, &mut self.scene_manager, &mut self.scheduler)?;
+        self.core.core_loop(
+            &self.context,
+            &mut self.scene_manager,
+            &mut self.scheduler,
+            &mut self.assets,
+        )?;
 
         // Shutdown engine
 
@@ -236,6 +337,29 @@ impl World {
     pub fn get_scheduler(&mut self) -> &mut Scheduler {
         &mut self.scheduler
     }
+
+    /// Nodes can keep this to change the update and frame rates while the
+    /// game runs.
+    pub fn get_rates(&self) -> Rates {
+        self.core.rates()
+    }
+
+    /// Likewise for slow motion, fast forward and pausing.
+    pub fn get_time_control(&self) -> TimeControl {
+        self.scheduler.time()
+    }
+
+    /// And for scheduling callbacks.
+    pub fn get_timers(&self) -> Timers {
+        self.scheduler.timers()
+    }
+
+    // ---------------------------------------------------------------
+    // Assets
+    // ---------------------------------------------------------------
+    pub fn get_assets(&mut self) -> &mut AssetManager {
+        &mut self.assets
+    }
 }
 
 // #[derive(Debug)]
@@ -0,0 +1,431 @@
+extern crate ranger;
+
+use ranger::animation::clip::{AnimationClip, NodeTracks, PlayMode};
+use ranger::animation::keyframe::{KeyInterpolation, Track};
+use ranger::animation::motion::{AngularMotion, AngularUnits, ColorMotion, LinearMotion, Motion};
+use ranger::animation::sprite::{SpriteAnimator, SpriteClip};
+use ranger::geometry::point::Point;
+use ranger::math::interpolation::Interpolation;
+use ranger::rendering::color::Palette;
+use std::f64::consts::PI;
+
+const EPSILON: f64 = 0.000001;
+
+fn equal(a: f64, b: f64) -> bool {
+    (a - b).abs() < EPSILON
+}
+
+#[test]
+fn angle_wrap() {
+    assert!(equal(Interpolation::wrap_angle(370.0, 360.0), 10.0));
+    assert!(equal(Interpolation::wrap_angle(-10.0, 360.0), 350.0));
+    assert!(equal(Interpolation::wrap_angle(720.0, 360.0), 0.0));
+    assert!(equal(Interpolation::wrap_angle(-PI / 2.0, 2.0 * PI), 1.5 * PI));
+}
+
+#[test]
+fn angle_shortest_path_degrees() {
+    // 350 -> 10 should pass through 0, not 180.
+    assert!(equal(Interpolation::lerp_degrees(350.0, 10.0, 0.5), 0.0));
+    assert!(equal(Interpolation::lerp_degrees(350.0, 10.0, 0.25), 355.0));
+    assert!(equal(Interpolation::lerp_degrees(10.0, 350.0, 0.75), 355.0));
+    assert!(equal(Interpolation::lerp_degrees(0.0, 90.0, 0.5), 45.0));
+}
+
+#[test]
+fn angle_shortest_path_radians() {
+    let from = 1.9 * PI;
+    let to = 0.1 * PI;
+    assert!(equal(Interpolation::lerp_radians(from, to, 0.5), 0.0));
+    assert!(equal(
+        Interpolation::shortest_angle_delta(from, to, 2.0 * PI),
+        0.2 * PI
+    ));
+}
+
+#[test]
+fn angular_motion_wraps_without_jump() {
+    let mut motion = AngularMotion::new();
+    motion.enable_auto_wrap(true);
+    motion.set(350.0, 355.0);
+    motion.set_step_value(5.0);
+
+    let mut previous = motion.interpolate(1.0);
+    for _ in 0..10 {
+        motion.update(1.0);
+        for i in 1..=4 {
+            let value = motion.interpolate(i as f64 / 4.0);
+            assert!((0.0..360.0).contains(&value));
+            // Each sample advances by 1.25 degrees modulo a revolution.
+            let delta = Interpolation::shortest_angle_delta(previous, value, 360.0);
+            assert!(equal(delta, 1.25), "delta {}", delta);
+            previous = value;
+        }
+    }
+}
+
+#[test]
+fn angular_motion_wraps_negative_steps() {
+    let mut motion = AngularMotion::new();
+    motion.set_units(AngularUnits::Radians);
+    motion.enable_auto_wrap(true);
+    motion.set_step_value(-0.5);
+
+    for _ in 0..20 {
+        motion.update(1.0);
+        let value = motion.interpolate(0.5);
+        assert!((0.0..2.0 * PI).contains(&value));
+    }
+}
+
+#[test]
+fn angular_motion_shortest_path() {
+    let mut motion = AngularMotion::new();
+    motion.enable_shortest_path(true);
+    motion.set(350.0, 10.0);
+    assert!(equal(motion.interpolate(0.5), 0.0));
+}
+
+#[test]
+fn linear_motion_scalar() {
+    let mut motion: LinearMotion<f64> = LinearMotion::new(0.0, 2.0);
+    motion.update(1.0);
+    assert!(equal(motion.interpolate(0.5), 1.0));
+    motion.update(1.0);
+    assert!(equal(motion.interpolate(0.0), 2.0));
+    assert!(equal(motion.interpolate(1.0), 4.0));
+}
+
+#[test]
+fn linear_motion_point() {
+    let mut motion = LinearMotion::new(Point::from_xy(1.0, 1.0), Point::from_xy(2.0, -2.0));
+    motion.update(1.0);
+    let p = motion.interpolate(0.5);
+    assert!(equal(p.x, 2.0));
+    assert!(equal(p.y, 0.0));
+}
+
+#[test]
+fn motion_trait_objects() {
+    // Nodes can hold any motion by its value type.
+    let mut motions: Vec<Box<dyn Motion<Value = f64>>> = vec![
+        Box::new(LinearMotion::new(0.0, 1.0)),
+        Box::new(AngularMotion::new()),
+    ];
+    for m in motions.iter_mut() {
+        m.update(1.0);
+        m.interpolate(0.5);
+    }
+}
+
+#[test]
+fn color_motion_fades() {
+    let mut motion = ColorMotion::new(Palette::BLACK(), Palette::WHITE(255), 100.0);
+    assert_eq!(motion.interpolate(1.0), Palette::BLACK());
+
+    motion.update(50.0);
+    let c = motion.interpolate(1.0);
+    assert_eq!(c.r, 128);
+    let c = motion.interpolate(0.5);
+    assert_eq!(c.r, 64);
+
+    motion.update(50.0);
+    assert_eq!(motion.interpolate(1.0), Palette::WHITE(255));
+    assert!(motion.is_complete());
+
+    // Further updates hold the final color.
+    motion.update(50.0);
+    assert_eq!(motion.interpolate(0.5), Palette::WHITE(255));
+}
+
+#[test]
+fn color_motion_loops() {
+    let mut motion = ColorMotion::new(Palette::BLACK(), Palette::WHITE(255), 10.0);
+    motion.enable_looping(true);
+    motion.update(10.0);
+    assert!(!motion.is_complete());
+    motion.update(5.0);
+    assert_eq!(motion.interpolate(1.0).r, 128);
+}
+
+// ------------------------------------------------------------------
+// Keyframes and clips
+// ------------------------------------------------------------------
+#[test]
+fn track_sample_interpolations() {
+    let track = Track::new()
+        .with_key(0.0, 0.0, KeyInterpolation::Linear)
+        .with_key(100.0, 10.0, KeyInterpolation::Step)
+        .with_key(200.0, 20.0, KeyInterpolation::Linear);
+
+    assert!(equal(track.sample(-10.0).unwrap(), 0.0));
+    assert!(equal(track.sample(50.0).unwrap(), 5.0));
+    assert!(equal(track.sample(100.0).unwrap(), 10.0));
+    // Step holds until the next key.
+    assert!(equal(track.sample(199.0).unwrap(), 10.0));
+    assert!(equal(track.sample(200.0).unwrap(), 20.0));
+    assert!(equal(track.sample(500.0).unwrap(), 20.0));
+    assert!(equal(track.duration(), 200.0));
+}
+
+#[test]
+fn track_keys_are_ordered() {
+    let mut track = Track::new();
+    track.add_key(100.0, 1.0, KeyInterpolation::Linear);
+    track.add_key(0.0, 0.0, KeyInterpolation::Linear);
+    track.add_key(100.0, 2.0, KeyInterpolation::Linear);
+
+    assert_eq!(track.keys().len(), 2);
+    assert!(equal(track.keys()[0].time, 0.0));
+    assert!(equal(track.keys()[1].value, 2.0));
+}
+
+#[test]
+fn track_bezier_endpoints() {
+    let track = Track::new()
+        .with_key(0.0, 0.0, KeyInterpolation::Bezier(0.42, 0.0, 0.58, 1.0))
+        .with_key(10.0, 100.0, KeyInterpolation::Linear);
+
+    assert!(equal(track.sample(5.0).unwrap(), 50.0));
+    // Ease-in-out is slower than linear near the start.
+    assert!(track.sample(2.0).unwrap() < 20.0);
+}
+
+#[test]
+fn track_empty_samples_none() {
+    let track: Track<f64> = Track::new();
+    assert!(track.sample(0.0).is_none());
+}
+
+fn build_clip(mode: PlayMode) -> AnimationClip {
+    let mut tracks = NodeTracks::new("layer/ship");
+    tracks.position = Some(
+        Track::new()
+            .with_key(0.0, Point::from_xy(0.0, 0.0), KeyInterpolation::Linear)
+            .with_key(100.0, Point::from_xy(100.0, 50.0), KeyInterpolation::Linear),
+    );
+    tracks.visible = Some(
+        Track::new()
+            .with_key(0.0, true, KeyInterpolation::Linear)
+            .with_key(50.0, false, KeyInterpolation::Linear),
+    );
+    tracks.color = Some(
+        Track::new()
+            .with_key(0.0, Palette::BLACK(), KeyInterpolation::Linear)
+            .with_key(100.0, Palette::WHITE(255), KeyInterpolation::Linear),
+    );
+
+    let mut clip = AnimationClip::new("fly");
+    clip.add_tracks(tracks);
+    clip.add_event(25.0, "quarter");
+    clip.add_event(100.0, "end");
+    clip.set_mode(mode);
+    clip
+}
+
+#[test]
+fn clip_plays_once() {
+    let mut clip = build_clip(PlayMode::Once);
+    let mut fired = Vec::new();
+    assert!(equal(clip.duration(), 100.0));
+
+    // Not playing yet
+    clip.advance(10.0, &mut fired);
+    assert!(equal(clip.time(), 0.0));
+
+    clip.play();
+    clip.advance(40.0, &mut fired);
+    assert_eq!(fired, vec!["quarter".to_string()]);
+
+    let pose = clip.sample(0);
+    let p = pose.position.unwrap();
+    assert!(equal(p.x, 40.0));
+    assert!(equal(p.y, 20.0));
+    assert_eq!(pose.visible, Some(true));
+    assert!(pose.rotation.is_none());
+
+    clip.advance(100.0, &mut fired);
+    assert_eq!(fired.last().unwrap(), "end");
+    assert!(!clip.is_playing());
+    assert!(equal(clip.time(), 100.0));
+    assert_eq!(clip.sample(0).visible, Some(false));
+    assert_eq!(clip.sample(0).color, Some(Palette::WHITE(255)));
+}
+
+#[test]
+fn clip_loops_and_fires_each_pass() {
+    let mut clip = build_clip(PlayMode::Loop);
+    let mut fired = Vec::new();
+    clip.play();
+
+    clip.advance(230.0, &mut fired);
+    assert!(equal(clip.time(), 30.0));
+    assert!(clip.is_playing());
+    assert_eq!(
+        fired,
+        ["quarter", "end", "quarter", "end", "quarter"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>()
+    );
+}
+
+#[test]
+fn clip_ping_pongs() {
+    let mut clip = build_clip(PlayMode::PingPong);
+    let mut fired = Vec::new();
+    clip.play();
+
+    clip.advance(120.0, &mut fired);
+    assert!(!clip.is_forward());
+    assert!(equal(clip.time(), 80.0));
+
+    fired.clear();
+    clip.advance(70.0, &mut fired);
+    assert!(equal(clip.time(), 10.0));
+    assert_eq!(fired, vec!["quarter".to_string()]);
+
+    clip.advance(20.0, &mut fired);
+    assert!(clip.is_forward());
+    assert!(equal(clip.time(), 10.0));
+}
+
+#[test]
+fn clip_speed_scales_time() {
+    let mut clip = build_clip(PlayMode::Once);
+    let mut fired = Vec::new();
+    clip.set_speed(0.5);
+    clip.play();
+    clip.advance(40.0, &mut fired);
+    assert!(equal(clip.time(), 20.0));
+    assert!(fired.is_empty());
+}
+
+#[test]
+fn clip_ignores_non_finite_dt() {
+    for &mode in &[PlayMode::Once, PlayMode::Loop, PlayMode::PingPong] {
+        let mut clip = build_clip(mode);
+        let mut fired = Vec::new();
+        clip.play();
+        clip.advance(30.0, &mut fired);
+
+        clip.advance(f64::INFINITY, &mut fired);
+        clip.advance(f64::NAN, &mut fired);
+        assert!(equal(clip.time(), 30.0));
+        assert_eq!(fired, vec!["quarter".to_string()]);
+    }
+}
+
+#[test]
+fn clip_skips_whole_cycles_of_a_huge_dt() {
+    let mut clip = build_clip(PlayMode::Loop);
+    let mut fired = Vec::new();
+    clip.play();
+
+    // Returns promptly, landing where stepping would have.
+    clip.advance(1.0e15 + 30.0, &mut fired);
+    assert!(equal(clip.time(), 30.0));
+    assert!(clip.is_playing());
+    // Events fire per cycle, up to a limit.
+    assert!(!fired.is_empty() && fired.len() <= 40);
+
+    let mut clip = build_clip(PlayMode::PingPong);
+    fired.clear();
+    clip.play();
+    clip.advance(2.0e15 + 330.0, &mut fired);
+    assert!(!clip.is_forward());
+    assert!(equal(clip.time(), 70.0));
+}
+
+#[test]
+fn clip_skipped_cycles_fire_like_stepped_ones() {
+    let mut stepped = build_clip(PlayMode::PingPong);
+    let mut skipped = build_clip(PlayMode::PingPong);
+    let mut stepped_fired = Vec::new();
+    let mut skipped_fired = Vec::new();
+    stepped.play();
+    skipped.play();
+    stepped.advance(30.0, &mut stepped_fired);
+    skipped.advance(30.0, &mut skipped_fired);
+
+    for _ in 0..7 {
+        stepped.advance(90.0, &mut stepped_fired);
+    }
+    skipped.advance(630.0, &mut skipped_fired);
+
+    assert_eq!(skipped_fired, stepped_fired);
+    assert_eq!(skipped.is_forward(), stepped.is_forward());
+    assert!(equal(skipped.time(), stepped.time()));
+}
+
+// ------------------------------------------------------------------
+// Sprite frame animation
+// ------------------------------------------------------------------
+fn build_animator() -> SpriteAnimator {
+    let mut animator = SpriteAnimator::new();
+    animator.add_clip(SpriteClip::from_range("walk", 0, 4, 100.0, true));
+    animator.add_clip(
+        SpriteClip::new("attack", false)
+            .with_frame(8, 50.0)
+            .with_frame(9, 150.0),
+    );
+    animator
+}
+
+#[test]
+fn sprite_animator_idle_without_clip() {
+    let mut animator = build_animator();
+    assert!(animator.current_frame().is_none());
+    assert!(!animator.update(1000.0));
+    assert!(!animator.play("run"));
+}
+
+#[test]
+fn sprite_animator_loops() {
+    let mut animator = build_animator();
+    assert!(animator.play("walk"));
+    assert_eq!(animator.current_frame(), Some(0));
+
+    assert!(!animator.update(99.0));
+    assert!(animator.update(1.0));
+    assert_eq!(animator.current_frame(), Some(1));
+
+    // Skip past the end and wrap
+    animator.update(350.0);
+    assert_eq!(animator.current_frame(), Some(0));
+    assert!(animator.is_playing());
+    assert!(!animator.is_finished());
+}
+
+#[test]
+fn sprite_animator_per_frame_durations() {
+    let mut animator = build_animator();
+    animator.play("attack");
+    assert_eq!(animator.current_frame(), Some(8));
+
+    animator.update(60.0);
+    assert_eq!(animator.current_frame(), Some(9));
+
+    animator.update(139.0);
+    assert!(animator.is_playing());
+
+    animator.update(1.0);
+    // Holds the last frame
+    assert_eq!(animator.current_frame(), Some(9));
+    assert!(!animator.is_playing());
+    assert!(animator.is_finished());
+    assert!(equal(animator.clip("attack").unwrap().duration(), 200.0));
+}
+
+#[test]
+fn sprite_animator_play_is_idempotent() {
+    let mut animator = build_animator();
+    animator.play("walk");
+    animator.update(150.0);
+    animator.play("walk");
+    assert_eq!(animator.current_frame(), Some(1));
+
+    animator.play("attack");
+    assert_eq!(animator.current_clip().unwrap(), "attack");
+    assert_eq!(animator.current_frame(), Some(8));
+}
@@ -0,0 +1,135 @@
+extern crate ranger;
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use ranger::assets::asset_manager::{AssetError, AssetManager, Handle};
+use ranger::geometry::mesh::Mesh;
+use ranger::rendering::vector_font::VectorFont;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+const SQUARE: &str = "# unit square\nv 0 0\nv 1 0\nv 1 1\nv 0 1 0\nf 1 2 3 4\n";
+
+#[test]
+fn asset_cached_by_type_and_path() {
+    let dir = temp_dir("ranger_asset_cache");
+    fs::write(dir.join("square.obj"), SQUARE).unwrap();
+    let mut assets = AssetManager::new(&dir);
+
+    let a: Handle<Mesh> = assets.load("square.obj").unwrap();
+    let b: Handle<Mesh> = assets.load("square.obj").unwrap();
+    assert!(a.ptr_eq(&b));
+    assert_eq!(a.key(), "square.obj");
+    assert_eq!(a.borrow().vertices.len(), 4);
+    assert_eq!(a.borrow().triangles, vec![[0, 1, 2], [0, 2, 3]]);
+
+    // The same file as another type is a separate asset.
+    let text: Handle<String> = assets.load("square.obj").unwrap();
+    assert_eq!(*text.borrow(), SQUARE);
+    assert_eq!(assets.len(), 2);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn asset_unloads_with_last_handle() {
+    let mut assets = AssetManager::new("");
+    let a = assets.insert("data", vec![1u8, 2, 3]);
+    let b = a.clone();
+    assert!(assets.is_loaded::<Vec<u8>>("data"));
+
+    drop(a);
+    assert!(assets.get::<Vec<u8>>("data").unwrap().ptr_eq(&b));
+    drop(b);
+    assert!(!assets.is_loaded::<Vec<u8>>("data"));
+    assert!(assets.is_empty());
+}
+
+#[test]
+fn asset_load_errors() {
+    let dir = temp_dir("ranger_asset_errors");
+    fs::write(dir.join("bad.obj"), "v 0 0\nv 1 0\nf 1 2 3\n").unwrap();
+    let mut assets = AssetManager::new(&dir);
+
+    match assets.load::<Mesh>("missing.obj") {
+        Err(AssetError::Io(_)) => (),
+        other => panic!("expected Io, got {:?}", other.err()),
+    }
+    match assets.load::<Mesh>("bad.obj") {
+        Err(AssetError::Parse(ref what)) => assert!(what.contains("line 3"), "{}", what),
+        other => panic!("expected Parse, got {:?}", other.err()),
+    }
+    assert!(assets.is_empty());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn asset_reloads_changed_files() {
+    let dir = temp_dir("ranger_asset_reload");
+    let path = dir.join("level.txt");
+    fs::write(&path, "one").unwrap();
+    let mut assets = AssetManager::new(&dir);
+    assets.set_watching(true);
+
+    let level: Handle<String> = assets.load("level.txt").unwrap();
+    assert!(assets.reload_changed().is_empty());
+
+    let touch = |text: &str, secs: u64| {
+        fs::write(&path, text).unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(secs))
+            .unwrap();
+    };
+
+    touch("two", 10);
+    let results = assets.reload_changed();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, "level.txt");
+    assert!(results[0].1.is_ok());
+    assert_eq!(*level.borrow(), "two");
+    assert_eq!(level.version(), 1);
+
+    touch("three", 20);
+    assert!(assets.reload::<String>("level.txt").unwrap());
+    assert_eq!(*level.borrow(), "three");
+    assert_eq!(level.version(), 2);
+
+    // A failed reload keeps the previous value.
+    let mesh_path = dir.join("square.obj");
+    fs::write(&mesh_path, SQUARE).unwrap();
+    let mesh: Handle<Mesh> = assets.load("square.obj").unwrap();
+    fs::write(&mesh_path, "f 1 2\n").unwrap();
+    let file = fs::File::options().write(true).open(&mesh_path).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(30))
+        .unwrap();
+    let results = assets.reload_changed();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].1.is_err());
+    assert_eq!(mesh.borrow().vertices.len(), 4);
+    assert_eq!(mesh.version(), 0);
+    // Not retried until the file changes again.
+    assert!(assets.reload_changed().is_empty());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn asset_font_parse() {
+    let text = "spacing 1.5 2.0\nscale 4\nglyph U+0041\nline 0 0 1 1\nline 1 1 2 0\n";
+    let font = VectorFont::parse(text).unwrap();
+    // Two lines, as pairs of points.
+    assert_eq!(font.get_glyph('A').get_lines().len(), 4);
+    // The other built in glyphs are still there.
+    assert!(!font.get_glyph('B').get_lines().is_empty());
+
+    assert!(VectorFont::parse("line 0 0 1 1\n").is_err());
+    assert!(VectorFont::parse("glyph ab\n").is_err());
+}
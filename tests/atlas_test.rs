@@ -0,0 +1,200 @@
+extern crate ranger;
+
+use ranger::animation::sprite::SpriteClip;
+use ranger::rendering::atlas::{Atlas, AtlasError, AtlasMetadata, AtlasPacker, AtlasRegion};
+use ranger::rendering::image::{ImgRectangle, RGBA};
+
+// Every pixel is distinct so misplaced or misrotated copies show up.
+fn tile(w: usize, h: usize, seed: u8) -> RGBA {
+    let mut img = RGBA::new(w, h);
+    for y in 0..h {
+        for x in 0..w {
+            img.set(x, y, [seed, x as u8, y as u8, 255]);
+        }
+    }
+    img
+}
+
+fn same(a: &RGBA, b: &RGBA) -> bool {
+    a.bounds().w() == b.bounds().w()
+        && a.bounds().h() == b.bounds().h()
+        && (0..a.bounds().h()).all(|y| (0..a.bounds().w()).all(|x| a.at(x, y) == b.at(x, y)))
+}
+
+fn overlap(a: &AtlasRegion, b: &AtlasRegion, padding: usize) -> bool {
+    a.page == b.page
+        && a.rect.min.x < b.rect.max.x + padding
+        && b.rect.min.x < a.rect.max.x + padding
+        && a.rect.min.y < b.rect.max.y + padding
+        && b.rect.min.y < a.rect.max.y + padding
+}
+
+fn sizes() -> Vec<(usize, usize)> {
+    vec![
+        (30, 10),
+        (12, 12),
+        (8, 40),
+        (20, 20),
+        (5, 5),
+        (16, 24),
+        (40, 8),
+        (9, 3),
+        (25, 14),
+        (3, 30),
+    ]
+}
+
+#[test]
+fn atlas_packs_without_overlap() {
+    for &rotation in [false, true].iter() {
+        let mut packer = AtlasPacker::new(64, 64);
+        packer.set_padding(2);
+        packer.set_allow_rotation(rotation);
+        let images: Vec<RGBA> = sizes()
+            .iter()
+            .enumerate()
+            .map(|(i, &(w, h))| tile(w, h, i as u8))
+            .collect();
+        for (i, img) in images.iter().enumerate() {
+            packer.add(&format!("tile {}", i), img.clone());
+        }
+
+        let atlas = packer.pack().unwrap();
+        let regions = atlas.regions();
+        assert_eq!(regions.len(), images.len());
+
+        for (i, region) in regions.iter().enumerate() {
+            // Regions keep the order images were added.
+            assert_eq!(region.name, format!("tile {}", i));
+            assert_eq!(region.size(), sizes()[i]);
+            if !rotation {
+                assert!(!region.rotated);
+            }
+
+            let page = &atlas.pages()[region.page];
+            assert!(region.rect.max.x <= page.bounds().w());
+            assert!(region.rect.max.y <= page.bounds().h());
+            assert!(page.bounds().w() <= 64 && page.bounds().h() <= 64);
+
+            for other in regions[i + 1..].iter() {
+                assert!(!overlap(region, other, 2), "{:?} {:?}", region, other);
+            }
+
+            let extracted = atlas.extract(&region.name).unwrap();
+            assert!(same(&extracted, &images[i]), "{}", region.name);
+        }
+    }
+}
+
+#[test]
+fn atlas_rotates_to_fit() {
+    let mut packer = AtlasPacker::new(10, 40);
+    packer.add("wide", tile(40, 10, 1));
+
+    match packer.pack() {
+        Err(AtlasError::TooLarge(ref name)) if name == "wide" => (),
+        Err(err) => panic!("expected TooLarge, got {}", err),
+        Ok(_) => panic!("expected TooLarge"),
+    }
+
+    packer.set_allow_rotation(true);
+    let atlas = packer.pack().unwrap();
+    let region = atlas.region("wide").unwrap();
+    assert!(region.rotated);
+    assert_eq!((region.rect.w(), region.rect.h()), (10, 40));
+    assert!(same(&atlas.extract("wide").unwrap(), &tile(40, 10, 1)));
+}
+
+#[test]
+fn atlas_spills_onto_more_pages() {
+    let mut packer = AtlasPacker::new(32, 32);
+    for i in 0..5 {
+        packer.add(&format!("big{}", i), tile(20, 20, i));
+    }
+
+    let atlas = packer.pack().unwrap();
+    assert_eq!(atlas.pages().len(), 5);
+    for page in atlas.pages().iter() {
+        // Cropped to what's used.
+        assert_eq!((page.bounds().w(), page.bounds().h()), (20, 20));
+    }
+}
+
+#[test]
+fn atlas_metadata_round_trip() {
+    let metadata = AtlasMetadata {
+        pages: vec![String::from("a-0.tga"), String::from("a-1.tga")],
+        regions: vec![
+            AtlasRegion {
+                name: String::from("hero walk 0"),
+                page: 1,
+                rect: ImgRectangle::from_xywh(3, 4, 10, 20),
+                rotated: true,
+            },
+            AtlasRegion {
+                name: String::from("coin"),
+                page: 0,
+                rect: ImgRectangle::from_xywh(0, 0, 8, 8),
+                rotated: false,
+            },
+        ],
+    };
+
+    let text = metadata.to_string();
+    assert!(text.contains("region 1 3 4 10 20 1 hero walk 0"));
+    assert_eq!(AtlasMetadata::parse(&text).unwrap(), metadata);
+
+    let bad = [
+        "page a.tga\nregion 0 1 2 3\n",
+        "page a.tga\nregion 1 0 0 1 1 0 x\n",
+        "page a.tga\nregion 0 0 0 1 1 0\n",
+        "sprite 0\n",
+    ];
+    for text in bad.iter() {
+        match AtlasMetadata::parse(text) {
+            Err(AtlasError::Parse { line, .. }) => assert!(line >= 1),
+            other => panic!("expected a parse error for {:?}, got {:?}", text, other),
+        }
+    }
+}
+
+#[test]
+fn atlas_save_load_and_sheets() {
+    let mut packer = AtlasPacker::new(64, 64);
+    packer.set_allow_rotation(true);
+    for i in 0..12u8 {
+        packer.add(&format!("walk_{}", i), tile(6, 9, i));
+    }
+    packer.add("banner", tile(50, 7, 99));
+    let atlas = packer.pack().unwrap();
+
+    let dir = std::env::temp_dir().join("ranger_atlas_test");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("sprites.atlas");
+    atlas.save(&path).unwrap();
+
+    let loaded = Atlas::load(&path).unwrap();
+    assert_eq!(loaded.regions(), atlas.regions());
+    for region in atlas.regions().iter() {
+        assert!(same(
+            &loaded.extract(&region.name).unwrap(),
+            &atlas.extract(&region.name).unwrap()
+        ));
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let sheets = loaded.into_sheets();
+    assert_eq!(sheets.len(), 1);
+    let sheet = &sheets[0];
+    assert_eq!(sheet.frame_count(), 13);
+
+    let banner = sheet.frame_index("banner").unwrap();
+    assert_eq!(sheet.frame_size(banner), Some((50, 7)));
+
+    let clip = SpriteClip::from_prefix("walk", sheet, "walk_", 100.0, true);
+    let indices: Vec<usize> = clip.frames().iter().map(|f| f.index).collect();
+    let expected: Vec<usize> = (0..12)
+        .map(|i| sheet.frame_index(&format!("walk_{}", i)).unwrap())
+        .collect();
+    assert_eq!(indices, expected);
+}
@@ -0,0 +1,352 @@
+extern crate ranger;
+
+use std::path::Path;
+
+use ranger::rendering::codecs::{self, bmp, pnm, tga, ImageError, ImageFormat};
+use ranger::rendering::image::RGBA;
+
+fn sample(w: usize, h: usize) -> RGBA {
+    let mut img = RGBA::new(w, h);
+    for y in 0..h {
+        for x in 0..w {
+            let a = if x == 0 { 128 } else { 255 };
+            img.set(x, y, [(x * 40) as u8, (y * 60) as u8, 200, a]);
+        }
+    }
+    // A run for the TGA encoder.
+    for x in 0..w {
+        img.set(x, h - 1, [10, 20, 30, 255]);
+    }
+    img
+}
+
+fn assert_same(a: &RGBA, b: &RGBA) {
+    assert_eq!(a.bounds().w(), b.bounds().w());
+    assert_eq!(a.bounds().h(), b.bounds().h());
+    for y in 0..a.bounds().h() {
+        for x in 0..a.bounds().w() {
+            assert_eq!(a.at(x, y), b.at(x, y), "pixel {},{}", x, y);
+        }
+    }
+}
+
+fn le16(v: u16) -> [u8; 2] {
+    v.to_le_bytes()
+}
+
+fn le32(v: u32) -> [u8; 4] {
+    v.to_le_bytes()
+}
+
+// A 40 byte info header bitmap with the given pixel data.
+fn bmp_file(w: i32, h: i32, bpp: u16, palette: &[u8], pixels: &[u8]) -> Vec<u8> {
+    let offset = 14 + 40 + palette.len();
+    let mut out = Vec::new();
+    out.extend_from_slice(b"BM");
+    out.extend_from_slice(&le32((offset + pixels.len()) as u32));
+    out.extend_from_slice(&le32(0));
+    out.extend_from_slice(&le32(offset as u32));
+    out.extend_from_slice(&le32(40));
+    out.extend_from_slice(&le32(w as u32));
+    out.extend_from_slice(&le32(h as u32));
+    out.extend_from_slice(&le16(1));
+    out.extend_from_slice(&le16(bpp));
+    out.extend_from_slice(&le32(0));
+    out.extend_from_slice(&le32(pixels.len() as u32));
+    out.extend_from_slice(&[0; 8]);
+    out.extend_from_slice(&le32((palette.len() / 4) as u32));
+    out.extend_from_slice(&le32(0));
+    out.extend_from_slice(palette);
+    out.extend_from_slice(pixels);
+    out
+}
+
+#[test]
+fn codec_bmp_round_trip() {
+    let img = sample(5, 3);
+
+    let decoded = bmp::decode(&bmp::encode(&img)).unwrap();
+    assert_same(&img, &decoded);
+
+    // 24 bit drops alpha.
+    let decoded = bmp::decode(&bmp::encode_rgb(&img)).unwrap();
+    assert_eq!(decoded.at(0, 0), [0, 0, 200, 255]);
+    assert_eq!(decoded.at(4, 1), [160, 60, 200, 255]);
+}
+
+#[test]
+fn codec_bmp_bottom_up_24_bit() {
+    // 2x2, rows padded from 6 to 8 bytes, bottom row first.
+    let pixels = [
+        0, 0, 255, 0, 255, 0, 0, 0, // red, green
+        255, 0, 0, 255, 255, 255, 0, 0, // blue, white
+    ];
+    let img = bmp::decode(&bmp_file(2, 2, 24, &[], &pixels)).unwrap();
+
+    assert_eq!(img.at(0, 0), [0, 0, 255, 255]);
+    assert_eq!(img.at(1, 0), [255, 255, 255, 255]);
+    assert_eq!(img.at(0, 1), [255, 0, 0, 255]);
+    assert_eq!(img.at(1, 1), [0, 255, 0, 255]);
+
+    // A negative height is top-down.
+    let img = bmp::decode(&bmp_file(2, -2, 24, &[], &pixels)).unwrap();
+    assert_eq!(img.at(0, 0), [255, 0, 0, 255]);
+}
+
+#[test]
+fn codec_bmp_8_bit_palette() {
+    let palette = [0, 0, 0, 0, 255, 0, 0, 0, 0, 0, 255, 0];
+    let pixels = [2, 1, 0, 0];
+    let img = bmp::decode(&bmp_file(3, 1, 8, &palette, &pixels)).unwrap();
+
+    assert_eq!(img.at(0, 0), [255, 0, 0, 255]);
+    assert_eq!(img.at(1, 0), [0, 0, 255, 255]);
+    assert_eq!(img.at(2, 0), [0, 0, 0, 255]);
+
+    let bad = [3, 1, 0, 0];
+    match bmp::decode(&bmp_file(3, 1, 8, &palette, &bad)) {
+        Err(ImageError::Malformed(_)) => (),
+        other => panic!("expected Malformed, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn codec_bmp_32_bit_without_alpha_is_opaque() {
+    let pixels = [1, 2, 3, 0, 4, 5, 6, 0];
+    let img = bmp::decode(&bmp_file(2, 1, 32, &[], &pixels)).unwrap();
+    assert_eq!(img.at(0, 0), [3, 2, 1, 255]);
+    assert_eq!(img.at(1, 0), [6, 5, 4, 255]);
+}
+
+#[test]
+fn codec_bmp_errors() {
+    let data = bmp::encode(&sample(4, 4));
+
+    match bmp::decode(&data[..data.len() - 1]) {
+        Err(ImageError::Truncated) => (),
+        other => panic!("expected Truncated, got {:?}", other.err()),
+    }
+    match bmp::decode(&data[..20]) {
+        Err(ImageError::Truncated) => (),
+        other => panic!("expected Truncated, got {:?}", other.err()),
+    }
+    match bmp::decode(&bmp_file(2, 2, 16, &[], &[0; 8])) {
+        Err(ImageError::Unsupported(_)) => (),
+        other => panic!("expected Unsupported, got {:?}", other.err()),
+    }
+    match bmp::decode(b"XX not a bitmap") {
+        Err(ImageError::Malformed(_)) => (),
+        other => panic!("expected Malformed, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn codec_tga_round_trip() {
+    let img = sample(6, 4);
+
+    let raw = tga::encode(&img, false).unwrap();
+    let rle = tga::encode(&img, true).unwrap();
+    assert!(rle.len() < raw.len());
+
+    assert_same(&img, &tga::decode(&raw).unwrap());
+    assert_same(&img, &tga::decode(&rle).unwrap());
+
+    // Runs longer than a packet.
+    let mut wide = RGBA::new(300, 2);
+    wide.fill([1, 2, 3, 4]);
+    wide.set(150, 1, [9, 9, 9, 9]);
+    assert_same(
+        &wide,
+        &tga::decode(&tga::encode(&wide, true).unwrap()).unwrap(),
+    );
+
+    match tga::encode(&RGBA::new(65536, 1), true) {
+        Err(ImageError::Unsupported(_)) => (),
+        other => panic!("expected Unsupported, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn codec_tga_bottom_up_rle_24_bit() {
+    // 3x2, type 10, 24 bit, bottom-left origin.
+    let mut data = vec![0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3, 0, 2, 0, 24, 0];
+    // Bottom row: run of 3 blue.
+    data.extend_from_slice(&[0x82, 255, 0, 0]);
+    // Top row: raw red, green, then a single gray run.
+    data.extend_from_slice(&[0x01, 0, 0, 255, 0, 255, 0]);
+    data.extend_from_slice(&[0x80, 50, 50, 50]);
+
+    let img = tga::decode(&data).unwrap();
+    assert_eq!(img.at(0, 0), [255, 0, 0, 255]);
+    assert_eq!(img.at(1, 0), [0, 255, 0, 255]);
+    assert_eq!(img.at(2, 0), [50, 50, 50, 255]);
+    for x in 0..3 {
+        assert_eq!(img.at(x, 1), [0, 0, 255, 255]);
+    }
+
+    match tga::decode(&data[..data.len() - 2]) {
+        Err(ImageError::Truncated) => (),
+        other => panic!("expected Truncated, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn codec_tga_color_mapped_and_gray() {
+    // 2x1, type 1, two 24 bit map entries starting at index 0.
+    let mut data = vec![0, 1, 1, 0, 0, 2, 0, 24, 0, 0, 0, 0, 2, 0, 1, 0, 8, 0x20];
+    data.extend_from_slice(&[0, 0, 255, 255, 0, 0]);
+    data.extend_from_slice(&[1, 0]);
+    let img = tga::decode(&data).unwrap();
+    assert_eq!(img.at(0, 0), [0, 0, 255, 255]);
+    assert_eq!(img.at(1, 0), [255, 0, 0, 255]);
+
+    // 2x1, type 3, 8 bit gray, right-to-left.
+    let data = [
+        0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 1, 0, 8, 0x30, 10, 20,
+    ];
+    let img = tga::decode(&data).unwrap();
+    assert_eq!(img.at(0, 0), [20, 20, 20, 255]);
+    assert_eq!(img.at(1, 0), [10, 10, 10, 255]);
+
+    let unsupported = [0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 0, 8, 0];
+    match tga::decode(&unsupported) {
+        Err(ImageError::Unsupported(_)) => (),
+        other => panic!("expected Unsupported, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn codec_pnm_round_trip() {
+    let img = sample(4, 3);
+
+    let decoded = pnm::decode(&pnm::encode_ppm(&img)).unwrap();
+    for y in 0..3 {
+        for x in 0..4 {
+            let p = img.at(x, y);
+            assert_eq!(decoded.at(x, y), [p[0], p[1], p[2], 255]);
+        }
+    }
+
+    let decoded = pnm::decode(&pnm::encode_pgm(&img)).unwrap();
+    let p = decoded.at(1, 1);
+    assert_eq!(p[0], p[1]);
+    assert_eq!(p[1], p[2]);
+    assert_eq!(p[3], 255);
+}
+
+#[test]
+fn codec_pnm_ascii_with_comments() {
+    let ppm = b"P3\n# a comment\n2 1 # trailing\n15\n15 0 0  0 15 15\n";
+    let img = pnm::decode(ppm).unwrap();
+    assert_eq!(img.at(0, 0), [255, 0, 0, 255]);
+    assert_eq!(img.at(1, 0), [0, 255, 255, 255]);
+
+    let pgm = b"P2 2 2 255\n0 64\n128 255";
+    let img = pnm::decode(pgm).unwrap();
+    assert_eq!(img.at(1, 0), [64, 64, 64, 255]);
+    assert_eq!(img.at(0, 1), [128, 128, 128, 255]);
+
+    match pnm::decode(b"P2 2 2 255\n0 64\n128") {
+        Err(ImageError::Truncated) => (),
+        other => panic!("expected Truncated, got {:?}", other.err()),
+    }
+    match pnm::decode(b"P2 1 1 10\n11") {
+        Err(ImageError::Malformed(_)) => (),
+        other => panic!("expected Malformed, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn codec_pnm_16_bit_binary() {
+    let mut data = b"P5 2 1 65535\n".to_vec();
+    data.extend_from_slice(&[0xff, 0xff, 0x80, 0x00]);
+    let img = pnm::decode(&data).unwrap();
+    assert_eq!(img.at(0, 0), [255, 255, 255, 255]);
+    assert_eq!(img.at(1, 0), [128, 128, 128, 255]);
+
+    match pnm::decode(b"P4 1 1\n\0") {
+        Err(ImageError::Unsupported(_)) => (),
+        other => panic!("expected Unsupported, got {:?}", other.err()),
+    }
+}
+
+// Headers claiming the largest allowed image over a few bytes of data
+// must fail before the image is allocated.
+#[test]
+fn codec_huge_header_tiny_body() {
+    let bmp = bmp_file(32768, 32768, 32, &[], &[0; 4]);
+    match bmp::decode(&bmp) {
+        Err(ImageError::Truncated) => (),
+        other => panic!("expected Truncated, got {:?}", other.err()),
+    }
+
+    // 32768x32768 true color, raw then RLE.
+    let mut tga = vec![0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x80, 0, 0x80, 32, 0];
+    tga.extend_from_slice(&[0; 8]);
+    match tga::decode(&tga) {
+        Err(ImageError::Truncated) => (),
+        other => panic!("expected Truncated, got {:?}", other.err()),
+    }
+    tga[2] = 10;
+    match tga::decode(&tga) {
+        Err(ImageError::Truncated) => (),
+        other => panic!("expected Truncated, got {:?}", other.err()),
+    }
+
+    for pnm in [
+        &b"P3 32768 32768 255\n1 2 3"[..],
+        &b"P6 32768 32768 65535\n\x01\x02\x03"[..],
+        &b"P5 99999999999 99999999999 255\n\0"[..],
+    ] {
+        match pnm::decode(pnm) {
+            Err(ImageError::Truncated) => (),
+            other => panic!("expected Truncated, got {:?}", other.err()),
+        }
+    }
+}
+
+#[test]
+fn codec_format_detection() {
+    assert_eq!(
+        ImageFormat::from_extension(Path::new("a/b.BMP")),
+        Some(ImageFormat::Bmp)
+    );
+    assert_eq!(
+        ImageFormat::from_extension(Path::new("b.tga")),
+        Some(ImageFormat::Tga)
+    );
+    assert_eq!(
+        ImageFormat::from_extension(Path::new("b.pgm")),
+        Some(ImageFormat::Pnm)
+    );
+    assert_eq!(ImageFormat::from_extension(Path::new("b.png")), None);
+
+    assert_eq!(
+        ImageFormat::from_signature(b"BM...."),
+        Some(ImageFormat::Bmp)
+    );
+    assert_eq!(
+        ImageFormat::from_signature(b"P6 1 1"),
+        Some(ImageFormat::Pnm)
+    );
+    assert_eq!(ImageFormat::from_signature(b"\0\0\x02"), None);
+}
+
+#[test]
+fn codec_save_and_load() {
+    let img = sample(3, 3);
+    let dir = std::env::temp_dir();
+
+    for name in ["ranger_codec.bmp", "ranger_codec.tga"].iter() {
+        let path = dir.join(name);
+        codecs::save(&img, &path).unwrap();
+        let loaded = codecs::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_same(&img, &loaded);
+    }
+
+    match codecs::save(&img, dir.join("ranger_codec.xyz")) {
+        Err(ImageError::UnknownFormat) => (),
+        other => panic!("expected UnknownFormat, got {:?}", other.err()),
+    }
+}
@@ -0,0 +1,217 @@
+extern crate ranger;
+
+use std::fs;
+
+use ranger::config::{self, json, ConfigError};
+use ranger::rendering::clear_mode::ClearMode;
+use ranger::rendering::color::Palette;
+use ranger::world::WorldProperties;
+
+fn defaults() -> WorldProperties {
+    let mut wp = WorldProperties::new();
+    wp.window_width = 800;
+    wp.window_height = 600;
+    wp.view_width = 1200.0;
+    wp.view_height = 900.0;
+    wp.title = String::from("Code");
+    wp
+}
+
+fn invalid_key(text: &str) -> String {
+    match config::apply(text, &mut defaults()) {
+        Err(ConfigError::Invalid { key, .. }) => key,
+        other => panic!("expected Invalid for {}, got {:?}", text, other.err()),
+    }
+}
+
+#[test]
+fn config_merges_over_defaults() {
+    let mut wp = defaults();
+    let text = r#"{
+        "window": { "width": 1024, "title": "From \"file\"" },
+        "view": { "centered": false },
+        "clear": { "mode": "solid", "color": [0, 0, 64] },
+        "timing": { "frames_per_second": 60 },
+        "bindings": { "quit": "Q", "jump": ["Space", "Up"] },
+        "debug": { "coordinates": false }
+    }"#;
+    config::apply(text, &mut wp).unwrap();
+
+    assert_eq!(wp.window_width, 1024);
+    // Unset values keep the defaults.
+    assert_eq!(wp.window_height, 600);
+    assert_eq!(wp.view_width, 1200.0);
+    assert_eq!(wp.title, "From \"file\"");
+    assert!(!wp.view_centered);
+    assert!(wp.perform_clear);
+    match wp.clear_mode {
+        ClearMode::Solid(c) => assert_eq!(c, Palette::RGB(0, 0, 64)),
+        _ => panic!("expected a solid clear"),
+    }
+    assert_eq!(wp.frames_per_second, 60);
+    assert_eq!(wp.updates_per_second, 30);
+    assert_eq!(wp.bindings["quit"], vec!["Q"]);
+    assert_eq!(wp.bindings["jump"], vec!["Space", "Up"]);
+    assert!(wp.show_stats);
+    assert!(!wp.show_coordinates);
+}
+
+#[test]
+fn config_rejects_bad_settings() {
+    assert_eq!(
+        invalid_key(r#"{ "window": { "widht": 5 } }"#),
+        "window.widht"
+    );
+    assert_eq!(invalid_key(r#"{ "sound": {} }"#), "sound");
+    assert_eq!(
+        invalid_key(r#"{ "window": { "width": -5 } }"#),
+        "window.width"
+    );
+    assert_eq!(
+        invalid_key(r#"{ "window": { "width": 10.5 } }"#),
+        "window.width"
+    );
+    assert_eq!(
+        invalid_key(r#"{ "view": { "height": "big" } }"#),
+        "view.height"
+    );
+    assert_eq!(
+        invalid_key(r#"{ "timing": { "frames_per_second": 0 } }"#),
+        "timing.frames_per_second"
+    );
+    assert_eq!(
+        invalid_key(r#"{ "clear": { "mode": "plaid" } }"#),
+        "clear.mode"
+    );
+    assert_eq!(
+        invalid_key(r#"{ "clear": { "mode": "solid" } }"#),
+        "clear.color"
+    );
+    assert_eq!(
+        invalid_key(r#"{ "clear": { "color": [1, 2, 3] } }"#),
+        "clear.color"
+    );
+    assert_eq!(
+        invalid_key(
+            r#"{ "clear": { "mode": "gradient", "top": [1, 2, 3], "bottom": [1, 2, 300] } }"#
+        ),
+        "clear.bottom[2]"
+    );
+    assert_eq!(
+        invalid_key(r#"{ "bindings": { "quit": [""] } }"#),
+        "bindings.quit[0]"
+    );
+    assert_eq!(invalid_key("[]"), "(top level)");
+
+    // Nothing changes when any setting is bad.
+    let mut wp = defaults();
+    let err =
+        config::apply(r#"{ "window": { "width": 640, "height": true } }"#, &mut wp).unwrap_err();
+    assert_eq!(wp.window_width, 800);
+    assert_eq!(
+        err.to_string(),
+        "Config setting 'window.height': expected a whole number, found a boolean"
+    );
+}
+
+#[test]
+fn config_syntax_errors_have_positions() {
+    let cases = [
+        ("{\n  \"a\": 1,\n  \"b\" 2\n}", 3, 7),
+        ("{ \"a\": tru }", 1, 8),
+        ("{ \"a\": [1, 2 }", 1, 14),
+        ("{ \"a\": 1 } x", 1, 12),
+        ("{ \"a\": 1, \"a\": 2 }", 1, 11),
+        ("\"\\q\"", 1, 2),
+        ("", 1, 1),
+    ];
+    for &(text, line, column) in cases.iter() {
+        match json::parse(text) {
+            Err(err) => assert_eq!(
+                (err.line, err.column),
+                (line, column),
+                "{:?}: {}",
+                text,
+                err
+            ),
+            Ok(v) => panic!("{:?} parsed as {:?}", text, v),
+        }
+    }
+
+    let value =
+        json::parse(r#"{ "s": "\u00e9\ud83d\ude00\n", "n": [-1.5e2, 0, null, false] }"#).unwrap();
+    assert_eq!(
+        value.get("s"),
+        Some(&json::Value::String(String::from("é😀\n")))
+    );
+    assert_eq!(
+        value.get("n"),
+        Some(&json::Value::Array(vec![
+            json::Value::Number(-150.0),
+            json::Value::Number(0.0),
+            json::Value::Null,
+            json::Value::Bool(false),
+        ]))
+    );
+}
+
+#[test]
+fn config_missing_is_not_written() {
+    let dir = std::env::temp_dir().join("ranger_config_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("game.config");
+
+    let mut wp = defaults();
+    wp.clear_mode = ClearMode::VerticalGradient {
+        top: Palette::RGB(1, 2, 3),
+        bottom: Palette::RGBA(4, 5, 6, 7),
+    };
+    assert!(!config::load(&path, &mut wp).unwrap());
+    assert!(!path.exists());
+    config::save(&path, &wp).unwrap();
+
+    // The written file reproduces the properties.
+    let mut loaded = WorldProperties::new();
+    assert!(config::load(&path, &mut loaded).unwrap());
+    assert_eq!(loaded.window_width, 800);
+    assert_eq!(loaded.view_height, 900.0);
+    assert_eq!(loaded.title, "Code");
+    assert_eq!(loaded.bindings, wp.bindings);
+    match loaded.clear_mode {
+        ClearMode::VerticalGradient { top, bottom } => {
+            assert_eq!(top, Palette::RGB(1, 2, 3));
+            assert_eq!(bottom, Palette::RGBA(4, 5, 6, 7));
+        }
+        _ => panic!("expected a gradient clear"),
+    }
+    assert_eq!(
+        config::to_json(&loaded).to_string(),
+        fs::read_to_string(&path).unwrap().trim_end()
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn config_image_relative_to_file() {
+    let dir = std::env::temp_dir().join("ranger_config_image_test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("art")).unwrap();
+    fs::write(dir.join("art").join("back.ppm"), b"P3 1 1 255\n1 2 3\n").unwrap();
+    let path = dir.join("game.config");
+    fs::write(
+        &path,
+        r#"{ "clear": { "mode": "image", "image": "art/back.ppm" } }"#,
+    )
+    .unwrap();
+
+    let mut wp = defaults();
+    assert!(config::load(&path, &mut wp).unwrap());
+    match wp.clear_mode {
+        ClearMode::Image(ref image) => assert_eq!(image.at(0, 0), [1, 2, 3, 255]),
+        _ => panic!("expected an image clear"),
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+}
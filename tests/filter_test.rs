@@ -0,0 +1,230 @@
+extern crate ranger;
+
+use ranger::rendering::color::Palette;
+use ranger::rendering::filters::{
+    gaussian_kernel, Bloom, BoxBlur, ColorGrade, Dither, Filter, FilterChain, GaussianBlur,
+    Quantize, Scanlines, Vignette,
+};
+use ranger::rendering::image::RGBA;
+
+fn filled(w: usize, h: usize, color: [u8; 4]) -> RGBA {
+    let mut img = RGBA::new(w, h);
+    for y in 0..h {
+        for x in 0..w {
+            img.set(x, y, color);
+        }
+    }
+    img
+}
+
+fn sum_red(img: &RGBA) -> u32 {
+    let mut sum = 0;
+    for y in 0..img.bounds().h() {
+        for x in 0..img.bounds().w() {
+            sum += img.at(x, y)[0] as u32;
+        }
+    }
+    sum
+}
+
+#[test]
+fn filter_box_blur_spreads_evenly() {
+    let mut img = filled(5, 5, [0, 0, 0, 255]);
+    img.set(2, 2, [225, 0, 0, 255]);
+
+    BoxBlur::new(1).apply(&mut img);
+
+    // 225 / 9 over the 3x3 neighborhood
+    for y in 1..4 {
+        for x in 1..4 {
+            assert_eq!(img.at(x, y)[0], 25);
+        }
+    }
+    assert_eq!(img.at(0, 0)[0], 0);
+    assert_eq!(img.at(4, 2)[0], 0);
+}
+
+#[test]
+fn filter_blur_keeps_uniform_images() {
+    let color = [10, 120, 250, 200];
+    let mut img = filled(7, 4, color);
+
+    GaussianBlur::new(1.5).apply(&mut img);
+    BoxBlur::new(2).apply(&mut img);
+
+    for y in 0..4 {
+        for x in 0..7 {
+            assert_eq!(img.at(x, y), color);
+        }
+    }
+}
+
+#[test]
+fn filter_gaussian_is_symmetric() {
+    let kernel = gaussian_kernel(1.0);
+    assert_eq!(kernel.len(), 7);
+    let sum: f64 = kernel.iter().sum();
+    assert!((sum - 1.0).abs() < 1e-9);
+    for i in 0..3 {
+        assert!((kernel[i] - kernel[6 - i]).abs() < 1e-12);
+        assert!(kernel[i] < kernel[i + 1]);
+    }
+
+    let mut img = filled(9, 9, [0, 0, 0, 255]);
+    img.set(4, 4, [255, 0, 0, 255]);
+    GaussianBlur::new(1.0).apply(&mut img);
+
+    assert_eq!(img.at(3, 4), img.at(5, 4));
+    assert_eq!(img.at(4, 3), img.at(4, 5));
+    assert_eq!(img.at(3, 3), img.at(5, 5));
+    assert!(img.at(4, 4)[0] > img.at(3, 4)[0]);
+    // Energy is roughly preserved.
+    let sum = sum_red(&img) as i32;
+    assert!((sum - 255).abs() < 16, "sum {}", sum);
+}
+
+#[test]
+fn filter_bloom_only_affects_bright_areas() {
+    let mut dark = filled(8, 8, [50, 50, 50, 255]);
+    Bloom::new(200, 1.0, 1.0).apply(&mut dark);
+    assert_eq!(dark.at(4, 4), [50, 50, 50, 255]);
+
+    let mut img = filled(9, 9, [50, 50, 50, 255]);
+    img.set(4, 4, [255, 255, 255, 255]);
+    Bloom::new(200, 1.0, 1.0).apply(&mut img);
+
+    // Neighbors glow, distant pixels don't.
+    assert!(img.at(5, 4)[0] > 50);
+    assert_eq!(img.at(0, 0), [50, 50, 50, 255]);
+    assert_eq!(img.at(4, 4), [255, 255, 255, 255]);
+}
+
+#[test]
+fn filter_color_grade() {
+    let mut img = filled(2, 2, [0, 100, 255, 77]);
+
+    ColorGrade::invert().apply(&mut img);
+    assert_eq!(img.at(1, 1), [255, 155, 0, 77]);
+
+    let mut img = filled(1, 1, [100, 100, 100, 255]);
+    ColorGrade::tint(2.0, 1.0, 0.5).apply(&mut img);
+    assert_eq!(img.at(0, 0), [200, 100, 50, 255]);
+
+    let grade = ColorGrade::brightness_contrast(0.0, 2.0);
+    assert_eq!(grade.table(0)[0], 0);
+    assert_eq!(grade.table(0)[64], 0);
+    assert_eq!(grade.table(1)[255], 255);
+    assert_eq!(grade.table(2)[128], 129);
+
+    let identity = ColorGrade::identity();
+    for v in 0..256 {
+        assert_eq!(identity.table(0)[v] as usize, v);
+    }
+}
+
+#[test]
+fn filter_vignette_darkens_corners() {
+    let mut img = filled(21, 21, [200, 200, 200, 255]);
+
+    Vignette::new(1.0, 0.5, 0.5).apply(&mut img);
+
+    assert_eq!(img.at(10, 10), [200, 200, 200, 255]);
+    assert_eq!(img.at(0, 0), [0, 0, 0, 255]);
+    let mid = img.at(0, 10)[0];
+    assert!(mid > 0 && mid < 200, "mid {}", mid);
+}
+
+#[test]
+fn filter_vignette_without_softness() {
+    // Past the corners, nothing is darkened.
+    let mut img = filled(21, 21, [200, 200, 200, 255]);
+    Vignette::new(1.0, 2.0, 0.0).apply(&mut img);
+    assert_eq!(img.at(0, 0), [200, 200, 200, 255]);
+    assert_eq!(img.at(10, 10), [200, 200, 200, 255]);
+
+    // A hard edge.
+    Vignette::new(1.0, 0.5, 0.0).apply(&mut img);
+    assert_eq!(img.at(0, 0), [0, 0, 0, 255]);
+    assert_eq!(img.at(10, 10), [200, 200, 200, 255]);
+}
+
+#[test]
+fn filter_scanlines() {
+    let mut img = filled(3, 6, [200, 100, 50, 255]);
+
+    Scanlines::new(3, 1, 0.5).apply(&mut img);
+
+    for y in 0..6 {
+        let expected = if y % 3 == 0 {
+            [100, 50, 25, 255]
+        } else {
+            [200, 100, 50, 255]
+        };
+        assert_eq!(img.at(1, y), expected);
+    }
+}
+
+#[test]
+fn filter_quantize_without_dither() {
+    let palette = vec![Palette::BLACK(), Palette::WHITE(255), Palette::RED()];
+    let mut img = RGBA::new(3, 1);
+    img.set(0, 0, [40, 30, 20, 255]);
+    img.set(1, 0, [220, 230, 240, 128]);
+    img.set(2, 0, [200, 40, 30, 255]);
+
+    Quantize::new(palette, Dither::None).apply(&mut img);
+
+    assert_eq!(img.at(0, 0), [0, 0, 0, 255]);
+    assert_eq!(img.at(1, 0), [255, 255, 255, 128]);
+    assert_eq!(img.at(2, 0), [255, 0, 0, 255]);
+}
+
+#[test]
+fn filter_quantize_dithers_mid_gray() {
+    let palette = vec![Palette::BLACK(), Palette::WHITE(255)];
+
+    for dither in [Dither::FloydSteinberg, Dither::Ordered { spread: 255.0 }].iter() {
+        let mut img = filled(16, 16, [128, 128, 128, 255]);
+        Quantize::new(palette.clone(), *dither).apply(&mut img);
+
+        let mut white = 0;
+        for y in 0..16 {
+            for x in 0..16 {
+                let p = img.at(x, y);
+                assert!(p[0] == 0 || p[0] == 255);
+                if p[0] == 255 {
+                    white += 1;
+                }
+            }
+        }
+        // About half of the 256 pixels are white.
+        assert!(white > 112 && white < 144, "{:?}: {}", dither, white);
+    }
+}
+
+#[test]
+fn filter_chain_applies_in_order() {
+    let mut chain = FilterChain::new();
+    assert!(!chain.is_active());
+
+    chain.add(Box::new(ColorGrade::tint(2.0, 2.0, 2.0)));
+    chain.add(Box::new(ColorGrade::invert()));
+    assert_eq!(chain.len(), 2);
+    assert!(chain.is_active());
+
+    let mut img = filled(1, 1, [100, 10, 200, 255]);
+    chain.apply(&mut img);
+    // Tinted (and clamped) first, then inverted.
+    assert_eq!(img.at(0, 0), [55, 235, 0, 255]);
+
+    chain.set_enabled(false);
+    assert!(!chain.is_active());
+    chain.apply(&mut img);
+    assert_eq!(img.at(0, 0), [55, 235, 0, 255]);
+
+    chain.set_enabled(true);
+    assert!(chain.remove(0).is_some());
+    assert!(chain.remove(5).is_none());
+    chain.apply(&mut img);
+    assert_eq!(img.at(0, 0), [200, 20, 255, 255]);
+}
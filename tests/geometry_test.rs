@@ -1,5 +1,9 @@
 extern crate ranger;
 
+use std::f64::consts::PI;
+
+use ranger::geometry::clipping::{Clipping, PolygonClipper};
+use ranger::geometry::shapes::Shapes;
 use ranger::geometry::{point, rectangle};
 
 #[test]
@@ -219,3 +223,191 @@ fn rectangle_not_contains_point() {
 
     assert_eq!(contains, false);
 }
+
+fn near(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1.0e-9
+}
+
+#[test]
+fn shapes_segments_scale_with_radius() {
+    let small = Shapes::segments_for(2.0, 2.0 * PI);
+    let large = Shapes::segments_for(100.0, 2.0 * PI);
+    assert_eq!(small, 8);
+    assert!(large > small);
+
+    // A quarter sweep needs about a quarter of the segments.
+    let quarter = Shapes::segments_for(100.0, PI / 2.0);
+    assert_eq!(quarter, (large as f64 / 4.0).ceil() as usize);
+}
+
+#[test]
+fn shapes_ellipse_points_on_curve() {
+    let mut out = Vec::new();
+    Shapes::ellipse(10.0, 20.0, 4.0, 2.0, 16, &mut out);
+    assert_eq!(out.len(), 16);
+
+    for p in &out {
+        let x = (p.x - 10.0) / 4.0;
+        let y = (p.y - 20.0) / 2.0;
+        assert!(near(x * x + y * y, 1.0));
+    }
+
+    // The first vertex is at angle 0 and isn't repeated.
+    assert!(near(out[0].x, 14.0) && near(out[0].y, 20.0));
+}
+
+#[test]
+fn shapes_arc_and_pie() {
+    let mut out = Vec::new();
+    Shapes::arc(0.0, 0.0, 1.0, 1.0, 0.0, PI / 2.0, 4, &mut out);
+    assert_eq!(out.len(), 5);
+    assert!(near(out[0].x, 1.0) && near(out[0].y, 0.0));
+    assert!(near(out[4].x, 0.0) && near(out[4].y, 1.0));
+
+    Shapes::pie(0.0, 0.0, 1.0, 1.0, 0.0, PI / 2.0, 4, &mut out);
+    assert_eq!(out.len(), 6);
+    assert!(near(out[0].x, 0.0) && near(out[0].y, 0.0));
+}
+
+#[test]
+fn shapes_rounded_rectangle() {
+    let mut out = Vec::new();
+    Shapes::rounded_rectangle(0.0, 0.0, 10.0, 4.0, 1.0, 3, &mut out);
+    assert_eq!(out.len(), 4 * 4);
+
+    for p in &out {
+        assert!(p.x >= -1.0e-9 && p.x <= 10.0 + 1.0e-9);
+        assert!(p.y >= -1.0e-9 && p.y <= 4.0 + 1.0e-9);
+    }
+
+    // The top edge runs between the top-left and top-right arcs.
+    assert!(near(out[0].x, 9.0) && near(out[0].y, 0.0));
+    assert!(near(out[15].x, 1.0) && near(out[15].y, 0.0));
+
+    // A zero radius is a plain rectangle and a large radius is clamped.
+    Shapes::rounded_rectangle(0.0, 0.0, 10.0, 4.0, 0.0, 3, &mut out);
+    assert_eq!(out.len(), 4);
+
+    Shapes::rounded_rectangle(0.0, 0.0, 10.0, 4.0, 50.0, 3, &mut out);
+    for p in &out {
+        assert!(p.y >= -1.0e-9 && p.y <= 4.0 + 1.0e-9);
+    }
+}
+
+type LineClipper =
+    fn(&rectangle::Rectangle, &point::Point, &point::Point) -> Option<(point::Point, point::Point)>;
+
+fn clip_rect() -> rectangle::Rectangle {
+    rectangle::Rectangle::from_min_max(0.0, 0.0, 100.0, 50.0)
+}
+
+#[test]
+fn clipping_lines() {
+    let rect = clip_rect();
+    let clippers: [LineClipper; 2] = [Clipping::cohen_sutherland, Clipping::liang_barsky];
+
+    for clip in clippers.iter() {
+        // Inside is untouched
+        let a = point::Point::from_xy(10.0, 10.0);
+        let b = point::Point::from_xy(90.0, 40.0);
+        assert_eq!(clip(&rect, &a, &b), Some((a, b)));
+
+        // Entirely outside
+        let a = point::Point::from_xy(-10.0, -10.0);
+        let b = point::Point::from_xy(200.0, -5.0);
+        assert_eq!(clip(&rect, &a, &b), None);
+
+        // Crossing both sides
+        let a = point::Point::from_xy(-50.0, 25.0);
+        let b = point::Point::from_xy(150.0, 25.0);
+        let (p0, p1) = clip(&rect, &a, &b).unwrap();
+        assert!(near(p0.x, 0.0) && near(p0.y, 25.0));
+        assert!(near(p1.x, 100.0) && near(p1.y, 25.0));
+
+        // Diagonal through a corner region
+        let a = point::Point::from_xy(-10.0, -10.0);
+        let b = point::Point::from_xy(60.0, 60.0);
+        let (p0, p1) = clip(&rect, &a, &b).unwrap();
+        assert!(near(p0.x, 0.0) && near(p0.y, 0.0));
+        assert!(near(p1.x, 50.0) && near(p1.y, 50.0));
+
+        // Huge coordinates
+        let a = point::Point::from_xy(-1.0e12, 20.0);
+        let b = point::Point::from_xy(1.0e12, 20.0);
+        let (p0, p1) = clip(&rect, &a, &b).unwrap();
+        assert!(near(p0.x, 0.0) && near(p1.x, 100.0));
+    }
+}
+
+#[test]
+fn clipping_outcodes() {
+    let rect = clip_rect();
+    let inside = [
+        point::Point::from_xy(1.0, 1.0),
+        point::Point::from_xy(99.0, 49.0),
+    ];
+    assert!(Clipping::contains_all(&rect, &inside));
+    assert!(!Clipping::outside_all(&rect, &inside));
+
+    // All left of the rectangle
+    let left = [
+        point::Point::from_xy(-1.0, -100.0),
+        point::Point::from_xy(-5.0, 100.0),
+    ];
+    assert!(Clipping::outside_all(&rect, &left));
+
+    // Straddling, neither test is conclusive.
+    let across = [
+        point::Point::from_xy(-10.0, 25.0),
+        point::Point::from_xy(110.0, 25.0),
+    ];
+    assert!(!Clipping::contains_all(&rect, &across));
+    assert!(!Clipping::outside_all(&rect, &across));
+}
+
+#[test]
+fn clipping_polygons() {
+    let rect = clip_rect();
+    let mut clipper = PolygonClipper::new();
+    let mut out = Vec::new();
+
+    // A triangle with one vertex outside becomes a quadrilateral.
+    let tri = [
+        point::Point::from_xy(10.0, 10.0),
+        point::Point::from_xy(150.0, 10.0),
+        point::Point::from_xy(10.0, 40.0),
+    ];
+    clipper.clip(&rect, &tri, &mut out);
+    assert_eq!(out.len(), 4);
+    for p in &out {
+        assert!(p.x >= 0.0 && p.x <= 100.0 && p.y >= 0.0 && p.y <= 50.0);
+    }
+
+    // Covering the whole rectangle yields the rectangle.
+    let big = [
+        point::Point::from_xy(-1000.0, -1000.0),
+        point::Point::from_xy(1000.0, -1000.0),
+        point::Point::from_xy(1000.0, 1000.0),
+        point::Point::from_xy(-1000.0, 1000.0),
+    ];
+    clipper.clip(&rect, &big, &mut out);
+    assert_eq!(out.len(), 4);
+    let area: f64 = (0..out.len())
+        .map(|i| {
+            let (a, b) = (out[i], out[(i + 1) % out.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        .abs()
+        / 2.0;
+    assert!(near(area, 5000.0));
+
+    // Entirely outside
+    let outside = [
+        point::Point::from_xy(200.0, 0.0),
+        point::Point::from_xy(300.0, 0.0),
+        point::Point::from_xy(250.0, 40.0),
+    ];
+    clipper.clip(&rect, &outside, &mut out);
+    assert!(out.is_empty());
+}
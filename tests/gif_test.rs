@@ -0,0 +1,313 @@
+extern crate ranger;
+
+use std::time::Duration;
+
+use ranger::rendering::codecs::gif::{self, GifEncoder};
+use ranger::rendering::codecs::ImageError;
+use ranger::rendering::filters::{median_cut, Dither};
+use ranger::rendering::image::RGBA;
+
+struct Frame {
+    delay: u16,
+    transparent: Option<u8>,
+    width: usize,
+    height: usize,
+    palette: Vec<[u8; 3]>,
+    indices: Vec<u8>,
+}
+
+impl Frame {
+    fn at(&self, x: usize, y: usize) -> Option<[u8; 3]> {
+        let index = self.indices[y * self.width + x];
+        if Some(index) == self.transparent {
+            None
+        } else {
+            Some(self.palette[index as usize])
+        }
+    }
+}
+
+fn u16_at(data: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([data[i], data[i + 1]])
+}
+
+fn sub_blocks(data: &[u8], pos: &mut usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    loop {
+        let len = data[*pos] as usize;
+        *pos += 1;
+        if len == 0 {
+            return out;
+        }
+        out.extend_from_slice(&data[*pos..*pos + len]);
+        *pos += len;
+    }
+}
+
+fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1usize << min_code_size;
+    let end = clear + 1;
+    let reset = || -> Vec<Vec<u8>> { (0..clear + 2).map(|i| vec![i as u8]).collect() };
+
+    let mut table = reset();
+    let mut width = min_code_size as usize + 1;
+    let mut out = Vec::new();
+    let mut previous: Option<usize> = None;
+
+    let mut bit = 0;
+    while bit + width <= data.len() * 8 {
+        let mut code = 0;
+        for i in 0..width {
+            let b = bit + i;
+            code |= (((data[b / 8] >> (b % 8)) & 1) as usize) << i;
+        }
+        bit += width;
+
+        if code == clear {
+            table = reset();
+            width = min_code_size as usize + 1;
+            previous = None;
+            continue;
+        }
+        if code == end {
+            break;
+        }
+
+        let entry = match previous {
+            None => table[code].clone(),
+            Some(p) => {
+                let entry = if code < table.len() {
+                    table[code].clone()
+                } else {
+                    let mut e = table[p].clone();
+                    e.push(table[p][0]);
+                    e
+                };
+                if table.len() < 4096 {
+                    let mut added = table[p].clone();
+                    added.push(entry[0]);
+                    table.push(added);
+                    if table.len() == 1 << width && width < 12 {
+                        width += 1;
+                    }
+                }
+                entry
+            }
+        };
+        out.extend_from_slice(&entry);
+        previous = Some(code);
+    }
+
+    out
+}
+
+fn decode(data: &[u8]) -> (usize, usize, Option<u16>, Vec<Frame>) {
+    assert_eq!(&data[..6], b"GIF89a");
+    let width = u16_at(data, 6) as usize;
+    let height = u16_at(data, 8) as usize;
+    assert_eq!(data[10] & 0x80, 0, "no global color table expected");
+
+    let mut pos = 13;
+    let mut loops = None;
+    let mut frames = Vec::new();
+    let mut delay = 0;
+    let mut transparent = None;
+
+    loop {
+        match data[pos] {
+            0x21 => {
+                let label = data[pos + 1];
+                pos += 2;
+                let block = sub_blocks(data, &mut pos);
+                if label == 0xf9 {
+                    delay = u16_at(&block, 1);
+                    transparent = if block[0] & 1 != 0 {
+                        Some(block[3])
+                    } else {
+                        None
+                    };
+                } else if label == 0xff && block.starts_with(b"NETSCAPE2.0") {
+                    loops = Some(u16_at(&block, 12));
+                }
+            }
+            0x2c => {
+                let w = u16_at(data, pos + 5) as usize;
+                let h = u16_at(data, pos + 7) as usize;
+                let packed = data[pos + 9];
+                pos += 10;
+                assert!(packed & 0x80 != 0, "local color table expected");
+                let entries = 1 << ((packed & 7) + 1);
+                let palette = data[pos..pos + entries * 3]
+                    .chunks(3)
+                    .map(|c| [c[0], c[1], c[2]])
+                    .collect();
+                pos += entries * 3;
+                let min_code_size = data[pos];
+                pos += 1;
+                let indices = lzw_decode(&sub_blocks(data, &mut pos), min_code_size);
+                assert_eq!(indices.len(), w * h);
+                frames.push(Frame {
+                    delay,
+                    transparent,
+                    width: w,
+                    height: h,
+                    palette,
+                    indices,
+                });
+            }
+            0x3b => break,
+            other => panic!("unexpected block {:#x} at {}", other, pos),
+        }
+    }
+
+    (width, height, loops, frames)
+}
+
+fn gradient(w: usize, h: usize, shift: usize) -> RGBA {
+    let mut img = RGBA::new(w, h);
+    for y in 0..h {
+        for x in 0..w {
+            img.set(
+                x,
+                y,
+                [((x + shift) * 255 / w) as u8, (y * 255 / h) as u8, 90, 255],
+            );
+        }
+    }
+    img
+}
+
+#[test]
+fn gif_few_colors_are_exact() {
+    let mut img = RGBA::new(8, 4);
+    for y in 0..4 {
+        for x in 0..8 {
+            let color = match (x + y) % 3 {
+                0 => [255, 0, 0, 255],
+                1 => [0, 255, 0, 255],
+                _ => [0, 0, 255, 255],
+            };
+            img.set(x, y, color);
+        }
+    }
+
+    let data = gif::encode(&[img.clone()], Duration::from_millis(100)).unwrap();
+    let (w, h, loops, frames) = decode(&data);
+
+    assert_eq!((w, h), (8, 4));
+    assert_eq!(loops, Some(0));
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].delay, 10);
+    for y in 0..4 {
+        for x in 0..8 {
+            let p = img.at(x, y);
+            assert_eq!(frames[0].at(x, y), Some([p[0], p[1], p[2]]));
+        }
+    }
+}
+
+#[test]
+fn gif_large_frames_survive_lzw() {
+    // Enough distinct colors and pixels to fill the code table.
+    let frames: Vec<RGBA> = (0..2).map(|i| gradient(160, 120, i * 7)).collect();
+
+    let mut encoder = GifEncoder::new(Duration::from_millis(50));
+    encoder.set_dither(Dither::None);
+    for frame in frames.iter() {
+        encoder.add_frame(frame).unwrap();
+    }
+    let (_, _, _, decoded) = decode(&encoder.finish().unwrap());
+
+    assert_eq!(decoded.len(), 2);
+    for (frame, gif) in frames.iter().zip(decoded.iter()) {
+        assert_eq!(gif.delay, 5);
+        assert_eq!((gif.width, gif.height), (160, 120));
+        let mut error = 0;
+        for y in 0..120 {
+            for x in 0..160 {
+                let p = frame.at(x, y);
+                let q = gif.at(x, y).unwrap();
+                for c in 0..3 {
+                    error += (p[c] as i32 - q[c] as i32).unsigned_abs();
+                }
+            }
+        }
+        // Within a few levels per channel on average.
+        let average = error as f64 / (160.0 * 120.0 * 3.0);
+        assert!(average < 4.0, "average error {}", average);
+    }
+}
+
+#[test]
+fn gif_delays_follow_the_frame_period() {
+    // 120 fps is faster than GIF allows so every third frame is kept,
+    // alternating 2 and 3 centiseconds (the period is just under 2.5).
+    let period = Duration::new(0, 8_333_333);
+    let mut encoder = GifEncoder::new(period);
+    assert_eq!(encoder.stride(), 3);
+
+    let frame = gradient(4, 4, 0);
+    for _ in 0..12 {
+        encoder.add_frame(&frame).unwrap();
+    }
+    assert_eq!(encoder.frame_count(), 4);
+
+    let (_, _, _, frames) = decode(&encoder.finish().unwrap());
+    let delays: Vec<u16> = frames.iter().map(|f| f.delay).collect();
+    assert_eq!(delays, vec![2, 3, 2, 3]);
+}
+
+#[test]
+fn gif_downscales_and_keeps_transparency() {
+    let mut img = RGBA::new(20, 10);
+    img.fill([200, 100, 50, 255]);
+    img.fill_rect(
+        &ranger::rendering::image::ImgRectangle::from_xywh(0, 0, 10, 10),
+        [0, 0, 0, 0],
+    );
+
+    let mut encoder = GifEncoder::new(Duration::from_millis(40));
+    encoder.set_scale(0.5);
+    encoder.set_loop_count(None);
+    encoder.add_frame(&img).unwrap();
+    let (w, h, loops, frames) = decode(&encoder.finish().unwrap());
+
+    assert_eq!((w, h), (10, 5));
+    assert_eq!(loops, None);
+    assert_eq!(frames[0].at(1, 2), None);
+    assert_eq!(frames[0].at(8, 2), Some([200, 100, 50]));
+}
+
+#[test]
+fn gif_errors() {
+    let mut encoder = GifEncoder::new(Duration::from_millis(40));
+    encoder.add_frame(&gradient(4, 4, 0)).unwrap();
+    match encoder.add_frame(&gradient(5, 4, 0)) {
+        Err(ImageError::Unsupported(_)) => (),
+        other => panic!("expected Unsupported, got {:?}", other.err()),
+    }
+
+    match GifEncoder::new(Duration::from_millis(40)).finish() {
+        Err(ImageError::Malformed(_)) => (),
+        other => panic!("expected Malformed, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn gif_median_cut_palette() {
+    let mut img = RGBA::new(5, 1);
+    img.set(0, 0, [0, 0, 0, 255]);
+    img.set(1, 0, [10, 0, 0, 255]);
+    img.set(2, 0, [250, 250, 250, 255]);
+    img.set(3, 0, [240, 250, 250, 255]);
+    // Transparent pixels don't count.
+    img.set(4, 0, [0, 0, 255, 0]);
+
+    let palette = median_cut(&img, 2);
+    assert_eq!(palette.len(), 2);
+    assert!(palette.iter().any(|c| c.r == 5 && c.b == 0));
+    assert!(palette.iter().any(|c| c.r == 245 && c.b == 250));
+
+    // Can't split past the distinct colors.
+    assert_eq!(median_cut(&img, 16).len(), 4);
+}
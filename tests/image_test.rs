@@ -1,6 +1,13 @@
 extern crate ranger;
 
+use std::rc::Rc;
+
+use ranger::rendering::blend::BlendMode;
+use ranger::rendering::clear_mode::ClearMode;
+use ranger::rendering::color::Palette;
 use ranger::rendering::image;
+use ranger::rendering::image::ImageSource;
+use ranger::rendering::sprite_sheet::SpriteSheet;
 
 #[test]
 fn image_point_create() {
@@ -106,3 +113,307 @@ fn image_set_pixel_by_components() {
 
     assert_eq!(img.at(1, 0), [255, 127, 0, 0]);
 }
+
+#[test]
+fn sprite_sheet_from_grid() {
+    // 70x40 holds 3 columns and 2 rows of 20x20 frames with leftovers.
+    let sheet = SpriteSheet::from_grid(image::RGBA::new(70, 40), 20, 20);
+    assert_eq!(sheet.frame_count(), 6);
+
+    let f = sheet.frame(4).unwrap();
+    assert_eq!(f.min, image::ImgPoint::from_xy(20, 20));
+    assert_eq!(f.w(), 20);
+    assert_eq!(f.h(), 20);
+    assert!(sheet.frame(6).is_none());
+}
+
+#[test]
+fn blend_none_replaces() {
+    let src = [10, 20, 30, 40];
+    assert_eq!(BlendMode::None.blend(src, [200, 200, 200, 255]), src);
+}
+
+#[test]
+fn blend_alpha() {
+    let dst = [0, 0, 255, 255];
+
+    // Opaque and transparent sources
+    assert_eq!(
+        BlendMode::Alpha.blend([255, 0, 0, 255], dst),
+        [255, 0, 0, 255]
+    );
+    assert_eq!(BlendMode::Alpha.blend([255, 0, 0, 0], dst), dst);
+
+    // Half way
+    assert_eq!(
+        BlendMode::Alpha.blend([255, 0, 0, 128], dst),
+        [128, 0, 127, 255]
+    );
+
+    // Onto a transparent destination the alpha accumulates.
+    assert_eq!(
+        BlendMode::Alpha.blend([255, 255, 255, 128], [0, 0, 0, 0])[3],
+        128
+    );
+}
+
+#[test]
+fn blend_additive_saturates() {
+    assert_eq!(
+        BlendMode::Additive.blend([200, 100, 0, 255], [100, 100, 100, 255]),
+        [255, 200, 100, 255]
+    );
+    assert_eq!(
+        BlendMode::Additive.blend([200, 100, 0, 0], [100, 100, 100, 255]),
+        [100, 100, 100, 255]
+    );
+}
+
+#[test]
+fn blend_multiply_and_screen() {
+    let dst = [200, 100, 0, 255];
+
+    assert_eq!(
+        BlendMode::Multiply.blend([255, 128, 0, 255], dst),
+        [200, 50, 0, 255]
+    );
+    // A transparent source has no effect.
+    assert_eq!(BlendMode::Multiply.blend([0, 0, 0, 0], dst), dst);
+
+    assert_eq!(
+        BlendMode::Screen.blend([0, 255, 128, 255], dst),
+        [200, 255, 128, 255]
+    );
+    assert_eq!(BlendMode::Screen.blend([255, 255, 255, 0], dst), dst);
+}
+
+#[test]
+fn image_blend_pixel() {
+    let mut img = image::RGBA::new(2, 2);
+    img.set(0, 0, [0, 0, 255, 255]);
+
+    img.blend(0, 0, [255, 0, 0, 255], BlendMode::Additive);
+    assert_eq!(img.at(0, 0), [255, 0, 255, 255]);
+
+    // Outside of the bounds is ignored.
+    img.blend(5, 5, [255, 0, 0, 255], BlendMode::Alpha);
+}
+
+#[test]
+fn clear_mode_checkerboard() {
+    let mode = ClearMode::Checkerboard {
+        size: 10,
+        dark: Palette::BLACK(),
+        light: Palette::WHITE(255),
+    };
+
+    assert_eq!(mode.color_at(0, 0, 100), Some(Palette::BLACK()));
+    assert_eq!(mode.color_at(9, 9, 100), Some(Palette::BLACK()));
+    assert_eq!(mode.color_at(10, 0, 100), Some(Palette::WHITE(255)));
+    assert_eq!(mode.color_at(0, 10, 100), Some(Palette::WHITE(255)));
+    assert_eq!(mode.color_at(15, 15, 100), Some(Palette::BLACK()));
+}
+
+#[test]
+fn clear_mode_vertical_gradient() {
+    let mode = ClearMode::VerticalGradient {
+        top: Palette::RGB(0, 0, 0),
+        bottom: Palette::RGB(200, 100, 0),
+    };
+
+    assert_eq!(mode.color_at(0, 0, 101), Some(Palette::RGB(0, 0, 0)));
+    assert_eq!(mode.color_at(50, 50, 101), Some(Palette::RGB(100, 50, 0)));
+    assert_eq!(mode.color_at(0, 100, 101), Some(Palette::RGB(200, 100, 0)));
+
+    // A one pixel high window is the top color.
+    assert_eq!(mode.color_at(0, 0, 1), Some(Palette::RGB(0, 0, 0)));
+}
+
+#[test]
+fn clear_mode_solid_and_image() {
+    let solid = ClearMode::Solid(Palette::RED());
+    assert_eq!(solid.color_at(123, 456, 10), Some(Palette::RED()));
+
+    let image = ClearMode::Image(Rc::new(image::RGBA::new(4, 4)));
+    assert_eq!(image.color_at(0, 0, 10), None);
+}
+
+// A 3x2 image whose pixels encode their coordinates.
+fn coords_image() -> image::RGBA {
+    let mut img = image::RGBA::new(3, 2);
+    for y in 0..2 {
+        for x in 0..3 {
+            img.set(x, y, [x as u8, y as u8, 0, 255]);
+        }
+    }
+    img
+}
+
+#[test]
+fn image_rectangle_intersect() {
+    let a = image::ImgRectangle::from_xywh(0, 0, 10, 10);
+    let b = image::ImgRectangle::from_xywh(5, 8, 10, 10);
+    assert_eq!(a.intersect(&b), image::ImgRectangle::from_xywh(5, 8, 5, 2));
+
+    let c = image::ImgRectangle::from_xywh(20, 20, 2, 2);
+    assert!(a.intersect(&c).is_empty());
+    assert!(!a.is_empty());
+}
+
+#[test]
+fn image_sub_image_shares_pixels() {
+    let mut img = coords_image();
+
+    {
+        let sub = img.sub_image(&image::ImgRectangle::from_xywh(1, 1, 5, 5));
+        // Clipped to the image, coordinates are the parent's.
+        assert_eq!(*sub.bounds(), image::ImgRectangle::from_xywh(1, 1, 2, 1));
+        assert_eq!(sub.at(2, 1), [2, 1, 0, 255]);
+        assert_eq!(sub.at(0, 0), [0; 4]);
+
+        let copy = sub.to_image();
+        assert_eq!(copy.bounds().w(), 2);
+        assert_eq!(copy.at(0, 0), [1, 1, 0, 255]);
+    }
+
+    {
+        let mut sub = img.sub_image_mut(&image::ImgRectangle::from_xywh(1, 0, 1, 2));
+        sub.fill([9, 9, 9, 9]);
+        sub.set(0, 0, [1; 4]); // outside, ignored
+    }
+    assert_eq!(img.at(1, 0), [9, 9, 9, 9]);
+    assert_eq!(img.at(1, 1), [9, 9, 9, 9]);
+    assert_eq!(img.at(0, 0), [0, 0, 0, 255]);
+    assert_eq!(img.at(2, 1), [2, 1, 0, 255]);
+
+    let cropped = img.crop(&image::ImgRectangle::from_xywh(2, 0, 1, 2));
+    assert_eq!(cropped.at(0, 1), [2, 1, 0, 255]);
+}
+
+#[test]
+fn image_blit() {
+    let mut dst = image::RGBA::new(4, 4);
+    dst.fill([0, 0, 255, 255]);
+
+    let mut src = image::RGBA::new(2, 2);
+    src.fill([255, 0, 0, 128]);
+
+    // Replaces, clipped at the right edge.
+    dst.blit(&src, 3, 0, BlendMode::None);
+    assert_eq!(dst.at(3, 0), [255, 0, 0, 128]);
+    assert_eq!(dst.at(3, 1), [255, 0, 0, 128]);
+    assert_eq!(dst.at(2, 0), [0, 0, 255, 255]);
+
+    // Blended
+    dst.blit(&src, 0, 2, BlendMode::Alpha);
+    assert_eq!(dst.at(0, 2), [128, 0, 127, 255]);
+    assert_eq!(dst.at(0, 1), [0, 0, 255, 255]);
+
+    // From a sub-image
+    let coords = coords_image();
+    let sub = coords.sub_image(&image::ImgRectangle::from_xywh(1, 0, 2, 2));
+    dst.blit(&sub, 0, 0, BlendMode::None);
+    assert_eq!(dst.at(0, 0), [1, 0, 0, 255]);
+    assert_eq!(dst.at(1, 1), [2, 1, 0, 255]);
+}
+
+#[test]
+fn image_flips_and_rotations() {
+    let mut img = coords_image();
+    img.flip_horizontal();
+    assert_eq!(img.at(0, 0), [2, 0, 0, 255]);
+    assert_eq!(img.at(1, 1), [1, 1, 0, 255]);
+    assert_eq!(img.at(2, 1), [0, 1, 0, 255]);
+
+    let mut img = coords_image();
+    img.flip_vertical();
+    assert_eq!(img.at(0, 0), [0, 1, 0, 255]);
+    assert_eq!(img.at(2, 1), [2, 0, 0, 255]);
+
+    let mut img = coords_image();
+    img.rotate_180();
+    assert_eq!(img.at(0, 0), [2, 1, 0, 255]);
+    assert_eq!(img.at(2, 1), [0, 0, 0, 255]);
+
+    let img = coords_image();
+    let cw = img.rotate_cw();
+    assert_eq!((cw.bounds().w(), cw.bounds().h()), (2, 3));
+    // The left column becomes the top row.
+    assert_eq!(cw.at(0, 0), [0, 1, 0, 255]);
+    assert_eq!(cw.at(1, 0), [0, 0, 0, 255]);
+    assert_eq!(cw.at(1, 2), [2, 0, 0, 255]);
+
+    let ccw = img.rotate_ccw();
+    assert_eq!((ccw.bounds().w(), ccw.bounds().h()), (2, 3));
+    assert_eq!(ccw.at(0, 0), [2, 0, 0, 255]);
+    assert_eq!(ccw.at(1, 2), [0, 1, 0, 255]);
+
+    // Four quarter turns are the identity.
+    let back = img.rotate_cw().rotate_cw().rotate_cw().rotate_cw();
+    assert_eq!(back.pix, img.pix);
+}
+
+#[test]
+fn image_resize_nearest() {
+    let img = coords_image();
+
+    let big = img.resize_nearest(6, 4);
+    assert_eq!(big.at(0, 0), [0, 0, 0, 255]);
+    assert_eq!(big.at(1, 1), [0, 0, 0, 255]);
+    assert_eq!(big.at(2, 0), [1, 0, 0, 255]);
+    assert_eq!(big.at(5, 3), [2, 1, 0, 255]);
+
+    let small = big.resize_nearest(3, 2);
+    assert_eq!(small.pix, img.pix);
+}
+
+#[test]
+fn image_resize_bilinear() {
+    let mut img = image::RGBA::new(2, 1);
+    img.set(0, 0, [0, 0, 0, 255]);
+    img.set(1, 0, [200, 100, 0, 255]);
+
+    let wide = img.resize_bilinear(4, 1);
+    assert_eq!(wide.at(0, 0), [0, 0, 0, 255]);
+    assert_eq!(wide.at(1, 0), [50, 25, 0, 255]);
+    assert_eq!(wide.at(2, 0), [150, 75, 0, 255]);
+    assert_eq!(wide.at(3, 0), [200, 100, 0, 255]);
+
+    // Transparent neighbors don't bleed their color.
+    let mut img = image::RGBA::new(2, 1);
+    img.set(0, 0, [0, 0, 0, 0]);
+    img.set(1, 0, [255, 255, 255, 255]);
+    let wide = img.resize_bilinear(4, 1);
+    assert_eq!(wide.at(1, 0), [255, 255, 255, 64]);
+}
+
+#[test]
+fn image_fill_and_replace() {
+    let mut img = image::RGBA::new(3, 3);
+    img.fill([1, 2, 3, 4]);
+    img.fill_rect(&image::ImgRectangle::from_xywh(1, 1, 5, 5), [5, 6, 7, 8]);
+    assert_eq!(img.at(0, 0), [1, 2, 3, 4]);
+    assert_eq!(img.at(2, 2), [5, 6, 7, 8]);
+
+    assert_eq!(img.replace_color([5, 6, 7, 8], [0; 4]), 4);
+    assert_eq!(img.at(1, 1), [0; 4]);
+    assert_eq!(img.replace_color([5, 6, 7, 8], [0; 4]), 0);
+}
+
+#[test]
+fn image_premultiply() {
+    let mut img = image::RGBA::new(3, 1);
+    img.set(0, 0, [255, 128, 0, 128]);
+    img.set(1, 0, [100, 100, 100, 255]);
+    img.set(2, 0, [100, 100, 100, 0]);
+
+    img.premultiply();
+    assert_eq!(img.at(0, 0), [128, 64, 0, 128]);
+    assert_eq!(img.at(1, 0), [100, 100, 100, 255]);
+    assert_eq!(img.at(2, 0), [0, 0, 0, 0]);
+
+    img.unpremultiply();
+    assert_eq!(img.at(0, 0), [255, 128, 0, 128]);
+    assert_eq!(img.at(1, 0), [100, 100, 100, 255]);
+    assert_eq!(img.at(2, 0), [0, 0, 0, 0]);
+}
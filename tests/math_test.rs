@@ -3,6 +3,8 @@ extern crate ranger;
 use ranger::geometry::point::Point;
 use ranger::geometry::rectangle::Rectangle;
 use ranger::math::affine_transform::AffineTransform;
+use ranger::math::curve::{ArcLength, BSpline, CatmullRom, CubicBezier, Curve, QuadraticBezier};
+use ranger::math::interpolation::{Easing, Interpolation};
 use ranger::math::vector2::Vector2;
 
 const EPISION: f64 = 0.000001;
@@ -262,3 +264,290 @@ fn math_rotate_rectangle() {
     assert!(equal_abs(r.width, 14.142136));
     assert!(equal_abs(r.height, 14.142136));
 }
+
+// ------------------------------------------------------------------
+// Easing
+// ------------------------------------------------------------------
+const ALL_EASINGS: [Easing; 33] = [
+    Easing::Linear,
+    Easing::QuadIn,
+    Easing::QuadOut,
+    Easing::QuadInOut,
+    Easing::CubicIn,
+    Easing::CubicOut,
+    Easing::CubicInOut,
+    Easing::QuartIn,
+    Easing::QuartOut,
+    Easing::QuartInOut,
+    Easing::QuintIn,
+    Easing::QuintOut,
+    Easing::QuintInOut,
+    Easing::SineIn,
+    Easing::SineOut,
+    Easing::SineInOut,
+    Easing::ExpoIn,
+    Easing::ExpoOut,
+    Easing::ExpoInOut,
+    Easing::CircIn,
+    Easing::CircOut,
+    Easing::CircInOut,
+    Easing::BackIn,
+    Easing::BackOut,
+    Easing::BackInOut,
+    Easing::ElasticIn,
+    Easing::ElasticOut,
+    Easing::ElasticInOut,
+    Easing::BounceIn,
+    Easing::BounceOut,
+    Easing::BounceInOut,
+    Easing::SmoothStep,
+    Easing::SmootherStep,
+];
+
+#[test]
+fn math_easing_endpoints() {
+    for easing in ALL_EASINGS.iter() {
+        assert!(equal_abs(easing.ease(0.0), 0.0), "{:?} at 0", easing);
+        assert!(equal_abs(easing.ease(1.0), 1.0), "{:?} at 1", easing);
+    }
+
+    let bezier = Easing::CubicBezier(0.25, 0.1, 0.25, 1.0);
+    assert_eq!(bezier.ease(0.0), 0.0);
+    assert_eq!(bezier.ease(1.0), 1.0);
+}
+
+#[test]
+fn math_easing_clamps_t() {
+    assert_eq!(Easing::QuadIn.ease(-1.0), 0.0);
+    assert_eq!(Easing::QuadIn.ease(2.0), 1.0);
+}
+
+#[test]
+fn math_easing_monotonic() {
+    let mut easings = ALL_EASINGS.to_vec();
+    easings.push(Easing::CubicBezier(0.42, 0.0, 0.58, 1.0));
+
+    for easing in easings.iter().filter(|e| e.is_monotonic()) {
+        let mut previous = easing.ease(0.0);
+        for i in 1..=100 {
+            let value = easing.ease(i as f64 / 100.0);
+            assert!(value >= previous - EPISION, "{:?} at step {}", easing, i);
+            assert!((0.0..=1.0 + EPISION).contains(&value));
+            previous = value;
+        }
+    }
+}
+
+#[test]
+fn math_easing_in_out_symmetric() {
+    assert!(equal_abs(Easing::QuadInOut.ease(0.5), 0.5));
+    assert!(equal_abs(Easing::SineInOut.ease(0.5), 0.5));
+    assert!(equal_abs(Easing::SmoothStep.ease(0.5), 0.5));
+    assert!(equal_abs(Easing::BounceInOut.ease(0.5), 0.5));
+}
+
+#[test]
+fn math_easing_cubic_bezier_linear() {
+    // Control points on the diagonal produce a linear curve.
+    let bezier = Easing::CubicBezier(1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0);
+    for i in 0..=10 {
+        let t = i as f64 / 10.0;
+        assert!((bezier.ease(t) - t).abs() < 0.0001);
+    }
+}
+
+#[test]
+fn math_smoothstep_edges() {
+    assert_eq!(Interpolation::smoothstep(10.0, 20.0, 5.0), 0.0);
+    assert_eq!(Interpolation::smoothstep(10.0, 20.0, 25.0), 1.0);
+    assert!(equal_abs(Interpolation::smoothstep(10.0, 20.0, 15.0), 0.5));
+    assert!(equal_abs(
+        Interpolation::smootherstep(10.0, 20.0, 15.0),
+        0.5
+    ));
+}
+
+fn close_to(a: &Point, b: &Point, eps: f64) -> bool {
+    (a.x - b.x).abs() < eps && (a.y - b.y).abs() < eps
+}
+
+fn cubic() -> CubicBezier {
+    CubicBezier::new(
+        Point::from_xy(0.0, 0.0),
+        Point::from_xy(0.0, 100.0),
+        Point::from_xy(100.0, 100.0),
+        Point::from_xy(100.0, 0.0),
+    )
+}
+
+#[test]
+fn curve_bezier_endpoints_and_midpoint() {
+    let q = QuadraticBezier::new(
+        Point::from_xy(0.0, 0.0),
+        Point::from_xy(50.0, 100.0),
+        Point::from_xy(100.0, 0.0),
+    );
+    assert!(close_to(
+        &q.evaluate(0.0),
+        &Point::from_xy(0.0, 0.0),
+        EPISION
+    ));
+    assert!(close_to(
+        &q.evaluate(1.0),
+        &Point::from_xy(100.0, 0.0),
+        EPISION
+    ));
+    assert!(close_to(
+        &q.evaluate(0.5),
+        &Point::from_xy(50.0, 50.0),
+        EPISION
+    ));
+
+    let c = cubic();
+    assert!(close_to(
+        &c.evaluate(0.5),
+        &Point::from_xy(50.0, 75.0),
+        EPISION
+    ));
+}
+
+#[test]
+fn curve_derivatives_match_finite_differences() {
+    let h = 1.0e-6;
+    let curves: Vec<Box<dyn Curve>> = vec![
+        Box::new(cubic()),
+        Box::new(CatmullRom::new(
+            vec![
+                Point::from_xy(0.0, 0.0),
+                Point::from_xy(10.0, 30.0),
+                Point::from_xy(40.0, 20.0),
+                Point::from_xy(60.0, 50.0),
+            ],
+            false,
+        )),
+        Box::new(BSpline::new(
+            vec![
+                Point::from_xy(0.0, 0.0),
+                Point::from_xy(10.0, 30.0),
+                Point::from_xy(40.0, 20.0),
+                Point::from_xy(60.0, 50.0),
+            ],
+            true,
+        )),
+    ];
+
+    for curve in curves.iter() {
+        for &t in [0.1, 0.3, 0.55, 0.8].iter() {
+            let a = curve.evaluate(t - h);
+            let b = curve.evaluate(t + h);
+            let numeric = Point::from_xy((b.x - a.x) / (2.0 * h), (b.y - a.y) / (2.0 * h));
+            assert!(close_to(&curve.derivative(t), &numeric, 1.0e-3));
+        }
+    }
+}
+
+#[test]
+fn curve_catmull_rom_passes_through_points() {
+    let points = vec![
+        Point::from_xy(0.0, 0.0),
+        Point::from_xy(10.0, 30.0),
+        Point::from_xy(40.0, 20.0),
+        Point::from_xy(60.0, 50.0),
+    ];
+    let spline = CatmullRom::new(points.clone(), false);
+    assert_eq!(spline.segments(), 3);
+
+    for (i, p) in points.iter().enumerate() {
+        let t = i as f64 / 3.0;
+        assert!(close_to(&spline.evaluate(t), p, EPISION));
+    }
+
+    let closed = CatmullRom::new(points.clone(), true);
+    assert_eq!(closed.segments(), 4);
+    assert!(close_to(&closed.evaluate(1.0), &points[0], EPISION));
+}
+
+#[test]
+fn curve_bspline_is_clamped() {
+    let points = vec![
+        Point::from_xy(0.0, 0.0),
+        Point::from_xy(10.0, 30.0),
+        Point::from_xy(40.0, 20.0),
+        Point::from_xy(60.0, 50.0),
+    ];
+    let spline = BSpline::new(points.clone(), false);
+    assert!(close_to(&spline.evaluate(0.0), &points[0], EPISION));
+    assert!(close_to(&spline.evaluate(1.0), &points[3], EPISION));
+
+    // Interior control points are only approximated.
+    let closed = BSpline::new(points, true);
+    assert!(close_to(
+        &closed.evaluate(0.0),
+        &closed.evaluate(1.0),
+        EPISION
+    ));
+}
+
+#[test]
+fn curve_flatten_within_tolerance() {
+    let c = cubic();
+    let tolerance = 0.25;
+    let mut out = Vec::new();
+    c.flatten(tolerance, &mut out);
+
+    assert!(out.len() > 4);
+    assert_eq!(out[0], c.evaluate(0.0));
+    assert_eq!(out[out.len() - 1], c.evaluate(1.0));
+
+    // Every curve point is near the polyline.
+    for i in 0..=200 {
+        let p = c.evaluate(i as f64 / 200.0);
+        let nearest = out
+            .windows(2)
+            .map(|seg| {
+                let (a, b) = (seg[0], seg[1]);
+                let d = b - a;
+                let len2 = d.x * d.x + d.y * d.y;
+                let s = if len2 == 0.0 {
+                    0.0
+                } else {
+                    (((p.x - a.x) * d.x + (p.y - a.y) * d.y) / len2)
+                        .max(0.0)
+                        .min(1.0)
+                };
+                p.distance_between(&Point::from_xy(a.x + d.x * s, a.y + d.y * s))
+            })
+            .fold(std::f64::MAX, f64::min);
+        assert!(nearest <= tolerance * 1.5);
+    }
+
+    // A tighter tolerance needs more points.
+    let mut fine = Vec::new();
+    c.flatten(0.01, &mut fine);
+    assert!(fine.len() > out.len());
+}
+
+#[test]
+fn curve_arc_length_parameterization() {
+    // A straight quadratic with its control point off-center moves at a
+    // non-uniform speed.
+    let q = QuadraticBezier::new(
+        Point::from_xy(0.0, 0.0),
+        Point::from_xy(90.0, 0.0),
+        Point::from_xy(100.0, 0.0),
+    );
+    let table = ArcLength::new(&q, 128);
+    assert!((table.length() - 100.0).abs() < 1.0e-6);
+    assert!((q.length() - 100.0).abs() < 1.0e-6);
+
+    for &d in [0.0, 25.0, 50.0, 75.0, 100.0].iter() {
+        let p = q.evaluate(table.parameter(d));
+        assert!((p.x - d).abs() < 0.05);
+    }
+
+    assert_eq!(table.parameter(-5.0), 0.0);
+    assert_eq!(table.parameter(500.0), 1.0);
+    assert_eq!(table.parameter(f64::NAN), 0.0);
+    let p = q.evaluate(table.parameter_normalized(0.5));
+    assert!((p.x - 50.0).abs() < 0.05);
+}
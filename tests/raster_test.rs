@@ -0,0 +1,712 @@
+extern crate ranger;
+
+use ranger::geometry::clipping::PolygonClipper;
+use ranger::geometry::point::Point;
+use ranger::geometry::rectangle::Rectangle;
+use ranger::rendering::color::Palette;
+use ranger::rendering::fx_polygon::{FXPolygon, FillRule};
+use ranger::rendering::fx_triangle::FXTriangle;
+use ranger::rendering::image::RGBA;
+use ranger::rendering::stroke::{LineCap, LineJoin, StrokeStyle, Stroker};
+use ranger::rendering::texture_sampler::{TextureFilter, TextureSampler, TextureWrap};
+use ranger::rendering::wu_line::wu_line;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+// 2x2 texture:
+// R G
+// B W
+fn quad_texture() -> RGBA {
+    let mut img = RGBA::new(2, 2);
+    img.set(0, 0, RED);
+    img.set(1, 0, GREEN);
+    img.set(0, 1, BLUE);
+    img.set(1, 1, WHITE);
+    img
+}
+
+// ------------------------------------------------------------------
+// Sampling
+// ------------------------------------------------------------------
+#[test]
+fn sampler_nearest() {
+    let img = quad_texture();
+    let s = TextureSampler::new(TextureFilter::Nearest, TextureWrap::Clamp);
+
+    assert_eq!(s.sample(&img, 0.5, 0.5), RED);
+    assert_eq!(s.sample(&img, 1.99, 0.0), GREEN);
+    assert_eq!(s.sample(&img, 0.0, 1.5), BLUE);
+    assert_eq!(s.sample(&img, 1.5, 1.5), WHITE);
+}
+
+#[test]
+fn sampler_wrap_modes() {
+    let img = quad_texture();
+    let clamp = TextureSampler::new(TextureFilter::Nearest, TextureWrap::Clamp);
+    let repeat = TextureSampler::new(TextureFilter::Nearest, TextureWrap::Repeat);
+
+    assert_eq!(clamp.sample(&img, 5.5, -3.0), GREEN);
+    assert_eq!(repeat.sample(&img, 2.5, 0.5), RED);
+    assert_eq!(repeat.sample(&img, -0.5, 0.5), GREEN);
+    assert_eq!(repeat.sample(&img, 3.5, 3.5), WHITE);
+}
+
+#[test]
+fn sampler_bilinear() {
+    let img = quad_texture();
+    let s = TextureSampler::new(TextureFilter::Bilinear, TextureWrap::Clamp);
+
+    // Texel centers are exact
+    assert_eq!(s.sample(&img, 0.5, 0.5), RED);
+    assert_eq!(s.sample(&img, 1.5, 1.5), WHITE);
+
+    // Half way between red and green
+    assert_eq!(s.sample(&img, 1.0, 0.5), [128, 128, 0, 255]);
+
+    // The center blends all four
+    assert_eq!(s.sample(&img, 1.0, 1.0), [128, 128, 128, 255]);
+}
+
+// ------------------------------------------------------------------
+// Textured triangles
+// ------------------------------------------------------------------
+fn draw_textured_quad(size: f64, sampler: &TextureSampler) -> (RGBA, Vec<u32>) {
+    let texture = quad_texture();
+    let s = size as usize;
+    let mut target = RGBA::new(s, s);
+    let mut hits = vec![0u32; s * s];
+
+    let mut tri = FXTriangle::new();
+
+    tri.set(0.0, 0.0, size, 0.0, size, size);
+    tri.set_uvs(0.0, 0.0, 1.0, 0.0, 1.0, 1.0);
+    tri.draw_textured(&texture, sampler, |x, y, c| {
+        target.set(x as usize, y as usize, c);
+        hits[y as usize * s + x as usize] += 1;
+    });
+
+    // Opposite winding
+    tri.set(0.0, 0.0, 0.0, size, size, size);
+    tri.set_uvs(0.0, 0.0, 0.0, 1.0, 1.0, 1.0);
+    tri.draw_textured(&texture, sampler, |x, y, c| {
+        target.set(x as usize, y as usize, c);
+        hits[y as usize * s + x as usize] += 1;
+    });
+
+    (target, hits)
+}
+
+#[test]
+fn textured_quad_covers_each_pixel_once() {
+    let s = TextureSampler::new(TextureFilter::Nearest, TextureWrap::Clamp);
+    let (_, hits) = draw_textured_quad(8.0, &s);
+
+    assert!(hits.iter().all(|h| *h == 1));
+}
+
+#[test]
+fn textured_quad_maps_texels() {
+    let s = TextureSampler::new(TextureFilter::Nearest, TextureWrap::Clamp);
+    let (target, _) = draw_textured_quad(8.0, &s);
+
+    // Each texel covers a 4x4 block
+    assert_eq!(target.at(0, 0), RED);
+    assert_eq!(target.at(3, 3), RED);
+    assert_eq!(target.at(4, 0), GREEN);
+    assert_eq!(target.at(7, 3), GREEN);
+    assert_eq!(target.at(0, 4), BLUE);
+    assert_eq!(target.at(3, 7), BLUE);
+    assert_eq!(target.at(4, 4), WHITE);
+    assert_eq!(target.at(7, 7), WHITE);
+}
+
+#[test]
+fn textured_quad_bilinear_blends() {
+    let s = TextureSampler::new(TextureFilter::Bilinear, TextureWrap::Clamp);
+    let (target, _) = draw_textured_quad(8.0, &s);
+
+    // Corners are clamped to the corner texels.
+    assert_eq!(target.at(0, 0), RED);
+    assert_eq!(target.at(7, 7), WHITE);
+
+    // Pixels straddling the texel boundary are blended.
+    let c = target.at(4, 0);
+    assert!(c[0] > 0 && c[0] < 255);
+    assert!(c[1] > 0 && c[1] < 255);
+}
+
+#[test]
+fn textured_degenerate_triangle_draws_nothing() {
+    let texture = quad_texture();
+    let s = TextureSampler::new(TextureFilter::Nearest, TextureWrap::Clamp);
+    let mut tri = FXTriangle::new();
+    tri.set(0.0, 0.0, 5.0, 5.0, 10.0, 10.0);
+    tri.set_uvs(0.0, 0.0, 0.5, 0.5, 1.0, 1.0);
+
+    let mut count = 0;
+    tri.draw_textured(&texture, &s, |_, _, _| count += 1);
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn non_finite_triangle_draws_nothing() {
+    let mut tri = FXTriangle::new();
+    tri.set(0.0, 0.0, 10.0, f64::NAN, 0.0, 10.0);
+    let c = Palette::RGBA(10, 20, 30, 40);
+    tri.set_colors(&c, &c, &c);
+
+    let mut count = 0;
+    tri.draw_shaded(|_, _, _| count += 1);
+    tri.set(0.0, 0.0, f64::INFINITY, 0.0, 0.0, 10.0);
+    tri.draw_shaded(|_, _, _| count += 1);
+    assert_eq!(count, 0);
+}
+
+// ------------------------------------------------------------------
+// Shaded triangles
+// ------------------------------------------------------------------
+#[test]
+fn shaded_triangle_solid_color() {
+    let mut tri = FXTriangle::new();
+    tri.set(0.0, 0.0, 10.0, 0.0, 0.0, 10.0);
+    let c = Palette::RGBA(10, 20, 30, 40);
+    tri.set_colors(&c, &c, &c);
+
+    let mut count = 0;
+    tri.draw_shaded(|_, _, color| {
+        assert_eq!(color, [10, 20, 30, 40]);
+        count += 1;
+    });
+    assert!(count > 0);
+}
+
+#[test]
+fn shaded_triangle_gradient() {
+    // A horizontal gradient from black (left) to white (right). The third
+    // vertex shares the left color.
+    let mut tri = FXTriangle::new();
+    tri.set(0.0, 0.0, 100.0, 0.0, 0.0, 100.0);
+    tri.set_colors(
+        &Palette::RGBA(0, 0, 0, 255),
+        &Palette::RGBA(255, 255, 255, 0),
+        &Palette::RGBA(0, 0, 0, 255),
+    );
+
+    let mut row: Vec<(i32, [u8; 4])> = Vec::new();
+    tri.draw_shaded(|x, y, color| {
+        if y == 0 {
+            row.push((x, color));
+        }
+    });
+
+    assert_eq!(row.len(), 100);
+    // Values are sampled at pixel centers
+    let (x, first) = row[0];
+    assert_eq!(x, 0);
+    assert_eq!(first[0], 1);
+    assert_eq!(first[3], 254);
+
+    let (x, mid) = row[50];
+    assert_eq!(x, 50);
+    assert_eq!(mid[0], 129);
+
+    // Strictly increasing red, decreasing alpha
+    for pair in row.windows(2) {
+        assert!(pair[1].1[0] > pair[0].1[0]);
+        assert!(pair[1].1[3] < pair[0].1[3]);
+    }
+}
+
+// ------------------------------------------------------------------
+// Polygons
+// ------------------------------------------------------------------
+const GRID: usize = 32;
+
+fn points(coords: &[(f64, f64)]) -> Vec<Point> {
+    coords.iter().map(|c| Point::from_tup(*c)).collect()
+}
+
+// Coverage counts for each pixel of a GRID x GRID area. Pixels outside
+// the area are ignored.
+fn coverage(poly: &FXPolygon, hits: &mut [u32]) {
+    poly.scan(|x1, x2, y| {
+        if y < 0 || y >= GRID as i32 {
+            return;
+        }
+        for x in x1.max(0)..x2.min(GRID as i32) {
+            hits[y as usize * GRID + x as usize] += 1;
+        }
+    });
+}
+
+// The spans FXTriangle::draw fills.
+fn triangle_coverage(tri: &FXTriangle, hits: &mut [u32]) {
+    tri.scan(|x1, x2, y| {
+        for x in x1..x2 {
+            hits[y as usize * GRID + x as usize] += 1;
+        }
+    });
+}
+
+fn covered(hits: &[u32], x: usize, y: usize) -> bool {
+    hits[y * GRID + x] > 0
+}
+
+#[test]
+fn polygon_square() {
+    let mut poly = FXPolygon::new();
+    poly.set(&points(&[(2.0, 2.0), (10.0, 2.0), (10.0, 6.0), (2.0, 6.0)]));
+
+    let mut hits = vec![0u32; GRID * GRID];
+    coverage(&poly, &mut hits);
+
+    assert_eq!(hits.iter().sum::<u32>(), 8 * 4);
+    assert!(covered(&hits, 2, 2));
+    assert!(covered(&hits, 9, 5));
+    assert!(!covered(&hits, 10, 5));
+    assert!(!covered(&hits, 9, 6));
+}
+
+#[test]
+fn polygon_clip_keeps_visible_coverage() {
+    // A diamond, with a square hole, that overhangs the grid on every
+    // side. Its edges cross the grid's sides at whole pixels so clipping
+    // is exact.
+    let mut poly = FXPolygon::new();
+    poly.set_fill_rule(FillRule::EvenOdd);
+    poly.add_contour(&points(&[
+        (16.0, -20.0),
+        (52.0, 16.0),
+        (16.0, 52.0),
+        (-20.0, 16.0),
+    ]));
+    poly.add_contour(&points(&[
+        (12.0, 12.0),
+        (20.0, 12.0),
+        (20.0, 20.0),
+        (12.0, 20.0),
+    ]));
+
+    let mut expected = vec![0u32; GRID * GRID];
+    coverage(&poly, &mut expected);
+
+    let rect = Rectangle::from_min_max(0.0, 0.0, GRID as f64, GRID as f64);
+    poly.clip(&rect, &mut PolygonClipper::new());
+
+    let mut hits = vec![0u32; GRID * GRID];
+    let mut outside = 0;
+    poly.scan(|x1, x2, y| {
+        for x in x1..x2 {
+            if x < 0 || y < 0 || x >= GRID as i32 || y >= GRID as i32 {
+                outside += 1;
+            } else {
+                hits[y as usize * GRID + x as usize] += 1;
+            }
+        }
+    });
+
+    assert_eq!(outside, 0);
+    assert_eq!(hits, expected);
+    assert!(!covered(&hits, 16, 16));
+    assert!(covered(&hits, 0, 16));
+
+    // Clipping away everything leaves nothing to draw.
+    let far = Rectangle::from_min_max(1000.0, 1000.0, 1010.0, 1010.0);
+    poly.clip(&far, &mut PolygonClipper::new());
+    let mut spans = 0;
+    poly.scan(|_, _, _| spans += 1);
+    assert_eq!(spans, 0);
+}
+
+#[test]
+fn polygon_concave() {
+    // A "U" shape
+    let mut poly = FXPolygon::new();
+    poly.set(&points(&[
+        (0.0, 0.0),
+        (4.0, 0.0),
+        (4.0, 8.0),
+        (8.0, 8.0),
+        (8.0, 0.0),
+        (12.0, 0.0),
+        (12.0, 12.0),
+        (0.0, 12.0),
+    ]));
+
+    let mut hits = vec![0u32; GRID * GRID];
+    coverage(&poly, &mut hits);
+
+    assert!(covered(&hits, 1, 1));
+    assert!(covered(&hits, 10, 1));
+    assert!(!covered(&hits, 6, 4));
+    assert!(covered(&hits, 6, 10));
+    assert_eq!(hits.iter().sum::<u32>(), 12 * 12 - 4 * 8);
+}
+
+fn square_with_hole(reverse_hole: bool) -> FXPolygon {
+    let mut poly = FXPolygon::new();
+    poly.add_contour(&points(&[
+        (0.0, 0.0),
+        (20.0, 0.0),
+        (20.0, 20.0),
+        (0.0, 20.0),
+    ]));
+    let mut hole = points(&[(5.0, 5.0), (15.0, 5.0), (15.0, 15.0), (5.0, 15.0)]);
+    if reverse_hole {
+        hole.reverse();
+    }
+    poly.add_contour(&hole);
+    poly
+}
+
+#[test]
+fn polygon_hole_fill_rules() {
+    let mut hits = vec![0u32; GRID * GRID];
+
+    // Even-odd always punches the hole
+    let mut poly = square_with_hole(false);
+    poly.set_fill_rule(FillRule::EvenOdd);
+    coverage(&poly, &mut hits);
+    assert!(!covered(&hits, 10, 10));
+    assert_eq!(hits.iter().sum::<u32>(), 400 - 100);
+
+    // Non-zero only punches an oppositely wound hole
+    let mut hits = vec![0u32; GRID * GRID];
+    let mut poly = square_with_hole(false);
+    poly.set_fill_rule(FillRule::NonZero);
+    coverage(&poly, &mut hits);
+    assert!(covered(&hits, 10, 10));
+    assert_eq!(hits.iter().sum::<u32>(), 400);
+
+    let mut hits = vec![0u32; GRID * GRID];
+    let mut poly = square_with_hole(true);
+    poly.set_fill_rule(FillRule::NonZero);
+    coverage(&poly, &mut hits);
+    assert!(!covered(&hits, 10, 10));
+    assert_eq!(hits.iter().sum::<u32>(), 400 - 100);
+}
+
+#[test]
+fn polygon_self_intersecting_star() {
+    // A pentagram, the center pentagon is wound twice.
+    let star = points(&[
+        (16.0, 0.0),
+        (26.0, 30.0),
+        (0.0, 11.0),
+        (31.0, 11.0),
+        (6.0, 30.0),
+    ]);
+
+    let mut poly = FXPolygon::new();
+    poly.set(&star);
+
+    poly.set_fill_rule(FillRule::EvenOdd);
+    let mut hits = vec![0u32; GRID * GRID];
+    coverage(&poly, &mut hits);
+    assert!(!covered(&hits, 16, 16));
+    assert!(covered(&hits, 16, 4));
+
+    poly.set_fill_rule(FillRule::NonZero);
+    let mut hits = vec![0u32; GRID * GRID];
+    coverage(&poly, &mut hits);
+    assert!(covered(&hits, 16, 16));
+    assert!(covered(&hits, 16, 4));
+
+    // No pixel is ever drawn twice
+    assert!(hits.iter().all(|h| *h <= 1));
+}
+
+#[test]
+fn polygon_adjacent_shapes_share_edges() {
+    // Two quads sharing a slanted edge tile a square exactly.
+    let mut left = FXPolygon::new();
+    left.set(&points(&[
+        (0.0, 0.0),
+        (20.0, 0.0),
+        (8.0, 24.0),
+        (0.0, 24.0),
+    ]));
+    let mut right = FXPolygon::new();
+    right.set(&points(&[
+        (20.0, 0.0),
+        (30.0, 0.0),
+        (30.0, 24.0),
+        (8.0, 24.0),
+    ]));
+
+    let mut hits = vec![0u32; GRID * GRID];
+    coverage(&left, &mut hits);
+    coverage(&right, &mut hits);
+
+    for y in 0..24 {
+        for x in 0..30 {
+            assert_eq!(hits[y * GRID + x], 1, "pixel ({}, {})", x, y);
+        }
+    }
+    assert_eq!(hits.iter().sum::<u32>(), 30 * 24);
+}
+
+#[test]
+fn polygon_matches_triangle_coverage() {
+    let (a, b, c) = ((3.7, 1.2), (27.1, 9.9), (11.5, 29.4));
+
+    let mut tri = FXTriangle::new();
+    tri.set(a.0, a.1, b.0, b.1, c.0, c.1);
+    let mut tri_hits = vec![0u32; GRID * GRID];
+    tri.draw_shaded(|x, y, _| tri_hits[y as usize * GRID + x as usize] += 1);
+
+    let mut poly = FXPolygon::new();
+    poly.set(&points(&[a, b, c]));
+    let mut poly_hits = vec![0u32; GRID * GRID];
+    coverage(&poly, &mut poly_hits);
+
+    assert_eq!(tri_hits, poly_hits);
+
+    // The flat fill too, in either winding.
+    let mut flat_hits = vec![0u32; GRID * GRID];
+    triangle_coverage(&tri, &mut flat_hits);
+    assert_eq!(flat_hits, poly_hits);
+
+    tri.set(a.0, a.1, c.0, c.1, b.0, b.1);
+    let mut flat_hits = vec![0u32; GRID * GRID];
+    triangle_coverage(&tri, &mut flat_hits);
+    assert_eq!(flat_hits, poly_hits);
+}
+
+#[test]
+fn triangle_and_polygon_adjacent_dont_overlap() {
+    // A triangle and a quad sharing a slanted edge tile a rectangle.
+    let mut tri = FXTriangle::new();
+    tri.set(0.0, 0.0, 20.0, 0.0, 0.0, 24.0);
+    let mut poly = FXPolygon::new();
+    poly.set(&points(&[
+        (20.0, 0.0),
+        (30.0, 0.0),
+        (30.0, 24.0),
+        (0.0, 24.0),
+    ]));
+
+    let mut hits = vec![0u32; GRID * GRID];
+    triangle_coverage(&tri, &mut hits);
+    coverage(&poly, &mut hits);
+
+    for y in 0..24 {
+        for x in 0..30 {
+            assert_eq!(hits[y * GRID + x], 1, "pixel ({}, {})", x, y);
+        }
+    }
+    assert_eq!(hits.iter().sum::<u32>(), 30 * 24);
+}
+
+#[test]
+fn clipped_triangle_matches_unclipped() {
+    // The Context fills triangles inside the clip rectangle with
+    // FXTriangle and those crossing it with a clipped FXPolygon. The
+    // visible pixels must be the same either way. The edges cross the
+    // clip rectangle, the grid plus an 8 pixel band, at whole pixels.
+    let (a, b, c) = ((16.0, -24.0), (56.0, 16.0), (16.0, 16.0));
+
+    let mut tri = FXTriangle::new();
+    tri.set(a.0, a.1, b.0, b.1, c.0, c.1);
+    let mut expected = vec![0u32; GRID * GRID];
+    tri.scan(|x1, x2, y| {
+        if y < 0 || y >= GRID as i32 {
+            return;
+        }
+        for x in x1.max(0)..x2.min(GRID as i32) {
+            expected[y as usize * GRID + x as usize] += 1;
+        }
+    });
+
+    let mut poly = FXPolygon::new();
+    poly.set(&points(&[a, b, c]));
+    poly.set_fill_rule(FillRule::NonZero);
+    let band = 8.0;
+    let rect = Rectangle::from_min_max(-band, -band, GRID as f64 + band, GRID as f64 + band);
+    poly.clip(&rect, &mut PolygonClipper::new());
+    let mut hits = vec![0u32; GRID * GRID];
+    coverage(&poly, &mut hits);
+
+    assert_eq!(hits, expected);
+    assert!(covered(&hits, GRID - 1, 15));
+    assert!(!covered(&hits, GRID - 1, 16));
+}
+
+#[test]
+fn polygon_degenerate_is_empty() {
+    let mut poly = FXPolygon::new();
+    let mut count = 0;
+
+    poly.scan(|_, _, _| count += 1);
+    poly.set(&points(&[(0.0, 5.0), (10.0, 5.0)]));
+    poly.scan(|_, _, _| count += 1);
+
+    assert_eq!(count, 0);
+}
+
+// ------------------------------------------------------------------
+// Anti-aliased lines
+// ------------------------------------------------------------------
+#[test]
+fn wu_line_horizontal() {
+    let mut plots: Vec<(i32, i32, f64)> = Vec::new();
+    wu_line(0.0, 5.0, 10.0, 5.0, |x, y, c| plots.push((x, y, c)));
+
+    // Everything lands on row 5
+    assert!(plots.iter().all(|p| p.1 == 5));
+    assert_eq!(plots.len(), 11);
+
+    // Interior pixels are fully covered, the ends half covered.
+    assert!(plots.iter().any(|p| p.0 == 5 && p.2 == 1.0));
+    assert!(plots.iter().any(|p| p.0 == 0 && p.2 == 0.5));
+}
+
+#[test]
+fn wu_line_coverage_per_column() {
+    // A shallow line: each interior column's coverage sums to one.
+    let mut columns = vec![0.0; GRID];
+    wu_line(0.0, 0.0, 20.0, 7.0, |x, _, c| columns[x as usize] += c);
+
+    for (x, total) in columns.iter().enumerate().take(20).skip(1) {
+        assert!((total - 1.0).abs() < 1.0e-9, "column {}: {}", x, total);
+    }
+}
+
+#[test]
+fn wu_line_steep() {
+    let mut rows = vec![0.0; GRID];
+    wu_line(3.0, 0.0, 5.0, 20.0, |x, y, c| {
+        assert!((2..=6).contains(&x));
+        rows[y as usize] += c;
+    });
+
+    for total in rows.iter().take(20).skip(1) {
+        assert!((total - 1.0).abs() < 1.0e-9);
+    }
+}
+
+// ------------------------------------------------------------------
+// Strokes
+// ------------------------------------------------------------------
+fn stroke_coverage(
+    stroker: &mut Stroker,
+    line: &[(f64, f64)],
+    closed: bool,
+    style: StrokeStyle,
+) -> Vec<u32> {
+    let mut hits = vec![0u32; GRID * GRID];
+    let poly = stroker.stroke(&points(line), closed, &style);
+    coverage(poly, &mut hits);
+    assert!(hits.iter().all(|h| *h <= 1), "pixels drawn more than once");
+    hits
+}
+
+#[test]
+fn stroke_segment_caps() {
+    let mut stroker = Stroker::new();
+    let line = [(8.0, 10.0), (18.0, 10.0)];
+
+    let butt = stroke_coverage(&mut stroker, &line, false, StrokeStyle::new(4.0));
+    assert_eq!(butt.iter().sum::<u32>(), 10 * 4);
+    assert!(covered(&butt, 8, 8));
+    assert!(!covered(&butt, 8, 7));
+    assert!(!covered(&butt, 7, 10));
+
+    let square = stroke_coverage(
+        &mut stroker,
+        &line,
+        false,
+        StrokeStyle::new(4.0).with_cap(LineCap::Square),
+    );
+    assert_eq!(square.iter().sum::<u32>(), 14 * 4);
+    assert!(covered(&square, 6, 10));
+
+    let round = stroke_coverage(
+        &mut stroker,
+        &line,
+        false,
+        StrokeStyle::new(4.0).with_cap(LineCap::Round),
+    );
+    let total = round.iter().sum::<u32>();
+    assert!(total > 40 && total < 56);
+}
+
+#[test]
+fn stroke_joins() {
+    let mut stroker = Stroker::new();
+    let corner = [(4.0, 4.0), (20.0, 4.0), (20.0, 20.0)];
+
+    let count = |stroker: &mut Stroker, join: LineJoin| -> (u32, bool) {
+        let hits = stroke_coverage(
+            stroker,
+            &corner,
+            false,
+            StrokeStyle::new(6.0).with_join(join),
+        );
+        (hits.iter().sum::<u32>(), covered(&hits, 22, 1))
+    };
+
+    let (miter, miter_corner) = count(&mut stroker, LineJoin::Miter);
+    let (round, round_corner) = count(&mut stroker, LineJoin::Round);
+    let (bevel, bevel_corner) = count(&mut stroker, LineJoin::Bevel);
+
+    // Only the miter reaches the outer corner.
+    assert!(miter_corner);
+    assert!(!round_corner);
+    assert!(!bevel_corner);
+    assert!(miter > round);
+    assert!(round > bevel);
+}
+
+#[test]
+fn stroke_miter_limit_bevels_sharp_corners() {
+    let mut stroker = Stroker::new();
+    // A very sharp turn
+    let spike = [(2.0, 16.0), (28.0, 14.0), (2.0, 12.0)];
+
+    let limited = stroke_coverage(&mut stroker, &spike, false, StrokeStyle::new(4.0));
+    let unlimited = stroke_coverage(
+        &mut stroker,
+        &spike,
+        false,
+        StrokeStyle::new(4.0).with_miter_limit(100.0),
+    );
+
+    assert!(unlimited.iter().sum::<u32>() > limited.iter().sum::<u32>());
+}
+
+#[test]
+fn stroke_closed_outline_is_hollow() {
+    let mut stroker = Stroker::new();
+    let square = [(4.0, 4.0), (24.0, 4.0), (24.0, 24.0), (4.0, 24.0)];
+    let hits = stroke_coverage(&mut stroker, &square, true, StrokeStyle::new(2.0));
+
+    assert!(!covered(&hits, 14, 14));
+    assert!(covered(&hits, 14, 4));
+    assert!(covered(&hits, 4, 14));
+    // Mitered corners
+    assert!(covered(&hits, 3, 3));
+    assert!(covered(&hits, 24, 24));
+    assert_eq!(hits.iter().sum::<u32>(), 22 * 22 - 18 * 18);
+}
+
+#[test]
+fn stroke_segments_strokes_each_pair() {
+    let mut stroker = Stroker::new();
+    let mut hits = vec![0u32; GRID * GRID];
+    let cross = points(&[(2.0, 10.0), (18.0, 10.0), (10.0, 2.0), (10.0, 18.0)]);
+    coverage(
+        stroker.stroke_segments(&cross, &StrokeStyle::new(2.0)),
+        &mut hits,
+    );
+
+    assert!(covered(&hits, 2, 9));
+    assert!(covered(&hits, 9, 2));
+    assert!(!covered(&hits, 4, 4));
+    // The segments overlap in the middle, NonZero fills it once.
+    assert!(hits.iter().all(|h| *h <= 1));
+    assert_eq!(hits.iter().sum::<u32>(), 16 * 2 * 2 - 4);
+}
@@ -0,0 +1,314 @@
+extern crate ranger;
+extern crate sdl2;
+
+use sdl2::rect::{Point, Rect};
+
+use ranger::rendering::blend::BlendMode;
+use ranger::rendering::color::Palette;
+use ranger::rendering::render_batch::{to_sdl_color, BatchState, BatchTarget, RenderBatch};
+
+#[derive(Debug, PartialEq)]
+enum Call {
+    State(BatchState),
+    Points(usize),
+    Polyline(Vec<Point>),
+    FilledRects(Vec<Rect>),
+    OutlinedRects(usize),
+}
+
+struct MockTarget {
+    calls: Vec<Call>,
+}
+
+impl MockTarget {
+    fn new() -> Self {
+        Self { calls: Vec::new() }
+    }
+}
+
+impl BatchTarget for MockTarget {
+    fn apply_state(&mut self, state: &BatchState) {
+        self.calls.push(Call::State(*state));
+    }
+
+    fn points(&mut self, points: &[Point]) {
+        self.calls.push(Call::Points(points.len()));
+    }
+
+    fn polyline(&mut self, points: &[Point]) {
+        self.calls.push(Call::Polyline(points.to_vec()));
+    }
+
+    fn filled_rects(&mut self, rects: &[Rect]) {
+        self.calls.push(Call::FilledRects(rects.to_vec()));
+    }
+
+    fn outlined_rects(&mut self, rects: &[Rect]) {
+        self.calls.push(Call::OutlinedRects(rects.len()));
+    }
+}
+
+#[test]
+fn batch_merges_same_state() {
+    let mut batch = RenderBatch::new();
+    let mut target = MockTarget::new();
+
+    batch.set_color(Palette::WHITE(255));
+    for y in 0..10 {
+        batch.line(0, y, 9, y);
+    }
+    assert_eq!(batch.len(), 1);
+
+    batch.flush(&mut target);
+    assert!(batch.is_empty());
+
+    assert_eq!(target.calls.len(), 2);
+    match target.calls[1] {
+        Call::FilledRects(ref rects) => {
+            assert_eq!(rects.len(), 10);
+            assert_eq!(rects[3], Rect::new(0, 3, 10, 1));
+        }
+        ref call => panic!("unexpected {:?}", call),
+    }
+
+    let stats = batch.stats();
+    assert_eq!(stats.draw_calls, 1);
+    assert_eq!(stats.batches, 1);
+    assert_eq!(stats.primitives, 10);
+}
+
+#[test]
+fn batch_splits_on_state_and_keeps_order() {
+    let mut batch = RenderBatch::new();
+    let mut target = MockTarget::new();
+
+    let red = Palette::RGB(255, 0, 0);
+    let green = Palette::RGB(0, 255, 0);
+
+    batch.set_color(red);
+    batch.fill_rect(0, 0, 4, 4);
+    batch.point(1, 1);
+    batch.set_color(green);
+    batch.point(2, 2);
+    batch.set_blend(BlendMode::Additive);
+    batch.point(3, 3);
+    batch.draw_rect(0, 0, 4, 4);
+
+    batch.flush(&mut target);
+
+    let red_state = BatchState {
+        color: red,
+        blend: BlendMode::Alpha,
+    };
+    let green_state = BatchState {
+        color: green,
+        blend: BlendMode::Alpha,
+    };
+    let additive_state = BatchState {
+        color: green,
+        blend: BlendMode::Additive,
+    };
+    assert_eq!(
+        target.calls,
+        vec![
+            Call::State(red_state),
+            Call::FilledRects(vec![Rect::new(0, 0, 4, 4)]),
+            Call::Points(1),
+            Call::State(green_state),
+            Call::Points(1),
+            Call::State(additive_state),
+            Call::Points(1),
+            Call::OutlinedRects(1),
+        ]
+    );
+
+    let stats = batch.take_stats();
+    assert_eq!(stats.state_changes, 3);
+    assert_eq!(stats.draw_calls, 5);
+    assert_eq!(stats.batches, 5);
+    assert_eq!(stats.primitives, 5);
+
+    // Taking the stats resets them.
+    assert_eq!(batch.stats().draw_calls, 0);
+}
+
+#[test]
+fn batch_state_persists_across_flushes() {
+    let mut batch = RenderBatch::new();
+    let mut target = MockTarget::new();
+
+    batch.set_color(Palette::WHITE(255));
+    batch.point(0, 0);
+    batch.flush(&mut target);
+    batch.point(1, 1);
+    batch.flush(&mut target);
+
+    // The state was applied once.
+    assert_eq!(target.calls.len(), 3);
+    assert_eq!(batch.stats().state_changes, 1);
+
+    batch.invalidate();
+    batch.point(2, 2);
+    batch.flush(&mut target);
+    assert_eq!(batch.stats().state_changes, 2);
+}
+
+#[test]
+fn batch_chains_connected_lines() {
+    let mut batch = RenderBatch::new();
+    let mut target = MockTarget::new();
+
+    // Two connected diagonals, then a disconnected one.
+    batch.line(0, 0, 5, 5);
+    batch.line(5, 5, 10, 0);
+    batch.line(20, 0, 25, 5);
+    assert_eq!(batch.len(), 1);
+
+    batch.flush(&mut target);
+
+    assert_eq!(
+        target.calls[1..],
+        [
+            Call::Polyline(vec![Point::new(0, 0), Point::new(5, 5), Point::new(10, 0)]),
+            Call::Polyline(vec![Point::new(20, 0), Point::new(25, 5)]),
+        ]
+    );
+    assert_eq!(batch.stats().draw_calls, 2);
+    assert_eq!(batch.stats().batches, 1);
+}
+
+#[test]
+fn sdl_multiply_matches_software_blend() {
+    // SDL's Mod computes src * dst and ignores the source alpha, so the
+    // color it's given must already account for it.
+    let sdl_mod = |src: [u8; 4], dst: [u8; 4]| {
+        let mul = |s: u8, d: u8| ((s as u32 * d as u32 + 127) / 255) as u8;
+        [
+            mul(src[0], dst[0]),
+            mul(src[1], dst[1]),
+            mul(src[2], dst[2]),
+            dst[3],
+        ]
+    };
+
+    let dst = [200, 120, 40, 255];
+    for &alpha in &[0, 1, 64, 128, 200, 255] {
+        let state = BatchState {
+            color: Palette::RGBA(30, 160, 255, alpha),
+            blend: BlendMode::Multiply,
+        };
+        let color = to_sdl_color(&state);
+        assert_eq!(color[3], 255);
+
+        let expected = BlendMode::Multiply.blend([30, 160, 255, alpha], dst);
+        let actual = sdl_mod(color, dst);
+        for i in 0..4 {
+            let diff = (expected[i] as i32 - actual[i] as i32).abs();
+            assert!(diff <= 1, "alpha {}: {:?} != {:?}", alpha, actual, expected);
+        }
+    }
+
+    // Other modes are passed through.
+    let state = BatchState {
+        color: Palette::RGBA(30, 160, 255, 64),
+        blend: BlendMode::Alpha,
+    };
+    assert_eq!(to_sdl_color(&state), [30, 160, 255, 64]);
+}
+
+#[test]
+fn batch_groups_colored_points_by_color() {
+    let mut batch = RenderBatch::new();
+    let mut target = MockTarget::new();
+
+    let light = Palette::RGBA(255, 255, 255, 64);
+    let dark = Palette::RGBA(255, 255, 255, 192);
+
+    // Alternating colors, as along an anti-aliased line.
+    for x in 0..8 {
+        let color = if x % 2 == 0 { light } else { dark };
+        batch.colored_point(x, 0, color);
+    }
+    assert_eq!(batch.len(), 1);
+
+    batch.flush(&mut target);
+
+    let state = |color| BatchState {
+        color,
+        blend: BlendMode::Alpha,
+    };
+    assert_eq!(
+        target.calls,
+        vec![
+            Call::State(state(light)),
+            Call::Points(4),
+            Call::State(state(dark)),
+            Call::Points(4),
+        ]
+    );
+
+    let stats = batch.stats();
+    assert_eq!(stats.state_changes, 2);
+    assert_eq!(stats.draw_calls, 2);
+    assert_eq!(stats.batches, 1);
+    assert_eq!(stats.primitives, 8);
+}
+
+#[test]
+fn batch_keeps_order_of_overlapping_colored_points() {
+    let mut batch = RenderBatch::new();
+    let mut target = MockTarget::new();
+
+    let red = Palette::RGBA(255, 0, 0, 128);
+    let blue = Palette::RGBA(0, 0, 255, 128);
+
+    // Drawing the same pixel again must not be reordered.
+    batch.colored_point(0, 0, red);
+    batch.colored_point(1, 0, blue);
+    batch.colored_point(0, 0, blue);
+    assert_eq!(batch.len(), 2);
+
+    // Nor merged across a blend change.
+    batch.set_blend(BlendMode::Additive);
+    batch.colored_point(2, 0, blue);
+    assert_eq!(batch.len(), 3);
+
+    // Colored points leave the batch's color alone.
+    assert_eq!(
+        *batch.state(),
+        BatchState {
+            color: Palette::BLACK(),
+            blend: BlendMode::Additive,
+        }
+    );
+
+    batch.flush(&mut target);
+
+    assert_eq!(
+        target.calls,
+        vec![
+            // The first run is grouped, in any order.
+            Call::State(BatchState {
+                color: blue,
+                blend: BlendMode::Alpha,
+            }),
+            Call::Points(1),
+            Call::State(BatchState {
+                color: red,
+                blend: BlendMode::Alpha,
+            }),
+            Call::Points(1),
+            // The pixel drawn again comes after it.
+            Call::State(BatchState {
+                color: blue,
+                blend: BlendMode::Alpha,
+            }),
+            Call::Points(1),
+            Call::State(BatchState {
+                color: blue,
+                blend: BlendMode::Additive,
+            }),
+            Call::Points(1),
+        ]
+    );
+}
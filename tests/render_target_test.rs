@@ -0,0 +1,50 @@
+extern crate ranger;
+
+use ranger::rendering::render_targets::{RenderTargets, GUARD_BAND};
+
+const WINDOW: (u32, u32) = (800, 600);
+
+#[test]
+fn render_targets_nest() {
+    let mut targets = RenderTargets::new();
+    assert!(targets.is_empty());
+    assert_eq!(targets.current(), None);
+
+    targets.push(3, 64, 32);
+    targets.push(5, 16, 8);
+    assert_eq!(targets.depth(), 2);
+    assert_eq!(targets.current(), Some(5));
+
+    // Ending the inner target goes back to the outer one, then the window.
+    assert!(targets.pop());
+    assert_eq!(targets.current(), Some(3));
+    assert!(targets.pop());
+    assert_eq!(targets.current(), None);
+
+    // An unmatched end is ignored.
+    assert!(!targets.pop());
+    assert!(targets.is_empty());
+}
+
+#[test]
+fn render_targets_device_size_and_clip_rect() {
+    let mut targets = RenderTargets::new();
+    assert_eq!(targets.device_size(WINDOW), WINDOW);
+
+    targets.push(1, 128, 64);
+    assert_eq!(targets.device_size(WINDOW), (128, 64));
+
+    let rect = targets.clip_rect(WINDOW);
+    assert_eq!((rect.min.x, rect.min.y), (-GUARD_BAND, -GUARD_BAND));
+    assert_eq!(
+        (rect.max.x, rect.max.y),
+        (128.0 + GUARD_BAND, 64.0 + GUARD_BAND)
+    );
+
+    targets.pop();
+    let rect = targets.clip_rect(WINDOW);
+    assert_eq!(
+        (rect.max.x, rect.max.y),
+        (800.0 + GUARD_BAND, 600.0 + GUARD_BAND)
+    );
+}
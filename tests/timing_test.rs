@@ -0,0 +1,371 @@
+extern crate ranger;
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+use ranger::config;
+use ranger::engine::timing::rates::{RateSettings, Rates, MAX_RATE};
+use ranger::engine::timing::scheduler::{Scheduler, TimingPriority};
+use ranger::nodes::node::{NodeTrait, OChildren, RNode};
+use ranger::nodes::node_properties::NodeData;
+use ranger::world::WorldProperties;
+
+// Records the dt of every update it gets. Can have children to stand in
+// for a scene.
+struct Recorder {
+    data: RefCell<NodeData>,
+    priority: TimingPriority,
+    dts: RefCell<Vec<f64>>,
+    children: OChildren,
+}
+
+impl NodeTrait for Recorder {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn data(&self) -> &RefCell<NodeData> {
+        &self.data
+    }
+
+    fn update(&self, dt: f64) {
+        self.dts.borrow_mut().push(dt);
+    }
+
+    fn priority(&self) -> TimingPriority {
+        self.priority
+    }
+
+    fn get_children(&self) -> &OChildren {
+        &self.children
+    }
+}
+
+fn recorder(id: usize, priority: TimingPriority, scheduler: &mut Scheduler) -> RNode {
+    let mut data = NodeData::new();
+    data.node.set_id(id);
+    let node: RNode = Rc::new(RefCell::new(Recorder {
+        data: RefCell::new(data),
+        priority,
+        dts: RefCell::new(Vec::new()),
+        children: Some(RefCell::new(Vec::new())),
+    }));
+    scheduler.register_timing_target(node.clone());
+    scheduler.schedule_timing_target(id);
+    node
+}
+
+fn dts(node: &RNode) -> Vec<f64> {
+    let node = node.borrow();
+    let recorder = node.as_any().downcast_ref::<Recorder>().unwrap();
+    let dts = recorder.dts.borrow().clone();
+    recorder.dts.borrow_mut().clear();
+    dts
+}
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn rates_periods() {
+    let mut settings = RateSettings::new();
+    settings.updates_per_second = 50;
+    settings.frames_per_second = 3;
+    assert_eq!(settings.update_period(), ms(20));
+    assert_eq!(settings.frame_period(), Duration::from_nanos(333_333_333));
+}
+
+#[test]
+fn rates_catch_up_is_capped() {
+    let mut settings = RateSettings::new();
+    settings.updates_per_second = 100;
+    settings.max_catch_up = 3;
+
+    // Not yet due.
+    assert_eq!(settings.catch_up(ms(9)), (0, ms(9)));
+    // Fractions carry over.
+    assert_eq!(settings.catch_up(ms(25)), (2, ms(5)));
+    assert_eq!(settings.catch_up(ms(30)), (3, ms(0)));
+    // A long stall runs 3 updates and drops the rest.
+    assert_eq!(settings.catch_up(ms(1004)), (3, ms(4)));
+}
+
+#[test]
+fn rates_shared_and_clamped() {
+    let rates = Rates::new(RateSettings::new());
+    let other = rates.clone();
+
+    other.set_updates_per_second(60);
+    other.set_frames_per_second(0);
+    other.set_limit_frame_rate(true);
+    other.set_max_catch_up(0);
+
+    let settings = rates.get();
+    assert_eq!(settings.updates_per_second, 60);
+    assert_eq!(settings.frames_per_second, 1);
+    assert!(settings.limit_frame_rate);
+    assert_eq!(settings.max_catch_up, 1);
+
+    other.set_updates_per_second(MAX_RATE * 10);
+    assert_eq!(rates.get().updates_per_second, MAX_RATE);
+}
+
+#[test]
+fn rates_from_config() {
+    let mut wp = WorldProperties::new();
+    let text = r#"{ "timing": { "updates_per_second": 60, "limit_frame_rate": true, "max_catch_up": 8 } }"#;
+    config::apply(text, &mut wp).unwrap();
+    assert_eq!(wp.updates_per_second, 60);
+    assert!(wp.limit_frame_rate);
+    assert_eq!(wp.max_catch_up, 8);
+
+    assert!(config::apply(r#"{ "timing": { "max_catch_up": 0 } }"#, &mut wp).is_err());
+    assert!(config::apply(r#"{ "timing": { "limit_frame_rate": 1 } }"#, &mut wp).is_err());
+}
+
+#[test]
+fn time_scales_game_time_not_system_time() {
+    let mut scheduler = Scheduler::new();
+    let system = recorder(1, TimingPriority::System, &mut scheduler);
+    let normal = recorder(2, TimingPriority::Normal, &mut scheduler);
+    let time = scheduler.time();
+
+    scheduler.update(10.0);
+    assert_eq!((dts(&system), dts(&normal)), (vec![10.0], vec![10.0]));
+
+    time.set_scale(0.5);
+    scheduler.update(10.0);
+    assert_eq!((dts(&system), dts(&normal)), (vec![10.0], vec![5.0]));
+
+    time.set_priority_scale(TimingPriority::Normal, 4.0);
+    time.set_priority_scale(TimingPriority::System, 0.25);
+    scheduler.update(10.0);
+    assert_eq!((dts(&system), dts(&normal)), (vec![2.5], vec![20.0]));
+
+    time.set_scale(-1.0);
+    assert_eq!(time.scale(), 0.0);
+    assert_eq!(time.priority_scale(TimingPriority::Normal), 4.0);
+}
+
+#[test]
+fn time_pause_and_step() {
+    let mut scheduler = Scheduler::new();
+    let system = recorder(1, TimingPriority::System, &mut scheduler);
+    let normal = recorder(2, TimingPriority::Normal, &mut scheduler);
+    let time = scheduler.time();
+
+    // Stepping only applies while paused.
+    time.step();
+    time.pause(true);
+    assert!(time.paused());
+    scheduler.update(10.0);
+    scheduler.update(10.0);
+    assert_eq!(dts(&system).len(), 2);
+    assert!(dts(&normal).is_empty());
+
+    time.set_scale(2.0);
+    time.step();
+    time.step();
+    for _ in 0..3 {
+        scheduler.update(10.0);
+    }
+    assert_eq!(dts(&system).len(), 3);
+    assert_eq!(dts(&normal), vec![20.0, 20.0]);
+
+    // Unused steps don't survive a resume and pause.
+    time.step();
+    time.toggle_pause();
+    time.toggle_pause();
+    scheduler.update(10.0);
+    assert!(dts(&normal).is_empty());
+
+    time.pause(false);
+    scheduler.update(10.0);
+    assert_eq!(dts(&normal), vec![20.0]);
+}
+
+// Counts calls through a shared cell.
+fn counter() -> (Rc<Cell<usize>>, impl FnMut(f64)) {
+    let count = Rc::new(Cell::new(0));
+    let inner = count.clone();
+    (count, move |_dt| inner.set(inner.get() + 1))
+}
+
+#[test]
+fn timers_once_repeat_and_every_frame() {
+    let mut scheduler = Scheduler::new();
+
+    let (once, callback) = counter();
+    let once_handle = scheduler.after(25.0, callback);
+    let (repeat, callback) = counter();
+    let repeat_handle = scheduler.every(10.0, Some(3), callback);
+    let (forever, callback) = counter();
+    scheduler.every(10.0, None, callback);
+    let frame_dts = Rc::new(RefCell::new(Vec::new()));
+    let inner = frame_dts.clone();
+    scheduler.every_frame(move |dt| inner.borrow_mut().push(dt));
+    assert_eq!(scheduler.timers().len(), 4);
+
+    scheduler.update(10.0);
+    scheduler.update(10.0);
+    assert_eq!((once.get(), repeat.get(), forever.get()), (0, 2, 2));
+    scheduler.update(10.0);
+    assert_eq!((once.get(), repeat.get(), forever.get()), (1, 3, 3));
+    assert!(!once_handle.is_active());
+    assert!(!repeat_handle.is_active());
+    assert_eq!(repeat_handle.fire_count(), 3);
+
+    // Finished timers are dropped, a long update catches repeats up.
+    scheduler.update(35.0);
+    assert_eq!((once.get(), repeat.get(), forever.get()), (1, 3, 6));
+    assert_eq!(scheduler.timers().len(), 2);
+    assert_eq!(*frame_dts.borrow(), vec![10.0, 10.0, 10.0, 35.0]);
+}
+
+#[test]
+fn timers_cancel_pause_and_game_time() {
+    let mut scheduler = Scheduler::new();
+    let time = scheduler.time();
+
+    let (game, callback) = counter();
+    let game_handle = scheduler.every(10.0, None, callback);
+    let (real, callback) = counter();
+    scheduler
+        .every(10.0, None, callback)
+        .set_priority(TimingPriority::System);
+
+    // Game time timers stop while paused and slow down with the scale.
+    time.pause(true);
+    scheduler.update(10.0);
+    assert_eq!((game.get(), real.get()), (0, 1));
+    time.pause(false);
+    time.set_scale(0.5);
+    scheduler.update(10.0);
+    scheduler.update(10.0);
+    assert_eq!((game.get(), real.get()), (1, 3));
+
+    game_handle.pause(true);
+    assert!(game_handle.paused());
+    scheduler.update(20.0);
+    assert_eq!(game.get(), 1);
+    game_handle.pause(false);
+    scheduler.update(20.0);
+    assert_eq!(game.get(), 2);
+
+    game_handle.cancel();
+    scheduler.update(100.0);
+    assert_eq!(game.get(), 2);
+    assert!(!game_handle.is_active());
+}
+
+#[test]
+fn timers_scheduled_from_callbacks() {
+    let mut scheduler = Scheduler::new();
+    let timers = scheduler.timers();
+    let (count, callback) = counter();
+    let callback = RefCell::new(Some(callback));
+
+    // A one-shot that schedules another one-shot.
+    scheduler.after(5.0, move |_| {
+        if let Some(callback) = callback.borrow_mut().take() {
+            timers.after(5.0, callback);
+        }
+    });
+
+    scheduler.update(5.0);
+    assert_eq!(count.get(), 0);
+    assert_eq!(scheduler.timers().len(), 1);
+    scheduler.update(5.0);
+    assert_eq!(count.get(), 1);
+    assert!(scheduler.timers().is_empty());
+}
+
+#[test]
+fn timers_bound_to_nodes() {
+    let mut scheduler = Scheduler::new();
+    let kept = recorder(1, TimingPriority::Normal, &mut scheduler);
+    let removed = recorder(2, TimingPriority::Normal, &mut scheduler);
+
+    let (a, callback) = counter();
+    scheduler.every_frame(callback).bind(&kept);
+    let (b, callback) = counter();
+    scheduler.every_frame(callback).bind(&removed);
+    let (c, callback) = counter();
+    let dropped = {
+        let node = recorder(3, TimingPriority::Normal, &mut Scheduler::new());
+        scheduler.every_frame(callback).bind(&node);
+        scheduler.update(1.0);
+        node
+    };
+    assert_eq!((a.get(), b.get(), c.get()), (1, 1, 1));
+
+    // Leaving the scheduler or being dropped cleans the node's timers up.
+    scheduler.unschedule_timing_target_by_id(2);
+    drop(dropped);
+    scheduler.update(1.0);
+    assert_eq!((a.get(), b.get(), c.get()), (2, 1, 1));
+    assert_eq!(scheduler.timers().len(), 1);
+}
+
+#[test]
+fn timers_cancelled_from_callbacks() {
+    let mut scheduler = Scheduler::new();
+    let first = recorder(1, TimingPriority::Normal, &mut scheduler);
+    let last = recorder(2, TimingPriority::Normal, &mut scheduler);
+
+    let (a, callback) = counter();
+    scheduler.every_frame(callback).bind(&first);
+    let timers = scheduler.timers();
+    scheduler.after(1.0, move |_| {
+        timers.cancel_bound(1);
+        timers.cancel_bound(2);
+    });
+    let (b, callback) = counter();
+    scheduler.every_frame(callback).bind(&last);
+
+    // The timer after the cancelling one doesn't run, the one before it
+    // has run but is gone by the next update.
+    scheduler.update(1.0);
+    assert_eq!((a.get(), b.get()), (1, 0));
+    scheduler.update(1.0);
+    assert_eq!((a.get(), b.get()), (1, 0));
+    assert!(scheduler.timers().is_empty());
+
+    // Timers bound after the update aren't affected.
+    let (c, callback) = counter();
+    scheduler.every_frame(callback).bind(&first);
+    scheduler.update(1.0);
+    assert_eq!(c.get(), 1);
+}
+
+#[test]
+fn timers_cancelled_when_scene_exits() {
+    let mut scheduler = Scheduler::new();
+    let old_scene = recorder(1, TimingPriority::Normal, &mut scheduler);
+    let child = recorder(2, TimingPriority::Normal, &mut scheduler);
+    old_scene.borrow().add_child(child.clone());
+    let new_scene = recorder(3, TimingPriority::Normal, &mut scheduler);
+
+    let (a, callback) = counter();
+    scheduler.every_frame(callback).bind(&old_scene);
+    let (b, callback) = counter();
+    scheduler.every(1.0, None, callback).bind(&child);
+    let (c, callback) = counter();
+    scheduler.every_frame(callback).bind(&new_scene);
+    scheduler.update(1.0);
+    assert_eq!((a.get(), b.get(), c.get()), (1, 1, 1));
+
+    // What the SceneManager does when a pushed scene replaces the running
+    // one. The old scene's nodes are still alive on the scene stack.
+    scheduler.timers().cancel_bound_tree(&old_scene);
+    scheduler.update(1.0);
+    assert_eq!((a.get(), b.get(), c.get()), (1, 1, 2));
+    assert_eq!(scheduler.timers().len(), 1);
+}